@@ -0,0 +1,43 @@
+//! Compresses the curated word lists in `bundled/` for embedding when the
+//! `bundled-words` feature is enabled, and embeds the public key named by
+//! `PHRASEFORGE_TRUST_KEY`, if set, as the default `--trust-key`.
+
+fn main() {
+    #[cfg(feature = "bundled-words")]
+    compress_bundled_words();
+    bundle_trust_key();
+}
+
+fn bundle_trust_key() {
+    println!("cargo:rerun-if-env-changed=PHRASEFORGE_TRUST_KEY");
+    println!("cargo:rustc-check-cfg=cfg(bundled_trust_key)");
+    let Ok(key) = std::env::var("PHRASEFORGE_TRUST_KEY") else {
+        return;
+    };
+    println!("cargo:rerun-if-changed={}", key);
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    std::fs::copy(&key, std::path::Path::new(&out_dir).join("trust-key.asc"))
+        .expect("Failed to read the PHRASEFORGE_TRUST_KEY file");
+    println!("cargo:rustc-cfg=bundled_trust_key");
+}
+
+#[cfg(feature = "bundled-words")]
+fn compress_bundled_words() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::path::Path;
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    for file in ["adjectives.txt", "nouns.txt", "verbs.txt", "adverbs.txt"] {
+        let source = Path::new("bundled").join(file);
+        println!("cargo:rerun-if-changed={}", source.display());
+        let text = std::fs::read(&source).expect("Failed to read bundled word list");
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&text).unwrap();
+        let compressed = encoder.finish().unwrap();
+        std::fs::write(Path::new(&out_dir).join(format!("{}.gz", file)), compressed)
+            .expect("Failed to write compressed word list");
+    }
+}
@@ -0,0 +1,63 @@
+//! `--abbrev`: a phrase's initials, e.g. `7bbbb` for
+//! `7-brave-badgers-bounce-briskly`.
+//!
+//! Many people keep such an abbreviation written down as a hint that means
+//! nothing to anyone else. Numbers are kept whole and each word contributes
+//! its first character exactly as it appears, capital or leetspeak digit.
+
+use crate::template::Slot;
+use unicode_segmentation::UnicodeSegmentation;
+
+fn initial(word: &str) -> &str {
+    word.graphemes(true).next().unwrap_or("")
+}
+
+/// The initials of `text`, using the rendered template `pieces` while `text`
+/// is still their concatenation. Once mutations or policy repairs have
+/// changed it, each run of letters and digits between separators counts as
+/// a word instead.
+pub fn initials(pieces: &[(Option<Slot>, String)], text: &str) -> String {
+    if pieces
+        .iter()
+        .map(|(_, piece)| piece.as_str())
+        .collect::<String>()
+        == text
+    {
+        return pieces
+            .iter()
+            .filter_map(|(slot, piece)| match slot {
+                Some(Slot::Number) => Some(piece.as_str()),
+                Some(_) => Some(initial(piece)),
+                None => None,
+            })
+            .collect();
+    }
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            if word.chars().all(|c| c.is_ascii_digit()) {
+                word
+            } else {
+                initial(word)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_numbers_and_capitals() {
+        let pieces = vec![
+            (Some(Slot::Number), "42".to_string()),
+            (None, "-".to_string()),
+            (Some(Slot::Adjective), "Brave".to_string()),
+            (None, "-".to_string()),
+            (Some(Slot::Noun), "8adgers".to_string()),
+        ];
+        assert_eq!(initials(&pieces, "42-Brave-8adgers"), "42B8");
+        assert_eq!(initials(&pieces, "42-Brave-badgers!"), "42Bb");
+    }
+}
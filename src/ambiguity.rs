@@ -0,0 +1,50 @@
+//! Avoiding glyphs that are easily confused when read off paper.
+//!
+//! A phrase printed or handwritten for someone else to type is only as good
+//! as its worst-looking letter: "rn" reads as "m", "cl" as "d", and "1" and
+//! "0" pass for "l" and "O". `--unambiguous` drops words containing any of
+//! these sequences and draws numbers without them. The config file can
+//! replace the list with an `[unambiguous]` table.
+
+use crate::filter::WordFilter;
+use crate::PartOfSpeech;
+
+/// Sequences avoided when the config file doesn't say otherwise.
+pub const DEFAULT_SEQUENCES: [&str; 6] = ["rn", "cl", "vv", "nn", "1", "0"];
+
+#[derive(Debug, Clone)]
+pub struct Ambiguity {
+    sequences: Vec<String>,
+}
+
+impl Default for Ambiguity {
+    fn default() -> Self {
+        Ambiguity {
+            sequences: DEFAULT_SEQUENCES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Ambiguity {
+    pub fn new(sequences: Vec<String>) -> Self {
+        Ambiguity { sequences }
+    }
+
+    /// Whether `text` is free of every confusable sequence.
+    pub fn allows(&self, text: &str) -> bool {
+        !self
+            .sequences
+            .iter()
+            .any(|sequence| text.contains(sequence.as_str()))
+    }
+}
+
+impl WordFilter for Ambiguity {
+    fn name(&self) -> String {
+        "unambiguous".to_string()
+    }
+
+    fn keep(&self, _pos: PartOfSpeech, word: &str) -> bool {
+        self.allows(word)
+    }
+}
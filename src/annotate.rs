@@ -0,0 +1,90 @@
+//! `--annotate`: how common each word of a phrase is, to judge its
+//! memorability and tune `--min-frequency` against.
+//!
+//! A word's rank is its place in the pool it was drawn from under the
+//! current `--min-frequency`, most frequent first. Its rating goes by its
+//! count in the frequency list, so the bounds double as `--min-frequency`
+//! values: `--min-frequency 20000` leaves no rare words.
+
+use crate::generate::eligible;
+use crate::template::Slot;
+use crate::WordEntry;
+
+/// Counts at and above which a word is common, and uncommon.
+const COMMON: u32 = 100_000;
+const UNCOMMON: u32 = 20_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Difficulty {
+    pub fn of(frequency: u32) -> Difficulty {
+        match frequency {
+            COMMON.. => Difficulty::Common,
+            UNCOMMON.. => Difficulty::Uncommon,
+            _ => Difficulty::Rare,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Common => "common",
+            Difficulty::Uncommon => "uncommon",
+            Difficulty::Rare => "rare",
+        }
+    }
+}
+
+/// A line such as `badger (noun): rank 1204 of 9880, frequency 23511,
+/// uncommon`, for `word` drawn for `slot` from `entries`.
+pub fn describe(slot: Slot, word: &str, entries: &[WordEntry], min_frequency: &u32) -> String {
+    let frequencies: Vec<u32> = eligible(entries, min_frequency, None)
+        .map(|entry| entry.frequency)
+        .collect();
+    let Some(frequency) = entries
+        .iter()
+        .find(|entry| entry.word == word)
+        .map(|entry| entry.frequency)
+    else {
+        return format!("{} ({}): not in the word list", word, slot.name());
+    };
+    let rank = 1 + frequencies.iter().filter(|&&f| f > frequency).count();
+    format!(
+        "{} ({}): rank {} of {}, frequency {}, {}",
+        word,
+        slot.name(),
+        rank,
+        frequencies.len(),
+        frequency,
+        Difficulty::of(frequency).name()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_words_within_their_pool() {
+        let entries: Vec<WordEntry> = [("otter", 150_000), ("badger", 30_000), ("vole", 12_000)]
+            .iter()
+            .map(|&(word, frequency)| WordEntry {
+                word: word.to_string(),
+                frequency,
+            })
+            .collect();
+        assert_eq!(
+            describe(Slot::Noun, "badger", &entries, &10_000),
+            "badger (noun): rank 2 of 3, frequency 30000, uncommon"
+        );
+        assert_eq!(
+            describe(Slot::Noun, "otter", &entries, &20_000),
+            "otter (noun): rank 1 of 2, frequency 150000, common"
+        );
+        assert_eq!(Difficulty::of(12_000), Difficulty::Rare);
+    }
+}
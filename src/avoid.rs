@@ -0,0 +1,162 @@
+//! `--avoid-file`: never issue a phrase that was issued before.
+//!
+//! The file holds one Argon2 hash per issued phrase, all under one random
+//! salt stored in its first line, so checking a candidate costs a single
+//! Argon2 computation however long the file grows, while guessing the
+//! phrases behind it costs one per guess. Candidates found in it are
+//! regenerated, and the phrases a run issues are appended once printed, so
+//! the file can be shared across an organization (or kept beside the
+//! `--log-history` history, via `[history] avoid_file`).
+//!
+//! ```text
+//! phraseforge-avoid v1 9f3c...        (the salt)
+//! 5e0b...                             (one hash per issued phrase)
+//! ```
+
+use crate::codec::{parse_hex, to_hex};
+use argon2::Argon2;
+use rand::Rng;
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+const HEADER: &str = "phraseforge-avoid v1";
+const SALT_BYTES: usize = 16;
+
+pub type Hash = [u8; 32];
+
+pub struct AvoidList {
+    pub path: PathBuf,
+    salt: Vec<u8>,
+    issued: HashSet<Hash>,
+    /// Issued by this run and not yet saved.
+    pending: Vec<Hash>,
+    /// Whether the file must be created, header first.
+    new: bool,
+}
+
+impl AvoidList {
+    /// Reads the file at `path`, or starts one with a fresh salt if there
+    /// is none yet.
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let mut salt = vec![0; SALT_BYTES];
+                rand::rng().fill(&mut salt[..]);
+                return Ok(AvoidList {
+                    path,
+                    salt,
+                    issued: HashSet::new(),
+                    pending: Vec::new(),
+                    new: true,
+                });
+            }
+            Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+        };
+        let (salt, issued) = parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(AvoidList {
+            path,
+            salt,
+            issued,
+            pending: Vec::new(),
+            new: false,
+        })
+    }
+
+    pub fn hash(&self, phrase: &str) -> Hash {
+        let mut hash = [0; 32];
+        Argon2::default()
+            .hash_password_into(phrase.as_bytes(), &self.salt, &mut hash)
+            .expect("Argon2 hashes any phrase");
+        hash
+    }
+
+    /// Records a phrase as issued; false if it already was.
+    pub fn insert(&mut self, hash: Hash) -> bool {
+        let new = self.issued.insert(hash);
+        if new {
+            self.pending.push(hash);
+        }
+        new
+    }
+
+    /// Appends the phrases this run issued.
+    pub fn save(&mut self) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        if self.new {
+            lines.push_str(&format!("{} {}\n", HEADER, to_hex(&self.salt)));
+        }
+        for hash in &self.pending {
+            lines.push_str(&to_hex(hash));
+            lines.push('\n');
+        }
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| format!("cannot append to {}: {}", self.path.display(), e))?;
+        self.pending.clear();
+        self.new = false;
+        Ok(())
+    }
+}
+
+fn parse(text: &str) -> Result<(Vec<u8>, HashSet<Hash>), String> {
+    let mut lines = text.lines();
+    let salt = lines
+        .next()
+        .and_then(|line| line.strip_prefix(HEADER))
+        .and_then(|salt| parse_hex(salt).ok())
+        .ok_or_else(|| format!("not an avoid file (expected a '{} <salt>' line)", HEADER))?;
+    let mut issued = HashSet::new();
+    for (number, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let hash = parse_hex(line)
+            .ok()
+            .and_then(|bytes| Hash::try_from(bytes).ok())
+            .ok_or_else(|| format!("line {} is not a hash", number + 2))?;
+        issued.insert(hash);
+    }
+    Ok((salt, issued))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_issued_phrases_across_runs() {
+        let path = std::env::temp_dir().join(format!("phraseforge-avoid-{}", std::process::id()));
+        let mut first = AvoidList::load(path.clone()).unwrap();
+        let otter = first.hash("7-brave-otters-sing");
+        assert!(first.insert(otter));
+        assert!(!first.insert(otter));
+        first.save().unwrap();
+
+        let mut second = AvoidList::load(path.clone()).unwrap();
+        assert!(!second.insert(second.hash("7-brave-otters-sing")));
+        let badger = second.hash("8-brave-badgers-sing");
+        assert!(second.insert(badger));
+        second.save().unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(text.matches(HEADER).count(), 1);
+    }
+}
@@ -0,0 +1,287 @@
+//! `phraseforge bootstrap --from-dir /seed`: builds the word list cache from
+//! sources fetched ahead of time, for Dockerfiles and CI images that build
+//! without network access.
+//!
+//! The seed directory holds the files under the names a `--mirror` serves,
+//! e.g. `WNdb-3.0.tar.gz` and `en_full.txt`, plus optionally the EFF and
+//! common password lists, which are installed alongside. A `SHA256SUMS` file
+//! in `sha256sum` format, if present, is checked first. Nothing prompts and
+//! nothing is downloaded: every step reports to one JSON document on stdout,
+//! and any problem stops the build and makes the exit status nonzero.
+
+use crate::cache::{self, GenerationParams};
+use crate::corpus::COMMON_PASSWORDS_FILE;
+use crate::datadir;
+use crate::diceware::{EFF_LARGE_WORDLIST_FILE, EFF_LARGE_WORDLIST_SIZE};
+use crate::download;
+use crate::frequency;
+use crate::moby;
+use crate::scowl::{self, SCOWL_ARCHIVE};
+use crate::sources::Sources;
+use crate::transcript::sha256_file;
+use crate::WORDNET_ARCHIVE;
+use crate::{install_word_lists, install_wordnet_dictionary, staging_dir, validate_word_list};
+use crate::{wordnet_synset_file, GENERATED_FILES, MIN_WORD_LIST_ENTRIES, PROPER_NOUNS_FILE};
+use serde::Serialize;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+const CHECKSUMS_FILE: &str = "SHA256SUMS";
+
+#[derive(Debug, Serialize)]
+pub struct Diagnostic {
+    pub check: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub ok: bool,
+    pub seed: String,
+    pub data_dir: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    fn line(&mut self, check: impl Display, result: Result<String, String>) -> bool {
+        let ok = result.is_ok();
+        self.ok &= ok;
+        self.diagnostics.push(Diagnostic {
+            check: check.to_string(),
+            ok,
+            detail: result.unwrap_or_else(|e| e),
+        });
+        ok
+    }
+}
+
+/// The seed files the configured dictionary and frequency list are built
+/// from, by the names a mirror serves them under.
+fn required_files(sources: &Sources) -> Vec<String> {
+    let mut files: Vec<String> = sources
+        .dictionary_sources()
+        .into_iter()
+        .map(|(name, _)| match name {
+            "WordNet" => WORDNET_ARCHIVE.to_string(),
+            "SCOWL" => SCOWL_ARCHIVE.to_string(),
+            _ => moby::POS_FILE.to_string(),
+        })
+        .collect();
+    files.push(sources.frequency_list_file());
+    files
+}
+
+/// Checks every file listed in the seed's `SHA256SUMS`.
+fn check_sums(seed: &Path, report: &mut Report) {
+    let Ok(text) = fs::read_to_string(seed.join(CHECKSUMS_FILE)) else {
+        report.line(
+            "seed checksums",
+            Ok(format!(
+                "no {}; the seed files are not verified",
+                CHECKSUMS_FILE
+            )),
+        );
+        return;
+    };
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let Some((expected, file)) = line.split_once(char::is_whitespace) else {
+            report.line(
+                "seed checksums",
+                Err(format!("{} line is malformed: {:?}", CHECKSUMS_FILE, line)),
+            );
+            continue;
+        };
+        // `sha256sum` marks binary-mode entries with a leading `*`.
+        let file = file.trim_start().trim_start_matches('*');
+        let result = match sha256_file(&seed.join(file)) {
+            Some(actual) if actual.eq_ignore_ascii_case(expected) => Ok(actual),
+            Some(actual) => Err(format!("SHA-256 is {}, expected {}", actual, expected)),
+            None => Err(format!("listed in {} but unreadable", CHECKSUMS_FILE)),
+        };
+        report.line(format!("sha256 {}", file), result);
+    }
+}
+
+/// Reads the seed's frequency list the way the word lists are built from it.
+fn check_frequency_list(path: &Path) -> Result<String, String> {
+    let file = File::open(path).map_err(|e| format!("unreadable: {}", e))?;
+    let parsed = frequency::parse(BufReader::new(file));
+    if parsed.entries.len() < MIN_WORD_LIST_ENTRIES {
+        return Err(format!(
+            "{} entries ({} lines skipped), expected at least {}",
+            parsed.entries.len(),
+            parsed.skipped.len(),
+            MIN_WORD_LIST_ENTRIES
+        ));
+    }
+    Ok(format!("{} entries", parsed.entries.len()))
+}
+
+fn check_eff_word_list(path: &Path) -> Result<String, String> {
+    let lines = fs::read_to_string(path)
+        .map_err(|e| format!("unreadable: {}", e))?
+        .lines()
+        .count();
+    if lines != EFF_LARGE_WORDLIST_SIZE {
+        return Err(format!(
+            "{} words, expected {}",
+            lines, EFF_LARGE_WORDLIST_SIZE
+        ));
+    }
+    Ok(format!("{} words", lines))
+}
+
+/// Copies `file` from the seed into `data_dir`, unpacking the dictionary
+/// archives by way of `staging`.
+fn install(seed: &Path, data_dir: &Path, staging: &Path, file: &str) -> Result<String, String> {
+    let source = seed.join(file);
+    let unpack = |keep: fn(&Path) -> bool| {
+        File::open(&source)
+            .and_then(|archive| download::unpack(archive, staging, keep))
+            .map_err(|e| format!("cannot unpack: {}", e))
+    };
+    if file == WORDNET_ARCHIVE {
+        unpack(wordnet_synset_file)?;
+        if !staging.join("dict").join("index.noun").exists() {
+            return Err("the archive has no dict/index.noun".to_string());
+        }
+        install_wordnet_dictionary(data_dir, staging);
+    } else if file == SCOWL_ARCHIVE {
+        unpack(scowl::word_list_file)?;
+        scowl::install(data_dir, staging);
+    } else {
+        fs::copy(&source, data_dir.join(file)).map_err(|e| format!("cannot copy: {}", e))?;
+    }
+    Ok(format!("installed in {}", data_dir.display()))
+}
+
+/// Builds the cache in `data_dir` from `seed`, stopping at the first stage
+/// with a problem.
+pub fn run(seed: &Path, data_dir: &Path, sources: &Sources, params: &GenerationParams) -> Report {
+    let mut report = Report {
+        ok: true,
+        seed: seed.display().to_string(),
+        data_dir: data_dir.display().to_string(),
+        diagnostics: Vec::new(),
+    };
+    report.line(
+        "data directory",
+        datadir::writable(data_dir)
+            .map(|()| "writable".to_string())
+            .map_err(|e| format!("not writable: {}", e)),
+    );
+
+    let required = required_files(sources);
+    let optional = [EFF_LARGE_WORDLIST_FILE, COMMON_PASSWORDS_FILE]
+        .map(str::to_string)
+        .into_iter()
+        .filter(|file| seed.join(file).is_file());
+    let files: Vec<String> = required.into_iter().chain(optional).collect();
+    for file in &files {
+        let result = match fs::metadata(seed.join(file)) {
+            Ok(metadata) if metadata.len() > 0 => Ok(format!("{} bytes", metadata.len())),
+            Ok(_) => Err("empty".to_string()),
+            Err(e) => Err(format!("missing from the seed: {}", e)),
+        };
+        report.line(format!("seed {}", file), result);
+    }
+    if !report.ok {
+        return report;
+    }
+
+    check_sums(seed, &mut report);
+    let frequency_list = sources.frequency_list_file();
+    report.line(
+        format!("parse {}", frequency_list),
+        check_frequency_list(&seed.join(&frequency_list)),
+    );
+    if files.iter().any(|file| file == EFF_LARGE_WORDLIST_FILE) {
+        report.line(
+            format!("parse {}", EFF_LARGE_WORDLIST_FILE),
+            check_eff_word_list(&seed.join(EFF_LARGE_WORDLIST_FILE)),
+        );
+    }
+    if !report.ok {
+        return report;
+    }
+
+    cache::invalidate(data_dir);
+    let staging = staging_dir(data_dir);
+    for file in &files {
+        if !report.line(
+            format!("install {}", file),
+            install(seed, data_dir, &staging, file),
+        ) {
+            return report;
+        }
+    }
+
+    install_word_lists(data_dir, sources, params, &staging, false);
+    for file in GENERATED_FILES {
+        let min_entries = if file == PROPER_NOUNS_FILE {
+            0
+        } else {
+            MIN_WORD_LIST_ENTRIES
+        };
+        let path = data_dir.join(file);
+        let result = validate_word_list(&path, min_entries).map(|()| {
+            let count = fs::read_to_string(&path)
+                .map(|text| text.lines().count())
+                .unwrap_or(0);
+            format!("{} words", count)
+        });
+        report.line(format!("generate {}", file), result);
+    }
+    if !report.ok {
+        return report;
+    }
+
+    cache::write_manifest(data_dir, &sources.word_list_sources(), params);
+    let _ = fs::remove_dir_all(&staging);
+    let result = match cache::corrupted_files(data_dir) {
+        Some((checked, corrupted)) if corrupted.is_empty() => {
+            Ok(format!("{} files match the manifest", checked))
+        }
+        Some((_, corrupted)) => Err(format!("{} differ from the manifest", corrupted.join(", "))),
+        None => Err("the manifest was not written".to_string()),
+    };
+    report.line("checksums", result);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_seed_files_without_installing_anything() {
+        let seed = std::env::temp_dir().join(format!("phraseforge-seed-{}", std::process::id()));
+        let data_dir = seed.join("cache");
+        fs::create_dir_all(&data_dir).unwrap();
+        let sources = Sources::new(
+            &crate::cli().get_matches_from(["phraseforge"]),
+            &Default::default(),
+        )
+        .unwrap();
+        let params = crate::generation_params(&sources, false);
+
+        let report = run(&seed, &data_dir, &sources, &params);
+        fs::remove_dir_all(&seed).unwrap();
+
+        assert!(!report.ok);
+        let missing: Vec<&str> = report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| !diagnostic.ok)
+            .map(|diagnostic| diagnostic.check.as_str())
+            .collect();
+        assert_eq!(missing, ["seed WNdb-3.0.tar.gz", "seed en_full.txt"]);
+        assert!(!report
+            .diagnostics
+            .iter()
+            .any(|d| d.check.starts_with("install")));
+    }
+}
@@ -0,0 +1,75 @@
+//! A small curated word list compiled into the binary.
+//!
+//! With the `bundled-words` feature, phrases can be generated on first run
+//! without any network access. The lists are a few hundred common, concrete
+//! words per part of speech, so phrases drawn from them carry fewer bits than
+//! ones drawn from the full downloaded lists, which are always preferred
+//! once cached.
+
+use crate::transcript::SourceRecord;
+use crate::WordLists;
+
+/// Whether this build carries the bundled lists.
+pub const AVAILABLE: bool = cfg!(feature = "bundled-words");
+
+#[cfg(feature = "bundled-words")]
+mod data {
+    use crate::{frequency, word_entries, WordEntry, WordLists, WordType};
+    use flate2::read::GzDecoder;
+    use std::io::BufReader;
+
+    macro_rules! bundled {
+        ($file:literal) => {
+            include_bytes!(concat!(env!("OUT_DIR"), "/", $file, ".gz"))
+        };
+    }
+
+    const ADJECTIVES: &[u8] = bundled!("adjectives.txt");
+    const NOUNS: &[u8] = bundled!("nouns.txt");
+    const VERBS: &[u8] = bundled!("verbs.txt");
+    const ADVERBS: &[u8] = bundled!("adverbs.txt");
+
+    fn decode(compressed: &[u8]) -> Vec<WordEntry> {
+        word_entries(frequency::parse(BufReader::new(GzDecoder::new(compressed))))
+    }
+
+    pub fn word_lists() -> WordLists {
+        WordLists {
+            adjectives: WordType::Adjective(decode(ADJECTIVES)),
+            nouns: WordType::Noun(decode(NOUNS)),
+            verbs: WordType::Verb(decode(VERBS)),
+            adverbs: WordType::Adverb(decode(ADVERBS)),
+        }
+    }
+}
+
+/// The bundled lists, or `None` when this build doesn't carry them.
+pub fn word_lists() -> Option<WordLists> {
+    #[cfg(feature = "bundled-words")]
+    return Some(data::word_lists());
+    #[cfg(not(feature = "bundled-words"))]
+    None
+}
+
+/// How a transcript records that the bundled lists were used.
+pub fn source_record() -> SourceRecord {
+    SourceRecord {
+        name: "bundled-words".to_string(),
+        location: format!("phraseforge {}", env!("CARGO_PKG_VERSION")),
+        sha256: None,
+    }
+}
+
+#[cfg(all(test, feature = "bundled-words"))]
+mod tests {
+    use super::*;
+    use crate::template::Slot;
+
+    #[test]
+    fn every_pool_decodes() {
+        let lists = word_lists().unwrap();
+        for slot in [Slot::Adjective, Slot::Noun, Slot::Adverb] {
+            assert!(lists.pool(slot).unwrap().len() >= 100);
+        }
+    }
+}
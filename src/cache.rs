@@ -0,0 +1,210 @@
+//! Versioned on-disk cache layout.
+//!
+//! Everything lives under `<data dir>/v2/`, alongside a `manifest.json`
+//! recording where each source came from, what every file should hash to and
+//! the parameters the word lists were generated with. The manifest is written
+//! last and removed before any rebuild starts, so a cache without one was
+//! interrupted and gets rebuilt. Caches from older layouts are migrated on
+//! first use.
+
+use crate::transcript::sha256_file;
+use crate::update::{self, write_atomically};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CACHE_VERSION: u32 = 2;
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Everything the version 1 layout kept directly in the data directory.
+const V1_ENTRIES: [&str; 9] = [
+    "dict",
+    "en_full.txt",
+    "eff_large_wordlist.txt",
+    "sources.json",
+    "adjectives.txt",
+    "nouns.txt",
+    "verbs.txt",
+    "adverbs.txt",
+    "proper_nouns.txt",
+];
+
+/// Files managed on their own schedule (`dice` downloads the EFF list on
+/// demand; `update` rewrites its validators), so not part of the manifest.
+const UNTRACKED: [&str; 2] = ["eff_large_wordlist.txt", "sources.json"];
+
+/// Settings that change the generated word lists; a mismatch means they must
+/// be regenerated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    pub minimum_word_length: usize,
+    #[serde(default = "default_frequency_list")]
+    pub frequency_list: String,
+    #[serde(default = "default_dictionary")]
+    pub dictionary: String,
+    /// Each word kept in only its most used part of speech.
+    #[serde(default)]
+    pub assign_part_of_speech: bool,
+}
+
+/// Caches from before frequency lists were selectable used the full list.
+fn default_frequency_list() -> String {
+    crate::DEFAULT_FREQUENCY_LIST.to_string()
+}
+
+/// Caches from before the dictionary was selectable used WordNet.
+fn default_dictionary() -> String {
+    crate::dictionary::DEFAULT_DICTIONARY.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub phraseforge_version: String,
+    /// Source name to the URL it was downloaded from.
+    pub sources: BTreeMap<String, String>,
+    pub generation: GenerationParams,
+    /// Path relative to the cache directory to its expected contents.
+    pub files: BTreeMap<String, FileRecord>,
+}
+
+/// Why a cache can't be used as is.
+#[derive(Debug)]
+pub struct Stale {
+    pub reason: String,
+    /// True when a downloaded source, not just a generated list, is damaged
+    /// and has to be fetched again.
+    pub sources_damaged: bool,
+}
+
+/// The cache directory for the current layout, migrating an older layout
+/// into it first if there is one.
+pub fn versioned_dir(root: &Path) -> PathBuf {
+    let dir = root.join(format!("v{}", CACHE_VERSION));
+    if dir.exists() {
+        return dir;
+    }
+    fs::create_dir_all(&dir).expect("Failed to create cache directory");
+
+    let mut migrated = false;
+    for entry in V1_ENTRIES {
+        let old = root.join(entry);
+        if old.exists() {
+            update::rename(&old, &dir.join(entry)).expect("Failed to migrate cache");
+            migrated = true;
+        }
+    }
+    if migrated {
+        log::info!("Migrated cache to {}", dir.display());
+    }
+    dir
+}
+
+pub fn read_manifest(dir: &Path) -> Option<Manifest> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Checks the manifest against `params` and the files on disk. Only sizes are
+/// compared, so this is cheap enough for every run.
+pub fn check(dir: &Path, params: &GenerationParams) -> Result<(), Stale> {
+    let stale = |reason: String, sources_damaged| {
+        Err(Stale {
+            reason,
+            sources_damaged,
+        })
+    };
+    let Some(manifest) = read_manifest(dir) else {
+        return stale(
+            "no manifest (first run of this layout, or an interrupted build)".to_string(),
+            false,
+        );
+    };
+    if manifest.version != CACHE_VERSION {
+        return stale(format!("cache layout version {}", manifest.version), false);
+    }
+    if manifest.generation != *params {
+        return stale("generation parameters changed".to_string(), false);
+    }
+    for (file, record) in &manifest.files {
+        let size = fs::metadata(dir.join(file)).map(|m| m.len()).ok();
+        if size != Some(record.size) {
+            let is_source = !crate::GENERATED_FILES.contains(&file.as_str());
+            return stale(format!("{} is missing or damaged", file), is_source);
+        }
+    }
+    Ok(())
+}
+
+/// Verifies every file in the manifest against its recorded SHA-256, which
+/// `check` skips because it reads each file in full. Returns how many files
+/// were checked and the ones that differ, or `None` without a manifest.
+pub fn corrupted_files(dir: &Path) -> Option<(usize, Vec<String>)> {
+    let manifest = read_manifest(dir)?;
+    let corrupted = manifest
+        .files
+        .iter()
+        .filter(|(file, record)| {
+            sha256_file(&dir.join(file)).as_deref() != Some(record.sha256.as_str())
+        })
+        .map(|(file, _)| file.clone())
+        .collect();
+    Some((manifest.files.len(), corrupted))
+}
+
+/// Removes the manifest so an interrupted rebuild is detected next time.
+pub fn invalidate(dir: &Path) {
+    let _ = fs::remove_file(dir.join(MANIFEST_FILE));
+}
+
+fn record_files(dir: &Path, relative: &Path, files: &mut BTreeMap<String, FileRecord>) {
+    let Ok(entries) = fs::read_dir(dir.join(relative)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = relative.join(entry.file_name());
+        let path = dir.join(&name);
+        let key = name.to_string_lossy().replace('\\', "/");
+        if path.is_dir() {
+            if !key.starts_with('.') {
+                record_files(dir, &name, files);
+            }
+        } else if key != MANIFEST_FILE
+            && !key.starts_with('.')
+            && !UNTRACKED.contains(&key.as_str())
+        {
+            files.insert(
+                key,
+                FileRecord {
+                    size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                    sha256: sha256_file(&path).unwrap_or_default(),
+                },
+            );
+        }
+    }
+}
+
+/// Records the finished cache; call only once every file is in place.
+pub fn write_manifest(dir: &Path, sources: &[(&str, &str)], params: &GenerationParams) {
+    let mut files = BTreeMap::new();
+    record_files(dir, Path::new(""), &mut files);
+    let manifest = Manifest {
+        version: CACHE_VERSION,
+        phraseforge_version: env!("CARGO_PKG_VERSION").to_string(),
+        sources: sources
+            .iter()
+            .map(|(name, url)| (name.to_string(), url.to_string()))
+            .collect(),
+        generation: params.clone(),
+        files,
+    };
+    let json = serde_json::to_string_pretty(&manifest).expect("Manifest serializes");
+    write_atomically(&dir.join(MANIFEST_FILE), json.as_bytes());
+}
@@ -0,0 +1,195 @@
+//! `phraseforge capabilities`: what this binary supports and which word
+//! lists it would use, for provisioning pipelines to check before they rely
+//! on a flag or template.
+//!
+//! Nothing is downloaded: the word lists are described from the cache
+//! manifest when there is one, with each file's SHA-256 as recorded when the
+//! cache was built.
+
+use crate::cache::{self, FileRecord, GenerationParams};
+use crate::config::SECONDS_PER_DAY;
+use crate::dictionary::{DEFAULT_DICTIONARY, SCOWL_SIZES};
+use crate::export::Format;
+use crate::hash::HashAlgorithm;
+use crate::inflect::VerbForm;
+use crate::policy::Policy;
+use crate::template::{self, DEFAULT_TEMPLATE};
+use crate::theme::Theme;
+use crate::update;
+use crate::variants::Spelling;
+use crate::{bundled, frequency_list_file, FREQUENCY_LISTS};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+pub struct Templates {
+    pub default: &'static str,
+    pub slots: Vec<&'static str>,
+    pub verb_forms: Vec<&'static str>,
+    /// Preset name to template; a built-in preset hides a config file's of
+    /// the same name.
+    pub presets: BTreeMap<String, String>,
+}
+
+/// The cached word lists, as their manifest records them.
+#[derive(Debug, Serialize)]
+pub struct WordLists {
+    pub directory: String,
+    /// The phraseforge version that built them.
+    pub built_by: String,
+    pub cache_version: u32,
+    pub generation: GenerationParams,
+    /// Days since the frequency list was downloaded or last confirmed
+    /// current by `update`.
+    pub age_days: Option<u64>,
+    /// Source name to the URL it was downloaded from.
+    pub sources: BTreeMap<String, String>,
+    pub files: BTreeMap<String, FileRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Capabilities {
+    pub phraseforge_version: &'static str,
+    pub subcommands: Vec<String>,
+    pub templates: Templates,
+    /// Word list languages; the dictionaries are all English.
+    pub languages: Vec<&'static str>,
+    pub spellings: Vec<&'static str>,
+    pub dictionaries: Vec<String>,
+    pub frequency_lists: Vec<&'static str>,
+    pub themes: Vec<&'static str>,
+    pub policies: Vec<&'static str>,
+    pub hash_algorithms: Vec<&'static str>,
+    /// Cargo features and whether this build has them.
+    pub features: BTreeMap<&'static str, bool>,
+    /// `None` until the word lists have been built.
+    pub word_lists: Option<WordLists>,
+}
+
+impl Capabilities {
+    pub fn new(
+        subcommands: Vec<String>,
+        config_templates: &BTreeMap<String, String>,
+        cache_dir: &Path,
+    ) -> Self {
+        let mut presets: BTreeMap<String, String> = config_templates.clone();
+        presets.extend(
+            template::PRESETS
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string())),
+        );
+        let word_lists = cache::read_manifest(cache_dir).map(|manifest| WordLists {
+            directory: cache_dir.display().to_string(),
+            built_by: manifest.phraseforge_version,
+            cache_version: manifest.version,
+            age_days: update::last_refreshed(
+                cache_dir,
+                &frequency_list_file(&manifest.generation.frequency_list),
+            )
+            .map(|refreshed| refreshed.elapsed().unwrap_or_default().as_secs() / SECONDS_PER_DAY),
+            generation: manifest.generation,
+            sources: manifest.sources,
+            files: manifest.files,
+        });
+        Capabilities {
+            phraseforge_version: env!("CARGO_PKG_VERSION"),
+            subcommands,
+            templates: Templates {
+                default: DEFAULT_TEMPLATE,
+                slots: vec!["number", "adjective", "noun", "verb", "adverb"],
+                verb_forms: VerbForm::NAMES.to_vec(),
+                presets,
+            },
+            languages: vec!["en"],
+            spellings: Spelling::NAMES.to_vec(),
+            dictionaries: std::iter::once(DEFAULT_DICTIONARY.to_string())
+                .chain(std::iter::once("moby".to_string()))
+                .chain(SCOWL_SIZES.iter().map(|size| format!("scowl-{}", size)))
+                .collect(),
+            frequency_lists: FREQUENCY_LISTS.to_vec(),
+            themes: Theme::NAMES.to_vec(),
+            policies: Policy::PRESET_NAMES.to_vec(),
+            hash_algorithms: HashAlgorithm::NAMES.to_vec(),
+            features: BTreeMap::from([("bundled-words", bundled::AVAILABLE)]),
+            word_lists,
+        }
+    }
+
+    pub fn write(&self, format: Format) -> io::Result<()> {
+        let mut out = BufWriter::new(io::stdout().lock());
+        match format {
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut out, self)?;
+                writeln!(out)?;
+            }
+            Format::Txt => {
+                let features: Vec<String> = self
+                    .features
+                    .iter()
+                    .map(|(name, on)| format!("{} ({})", name, if *on { "on" } else { "off" }))
+                    .collect();
+                writeln!(out, "phraseforge {}", self.phraseforge_version)?;
+                writeln!(out, "subcommands: {}", self.subcommands.join(", "))?;
+                writeln!(out, "default template: {}", self.templates.default)?;
+                writeln!(out, "slots: {}", self.templates.slots.join(", "))?;
+                writeln!(out, "verb forms: {}", self.templates.verb_forms.join(", "))?;
+                for (name, text) in &self.templates.presets {
+                    writeln!(out, "preset {}: {}", name, text)?;
+                }
+                writeln!(out, "languages: {}", self.languages.join(", "))?;
+                writeln!(out, "spellings: {}", self.spellings.join(", "))?;
+                writeln!(out, "dictionaries: {}", self.dictionaries.join(", "))?;
+                writeln!(out, "frequency lists: {}", self.frequency_lists.join(", "))?;
+                writeln!(out, "themes: {}", self.themes.join(", "))?;
+                writeln!(out, "policies: {}", self.policies.join(", "))?;
+                writeln!(out, "hash algorithms: {}", self.hash_algorithms.join(", "))?;
+                writeln!(out, "features: {}", features.join(", "))?;
+                match &self.word_lists {
+                    Some(lists) => {
+                        writeln!(
+                            out,
+                            "word lists: {} (cache v{}, built by phraseforge {}, {} dictionary, {} frequency list)",
+                            lists.directory,
+                            lists.cache_version,
+                            lists.built_by,
+                            lists.generation.dictionary,
+                            lists.generation.frequency_list
+                        )?;
+                        if let Some(days) = lists.age_days {
+                            writeln!(out, "frequency data age: {} days", days)?;
+                        }
+                        for (name, url) in &lists.sources {
+                            writeln!(out, "source {}: {}", name, url)?;
+                        }
+                        for (file, record) in &lists.files {
+                            writeln!(out, "{}  {}", record.sha256, file)?;
+                        }
+                    }
+                    None => writeln!(out, "word lists: not built yet")?,
+                }
+            }
+        }
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_presets_hide_the_config_files() {
+        let config = BTreeMap::from([
+            ("short".to_string(), "{noun}".to_string()),
+            ("mine".to_string(), "{noun}-{number}".to_string()),
+        ]);
+        let capabilities = Capabilities::new(Vec::new(), &config, Path::new("/nonexistent"));
+        let presets = &capabilities.templates.presets;
+        assert_eq!(presets["short"], "{number}-{adjective}-{noun}");
+        assert_eq!(presets["mine"], "{noun}-{number}");
+        assert!(capabilities.word_lists.is_none());
+        assert!(capabilities.dictionaries.contains(&"scowl-50".to_string()));
+    }
+}
@@ -0,0 +1,164 @@
+//! `--caps-pattern`: which letters of each word are capitalized.
+//!
+//! A pattern like `Xxxx-xxxx-XXXX` holds one group of `x`, `X` and `?` per
+//! word slot, in order; anything else (dashes, a digit standing for the
+//! number slot) only separates the groups. Each character of a group sets
+//! the case of the letter in the same place: `x` lower, `X` upper, `?`
+//! either, drawn from the RNG. A group's last `x` or `X` carries on over the
+//! rest of a longer word, so `Xx` capitalizes a word and `X` shouts it,
+//! whatever its length. `random:N` instead capitalizes N word slots chosen
+//! at random.
+//!
+//! Only random choices count toward the entropy estimate: a bit for each
+//! `?` that every eligible word of its slot is long enough to reach, and the
+//! choice of slots for `random:N`.
+
+use rand::seq::index;
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    Upper,
+    /// Upper or lower at random.
+    Either,
+}
+
+/// The cases for one word's letters, from its first.
+pub type Mask = Vec<Case>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CapsPattern {
+    /// One mask per word slot.
+    Masks(Vec<Mask>),
+    /// Capitalize this many word slots, chosen at random.
+    Random(usize),
+}
+
+impl CapsPattern {
+    /// Parses `random:N` or a pattern of masks; suitable for use as a clap
+    /// value parser.
+    pub fn parse(text: &str) -> Result<CapsPattern, String> {
+        if let Some(count) = text.strip_prefix("random:") {
+            return count
+                .parse()
+                .map(CapsPattern::Random)
+                .map_err(|_| format!("'{}' is not a number of words", count));
+        }
+        let masks: Vec<Mask> = text
+            .split(|c| !matches!(c, 'x' | 'X' | '?'))
+            .filter(|group| !group.is_empty())
+            .map(|group| {
+                group
+                    .chars()
+                    .map(|c| match c {
+                        'x' => Case::Lower,
+                        'X' => Case::Upper,
+                        _ => Case::Either,
+                    })
+                    .collect()
+            })
+            .collect();
+        if masks.is_empty() {
+            return Err(format!(
+                "'{}' has no groups of x, X or ? (e.g. Xxxx-xxxx-XXXX, or random:2)",
+                text
+            ));
+        }
+        Ok(CapsPattern::Masks(masks))
+    }
+
+    /// Why the pattern can't apply to a template with `word_slots` words, if
+    /// it can't.
+    pub fn check(&self, word_slots: usize) -> Result<(), String> {
+        match self {
+            CapsPattern::Masks(masks) if masks.len() != word_slots => Err(format!(
+                "--caps-pattern has {} words but the template has {} word slots",
+                masks.len(),
+                word_slots
+            )),
+            CapsPattern::Random(count) if *count > word_slots => Err(format!(
+                "--caps-pattern random:{} asks for more words than the template's {}",
+                count, word_slots
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The mask for each of `word_slots` words, picking the slots for
+    /// `random:N`.
+    pub fn masks(&self, word_slots: usize, rng: &mut impl Rng) -> Vec<Mask> {
+        match self {
+            CapsPattern::Masks(masks) => masks.clone(),
+            CapsPattern::Random(count) => {
+                let mut masks = vec![vec![Case::Lower]; word_slots];
+                for slot in index::sample(rng, word_slots, (*count).min(word_slots)) {
+                    masks[slot] = vec![Case::Upper, Case::Lower];
+                }
+                masks
+            }
+        }
+    }
+
+    /// Bits from choosing the capitalized slots under `random:N`.
+    pub fn slot_bits(&self, word_slots: usize) -> f64 {
+        match self {
+            CapsPattern::Masks(_) => 0.0,
+            CapsPattern::Random(count) => crate::mutate::position_bits(word_slots, *count),
+        }
+    }
+}
+
+/// `?` positions of `mask` within a word `length` letters long: the bits it
+/// is sure to add.
+pub fn either_bits(mask: &Mask, length: usize) -> f64 {
+    mask.iter()
+        .take(length)
+        .filter(|&&case| case == Case::Either)
+        .count() as f64
+}
+
+/// `word` with its letters cased by `mask`.
+pub fn apply(word: &str, mask: &Mask, rng: &mut impl Rng) -> String {
+    let carried = mask.last().filter(|&&case| case != Case::Either);
+    word.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let case = mask.get(i).or(carried);
+            let upper = match case {
+                Some(Case::Upper) => true,
+                Some(Case::Either) => rng.random_bool(0.5),
+                Some(Case::Lower) | None => false,
+            };
+            if upper {
+                c.to_uppercase().collect::<String>()
+            } else {
+                c.to_lowercase().collect()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn masks_carry_their_last_case() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let CapsPattern::Masks(masks) = CapsPattern::parse("9-Xx-xxxx-X-xX").unwrap() else {
+            panic!("expected masks");
+        };
+        let words = ["brave", "otters", "bounce", "softly"];
+        let cased: Vec<String> = words
+            .iter()
+            .zip(&masks)
+            .map(|(word, mask)| apply(word, mask, &mut rng))
+            .collect();
+        assert_eq!(cased, ["Brave", "otters", "BOUNCE", "sOFTLY"]);
+        assert!(CapsPattern::parse("random:2").unwrap().check(4).is_ok());
+        assert!(CapsPattern::parse("Xx-Xx").unwrap().check(4).is_err());
+    }
+}
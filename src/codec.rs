@@ -0,0 +1,169 @@
+//! `phraseforge encode` and `decode`: arbitrary bytes, such as a key or an
+//! API token, written as words from one of the user's own pools, in the
+//! spirit of BIP39.
+//!
+//! The codebook is the pool's eligible words under the current filters and
+//! `--min-frequency`, most frequent first, cut to the largest power of two so
+//! each word carries a whole number of bits. A byte of the input's SHA-256
+//! follows the input to catch a mistyped word or a codebook that changed, and
+//! a single 1 bit then zeros pad the end to a whole word, so the length
+//! needs no separate prefix. Decoding needs the same dictionary, pool,
+//! filters and `--min-frequency` the words were encoded with.
+
+use crate::generate::eligible;
+use crate::WordEntry;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+pub struct Codebook {
+    words: Vec<String>,
+    /// Bits each word carries.
+    bits: u32,
+}
+
+impl Codebook {
+    /// The codebook drawn from `entries` above `min_frequency`.
+    pub fn new(entries: &[WordEntry], min_frequency: &u32) -> Result<Codebook, String> {
+        let mut eligible: Vec<&WordEntry> = eligible(entries, min_frequency, None).collect();
+        eligible.sort_by(|a, b| b.frequency.cmp(&a.frequency).then(a.word.cmp(&b.word)));
+        let mut seen = HashSet::new();
+        let mut words: Vec<String> = eligible
+            .into_iter()
+            .filter(|entry| seen.insert(entry.word.as_str()))
+            .map(|entry| entry.word.clone())
+            .collect();
+        if words.len() < 2 {
+            return Err(format!(
+                "the pool has {} eligible words; lower --min-frequency",
+                words.len()
+            ));
+        }
+        let bits = words.len().ilog2();
+        words.truncate(1 << bits);
+        Ok(Codebook { words, bits })
+    }
+
+    pub fn bits_per_word(&self) -> u32 {
+        self.bits
+    }
+
+    /// The words spelling `bytes`.
+    pub fn encode(&self, bytes: &[u8]) -> Vec<&str> {
+        let mut stream: Vec<bool> = bytes
+            .iter()
+            .chain(&[checksum(bytes)])
+            .flat_map(|&byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+            .collect();
+        stream.push(true);
+        while !stream.len().is_multiple_of(self.bits as usize) {
+            stream.push(false);
+        }
+        stream
+            .chunks(self.bits as usize)
+            .map(|chunk| {
+                let index = chunk
+                    .iter()
+                    .fold(0, |index, &bit| (index << 1) | usize::from(bit));
+                self.words[index].as_str()
+            })
+            .collect()
+    }
+
+    /// The bytes `words` spell, checked against their checksum.
+    pub fn decode<'a>(&self, words: impl IntoIterator<Item = &'a str>) -> Result<Vec<u8>, String> {
+        let mut stream = Vec::new();
+        for word in words {
+            let index = self
+                .words
+                .iter()
+                .position(|known| known.eq_ignore_ascii_case(word))
+                .ok_or_else(|| {
+                    format!(
+                        "'{}' is not in the codebook; decode with the --pos, filters and --min-frequency the words were encoded with",
+                        word
+                    )
+                })?;
+            stream.extend((0..self.bits).rev().map(|bit| (index >> bit) & 1 == 1));
+        }
+        while stream.last() == Some(&false) {
+            stream.pop();
+        }
+        if stream.pop() != Some(true) || !stream.len().is_multiple_of(8) || stream.is_empty() {
+            return Err(
+                "the words do not end where an encoding would; check for a missing or extra word"
+                    .to_string(),
+            );
+        }
+        let mut bytes: Vec<u8> = stream
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |byte, &bit| (byte << 1) | u8::from(bit))
+            })
+            .collect();
+        let check = bytes.pop().expect("The stream holds at least the checksum");
+        if checksum(&bytes) != check {
+            return Err("the checksum does not match; check each word and the settings they were encoded with".to_string());
+        }
+        Ok(bytes)
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    Sha256::digest(bytes)[0]
+}
+
+/// Parses hex digits, ignoring whitespace and a leading `0x`.
+pub fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.split_whitespace().collect();
+    let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+    if let Some(bad) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a hex digit", bad));
+    }
+    if digits.is_empty() || !digits.len().is_multiple_of(2) {
+        return Err("expected an even number of hex digits".to_string());
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).expect("Checked to be hex digits"))
+        .collect())
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_survive_a_round_trip_through_words() {
+        let entries: Vec<WordEntry> = (0..1500)
+            .map(|i| WordEntry {
+                word: format!("word{}", i),
+                frequency: 20000 + i,
+            })
+            .collect();
+        let codebook = Codebook::new(&entries, &10000).unwrap();
+        assert_eq!(codebook.bits_per_word(), 10);
+
+        for hex in ["00", "ff00", "deadbeef", "0123456789abcdef0123456789abcdef"] {
+            let bytes = parse_hex(hex).unwrap();
+            let words = codebook.encode(&bytes);
+            assert_eq!(
+                to_hex(&codebook.decode(words.iter().copied()).unwrap()),
+                hex
+            );
+        }
+
+        let words = codebook.encode(&[0xde, 0xad]);
+        assert!(codebook
+            .decode(words[..words.len() - 1].iter().copied())
+            .is_err());
+        assert!(codebook.decode(["nonsense"]).is_err());
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+}
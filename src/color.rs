@@ -0,0 +1,51 @@
+//! ANSI colors for interactive output.
+//!
+//! Each part of speech gets its own color so a batch of candidates is easy to
+//! scan. Colors are only used when stdout is a terminal, and never when
+//! `NO_COLOR` is set (<https://no-color.org>) or `--no-color` is given.
+//! Windows consoles only interpret the escape codes once virtual terminal
+//! processing is switched on, and consoles too old to support it get plain
+//! text.
+
+use crate::template::Slot;
+use std::env;
+use std::io::{self, IsTerminal};
+
+const RESET: &str = "\x1b[0m";
+const DIM: &str = "\x1b[2m";
+
+pub fn enabled(no_color_flag: bool) -> bool {
+    let no_color_env = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color_flag && !no_color_env && io::stdout().is_terminal() && escape_codes_work()
+}
+
+/// Whether the terminal interprets escape codes, switching virtual terminal
+/// processing on first on Windows. Always true elsewhere.
+pub fn escape_codes_work() -> bool {
+    anstyle_query::windows::enable_ansi_colors().unwrap_or(true)
+}
+
+fn slot_color(slot: Slot) -> &'static str {
+    match slot {
+        Slot::Number => "\x1b[33m",    // yellow
+        Slot::Adjective => "\x1b[32m", // green
+        Slot::Noun => "\x1b[34m",      // blue
+        Slot::Verb(_) => "\x1b[35m",   // magenta
+        Slot::Adverb => "\x1b[36m",    // cyan
+    }
+}
+
+/// Renders a phrase from its pieces, coloring each slot's text.
+pub fn paint(pieces: &[(Option<Slot>, String)]) -> String {
+    pieces
+        .iter()
+        .map(|(slot, text)| match slot {
+            Some(slot) => format!("{}{}{}", slot_color(*slot), text, RESET),
+            None => text.clone(),
+        })
+        .collect()
+}
+
+pub fn dim(text: &str) -> String {
+    format!("{}{}{}", DIM, text, RESET)
+}
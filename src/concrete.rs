@@ -0,0 +1,107 @@
+//! Concreteness filtering.
+//!
+//! Concrete words are easier to picture, and pictures are easier to remember.
+//! Nouns count as concrete when their most frequent sense lives in one of
+//! WordNet's physical lexicographer files (animals, artifacts, food, ...).
+//! Adjectives count when they describe a perceptual attribute such as colour
+//! or size, either directly or through the head adjective of their cluster.
+
+use crate::filter::WordFilter;
+use crate::wordnet::{self, Synset};
+use crate::PartOfSpeech;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// noun.animal, noun.artifact, noun.body, noun.food, noun.object,
+/// noun.person, noun.plant and noun.substance.
+const CONCRETE_NOUN_LEX_FILES: [u8; 8] = [5, 6, 8, 13, 17, 18, 20, 27];
+
+/// Attribute nouns that adjectives can point at which describe something you
+/// can see, hear, touch or taste.
+const PERCEPTUAL_ATTRIBUTES: &[&str] = &[
+    "brightness",
+    "color",
+    "colour",
+    "hardness",
+    "height",
+    "length",
+    "loudness",
+    "shape",
+    "size",
+    "taste",
+    "temperature",
+    "texture",
+    "weight",
+    "wetness",
+    "width",
+];
+
+pub struct ConcreteFilter {
+    nouns: HashSet<String>,
+    adjectives: HashSet<String>,
+}
+
+fn is_perceptual(synset: &Synset, noun_synsets: &HashMap<u64, Synset>) -> bool {
+    synset
+        .pointers
+        .iter()
+        .filter(|p| p.symbol == "=")
+        .filter_map(|p| noun_synsets.get(&p.offset))
+        .any(|attribute| {
+            attribute
+                .words
+                .iter()
+                .any(|w| PERCEPTUAL_ATTRIBUTES.contains(&w.to_lowercase().as_str()))
+        })
+}
+
+impl ConcreteFilter {
+    pub fn new(dict_dir: &Path) -> Self {
+        let noun_synsets = wordnet::synsets_by_offset(&dict_dir.join("data.noun"));
+        let adj_synsets = wordnet::synsets_by_offset(&dict_dir.join("data.adj"));
+
+        let nouns = wordnet::primary_senses(&dict_dir.join("index.noun"))
+            .into_iter()
+            .filter(|(_, offset)| {
+                noun_synsets
+                    .get(offset)
+                    .is_some_and(|s| CONCRETE_NOUN_LEX_FILES.contains(&s.lex_filenum))
+            })
+            .map(|(lemma, _)| lemma)
+            .collect();
+
+        let adjectives = wordnet::primary_senses(&dict_dir.join("index.adj"))
+            .into_iter()
+            .filter(|(_, offset)| {
+                let Some(synset) = adj_synsets.get(offset) else {
+                    return false;
+                };
+                // Satellite adjectives inherit their head's attributes.
+                is_perceptual(synset, &noun_synsets)
+                    || synset
+                        .pointers
+                        .iter()
+                        .filter(|p| p.symbol == "&")
+                        .filter_map(|p| adj_synsets.get(&p.offset))
+                        .any(|head| is_perceptual(head, &noun_synsets))
+            })
+            .map(|(lemma, _)| lemma)
+            .collect();
+
+        ConcreteFilter { nouns, adjectives }
+    }
+}
+
+impl WordFilter for ConcreteFilter {
+    fn name(&self) -> String {
+        "concrete".to_string()
+    }
+
+    fn keep(&self, pos: PartOfSpeech, word: &str) -> bool {
+        match pos {
+            PartOfSpeech::Noun => self.nouns.contains(word),
+            PartOfSpeech::Adjective => self.adjectives.contains(word),
+            PartOfSpeech::Verb | PartOfSpeech::Adverb => true,
+        }
+    }
+}
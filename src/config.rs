@@ -0,0 +1,296 @@
+//! The optional config file: `config.toml` in the platform config directory
+//! (e.g. `~/.config/phraseforge/config.toml`, or
+//! `%APPDATA%\tynsol\phraseforge\config\config.toml` on Windows), or
+//! wherever `--config` points.
+
+use crate::ambiguity::Ambiguity;
+use crate::encrypt::Recipients;
+use crate::history::{self, HistoryLog, Record};
+use crate::policy::{CharClass, Policy};
+use crate::template::Template;
+use directories::ProjectDirs;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Days before the word lists count as stale when `cache_max_age` is unset.
+const DEFAULT_CACHE_MAX_AGE_DAYS: u64 = 180;
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Defaults for flags the command line leaves out.
+    #[serde(default)]
+    pub defaults: DefaultsConfig,
+    /// Named policies, selected with `--policy <name>`.
+    #[serde(default)]
+    pub policy: BTreeMap<String, PolicyConfig>,
+    /// Named templates, selected with `--preset <name>`.
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+    /// Download locations, for networks that can't reach the defaults.
+    #[serde(default)]
+    pub sources: SourcesConfig,
+    /// Replaces the confusable sequences `--unambiguous` avoids.
+    pub unambiguous: Option<AmbiguityConfig>,
+    /// Where `--log-history` appends and whom it encrypts to.
+    pub history: Option<HistoryConfig>,
+    /// Days after which the word lists' frequency data counts as stale and
+    /// runs suggest `update`.
+    pub cache_max_age: Option<u64>,
+    /// Run that `update` in the background instead of suggesting it.
+    #[serde(default)]
+    pub cache_auto_refresh: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DefaultsConfig {
+    /// As for `--spelling`.
+    pub spelling: Option<String>,
+    /// As for `--theme`.
+    pub theme: Option<String>,
+    /// As for `--policy`.
+    pub policy: Option<String>,
+}
+
+impl DefaultsConfig {
+    /// The flags these defaults stand for, e.g. `--theme food`.
+    pub fn args(&self) -> Vec<String> {
+        [
+            ("--spelling", &self.spelling),
+            ("--theme", &self.theme),
+            ("--policy", &self.policy),
+        ]
+        .into_iter()
+        .filter_map(|(flag, value)| Some([flag.to_string(), value.clone()?]))
+        .flatten()
+        .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    file: Option<PathBuf>,
+    #[serde(default)]
+    encrypt_to: Vec<String>,
+    #[serde(default)]
+    age_recipients: Vec<String>,
+    /// `hashes` or `phrases`, as for `--log-history`.
+    record: Option<String>,
+    /// An `--avoid-file` that `--log-history` runs check and add to.
+    avoid_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmbiguityConfig {
+    sequences: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourcesConfig {
+    /// Base URL serving every source under its upstream file name.
+    pub mirror: Option<String>,
+    pub wordnet_url: Option<String>,
+    /// Dictionary the word lists are built from, as for `--dictionary`.
+    pub dictionary: Option<String>,
+    pub scowl_url: Option<String>,
+    pub moby_pos_url: Option<String>,
+    pub frequency_list_url: Option<String>,
+    /// Frequency list variant, as for `--frequency-list`.
+    pub frequency_list: Option<String>,
+    pub eff_word_list_url: Option<String>,
+    /// The breached-password list, for `--reject-common`.
+    pub common_passwords_url: Option<String>,
+    /// A self-hosted Pwned Passwords range API, for `--hibp`.
+    pub hibp_range_url: Option<String>,
+    /// Public key the signed manifest must be signed with, as for
+    /// `--trust-key`.
+    pub trust_key: Option<PathBuf>,
+    /// Signed `SHA256SUMS` listing the sources; defaults to the mirror's.
+    pub signed_manifest_url: Option<String>,
+    pub proxy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyConfig {
+    #[serde(default)]
+    min_length: usize,
+    #[serde(default)]
+    required_classes: Vec<String>,
+    #[serde(default)]
+    min_classes: usize,
+    #[serde(default)]
+    banned_substrings: Vec<String>,
+    max_consecutive_identical: Option<usize>,
+    max_attempts: Option<usize>,
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "tynsol", "phraseforge")
+        .map(|dirs| dirs.config_dir().join("config.toml"))
+}
+
+impl Config {
+    /// Reads `path`, or the default location when `None`. Only an explicitly
+    /// named file has to exist.
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match default_config_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read config file {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("invalid config file {}: {}", path.display(), e))
+    }
+
+    /// How old the word lists may get before they are refreshed.
+    pub fn cache_max_age(&self) -> Duration {
+        Duration::from_secs(
+            self.cache_max_age.unwrap_or(DEFAULT_CACHE_MAX_AGE_DAYS) * SECONDS_PER_DAY,
+        )
+    }
+
+    /// The template declared as `<name> = "..."` under `[templates]`, if any.
+    pub fn template(&self, name: &str) -> Result<Option<Template>, String> {
+        self.templates
+            .get(name)
+            .map(|text| Template::parse(text).map_err(|e| format!("template '{}': {}", name, e)))
+            .transpose()
+    }
+
+    /// The policy declared as `[policy.<name>]`, if any.
+    pub fn policy(&self, name: &str) -> Result<Option<Policy>, String> {
+        let Some(spec) = self.policy.get(name) else {
+            return Ok(None);
+        };
+        let required_classes = spec
+            .required_classes
+            .iter()
+            .map(|class| {
+                CharClass::from_name(class).ok_or_else(|| {
+                    format!(
+                        "policy '{}': unknown character class '{}' (expected one of: {})",
+                        name,
+                        class,
+                        CharClass::NAMES.join(", ")
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if spec.min_classes > CharClass::ALL.len() {
+            return Err(format!(
+                "policy '{}': min_classes is {} but there are only {} character classes",
+                name,
+                spec.min_classes,
+                CharClass::ALL.len()
+            ));
+        }
+        if spec.max_consecutive_identical == Some(0) {
+            return Err(format!(
+                "policy '{}': max_consecutive_identical must be at least 1",
+                name
+            ));
+        }
+        if spec.max_attempts == Some(0) {
+            return Err(format!(
+                "policy '{}': max_attempts must be at least 1",
+                name
+            ));
+        }
+
+        let defaults = Policy::default();
+        Ok(Some(Policy {
+            min_length: spec.min_length,
+            required_classes,
+            min_classes: spec.min_classes,
+            banned_substrings: spec.banned_substrings.clone(),
+            max_consecutive_identical: spec.max_consecutive_identical,
+            max_attempts: spec.max_attempts.unwrap_or(defaults.max_attempts),
+        }))
+    }
+
+    /// The sequences `--unambiguous` avoids: `[unambiguous]` if declared,
+    /// otherwise the defaults.
+    pub fn ambiguity(&self) -> Result<Ambiguity, String> {
+        let Some(spec) = &self.unambiguous else {
+            return Ok(Ambiguity::default());
+        };
+        if spec.sequences.iter().any(String::is_empty) {
+            return Err("unambiguous: sequences must not be empty strings".to_string());
+        }
+        Ok(Ambiguity::new(spec.sequences.clone()))
+    }
+
+    /// The history file: `[history] file` if set, otherwise the default.
+    pub fn history_path(&self) -> Result<PathBuf, String> {
+        self.history
+            .as_ref()
+            .and_then(|spec| spec.file.clone())
+            .or_else(history::default_path)
+            .ok_or_else(|| "history: no default location; set file in [history]".to_string())
+    }
+
+    /// `[history] avoid_file`, for `--log-history` runs.
+    pub fn avoid_file(&self) -> Option<PathBuf> {
+        self.history.as_ref()?.avoid_file.clone()
+    }
+
+    /// The log `--log-history` appends to, as declared in `[history]`.
+    pub fn history(&self) -> Result<HistoryLog, String> {
+        let Some(spec) = &self.history else {
+            return Err(
+                "--log-history needs a [history] section in the config file naming encrypt_to or age_recipients"
+                    .to_string(),
+            );
+        };
+        let recipients = match (spec.encrypt_to.is_empty(), spec.age_recipients.is_empty()) {
+            (false, true) => Recipients::Gpg(spec.encrypt_to.clone()),
+            (true, false) => Recipients::Age(spec.age_recipients.clone()),
+            (true, true) => {
+                return Err("history: set encrypt_to or age_recipients; the history is never written in cleartext".to_string())
+            }
+            (false, false) => {
+                return Err("history: set either encrypt_to or age_recipients, not both".to_string())
+            }
+        };
+        let record = match &spec.record {
+            None => Record::Hashes,
+            Some(name) => Record::from_name(name).ok_or_else(|| {
+                format!(
+                    "history: unknown record '{}' (expected one of: {})",
+                    name,
+                    Record::NAMES.join(", ")
+                )
+            })?,
+        };
+        Ok(HistoryLog::new(self.history_path()?, recipients, record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_a_policy_that_can_never_try() {
+        let config: Config =
+            toml::from_str("[policy.never]\nmax_attempts = 0\n\n[policy.once]\nmax_attempts = 1\n")
+                .unwrap();
+        assert!(config
+            .policy("never")
+            .unwrap_err()
+            .contains("max_attempts must be at least 1"));
+        assert_eq!(config.policy("once").unwrap().unwrap().max_attempts, 1);
+    }
+}
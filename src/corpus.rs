@@ -0,0 +1,103 @@
+//! `--reject-common`: the 100,000 most common passwords from breach dumps,
+//! downloaded once and cached alongside the word lists.
+//!
+//! Cracking tools try these first, so a phrase that is a single word of the
+//! list falls in seconds however large its pool claims to be. Templates with
+//! at most one word slot lose the listed words from their pools; longer
+//! phrases keep every word, since a listed word is harmless among others,
+//! but a finished phrase that is itself on the list is warned about.
+
+use crate::filter::WordFilter;
+use crate::sources::Sources;
+use crate::{datadir, exit, PartOfSpeech};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+pub const COMMON_PASSWORDS_URL: &str = "https://raw.githubusercontent.com/danielmiessler/SecLists/master/Passwords/Common-Credentials/10-million-password-list-top-100000.txt";
+pub const COMMON_PASSWORDS_FILE: &str = "10-million-password-list-top-100000.txt";
+
+/// Fewer entries than this means the download was truncated or is not the
+/// list at all.
+const MIN_ENTRIES: usize = 10_000;
+
+/// The breached passwords, lowercased. As a filter, it drops listed words
+/// from the pools of a template too short for other words to hide them.
+#[derive(Clone)]
+pub struct CommonPasswords(HashSet<String>);
+
+impl CommonPasswords {
+    /// Loads the cached list, downloading it first if missing or `force`d.
+    pub fn load_or_download(data_dir: &Path, sources: &Sources, force: bool) -> CommonPasswords {
+        let path = data_dir.join(COMMON_PASSWORDS_FILE);
+        if !path.exists() || force {
+            datadir::ensure_writable(data_dir, "download the common password list");
+            log::info!("Downloading the common password list...");
+            let bytes = sources
+                .get(&sources.common_passwords)
+                .and_then(|response| response.bytes())
+                .unwrap_or_else(|e| {
+                    exit::fail(
+                        exit::NETWORK,
+                        format!("cannot download {}: {}", sources.common_passwords, e),
+                    )
+                });
+            sources
+                .verify(&sources.common_passwords, &bytes)
+                .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+            fs::write(&path, &bytes).expect("Failed to save the common password list");
+        }
+        let bytes = fs::read(&path).expect("Failed to read the common password list");
+        let passwords = CommonPasswords::parse(&String::from_utf8_lossy(&bytes));
+        if passwords.0.len() < MIN_ENTRIES {
+            exit::fail(
+                exit::CACHE_CORRUPTION,
+                format!(
+                    "{} has only {} passwords; run again with --redownload to fetch a fresh copy",
+                    path.display(),
+                    passwords.0.len()
+                ),
+            );
+        }
+        passwords
+    }
+
+    fn parse(text: &str) -> CommonPasswords {
+        CommonPasswords(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_lowercase)
+                .collect(),
+        )
+    }
+
+    /// Whether `text` is on the list, ignoring case.
+    pub fn contains(&self, text: &str) -> bool {
+        self.0.contains(&text.to_lowercase())
+    }
+}
+
+impl WordFilter for CommonPasswords {
+    fn name(&self) -> String {
+        "reject-common".to_string()
+    }
+
+    fn keep(&self, _pos: PartOfSpeech, word: &str) -> bool {
+        !self.contains(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_listed_passwords_ignoring_case() {
+        let passwords = CommonPasswords::parse("123456\r\npassword\n\nDragon\n");
+        assert_eq!(passwords.0.len(), 3);
+        assert!(passwords.contains("Password") && passwords.contains("dragon"));
+        assert!(!passwords.keep(PartOfSpeech::Noun, "dragon"));
+        assert!(passwords.keep(PartOfSpeech::Noun, "otter"));
+    }
+}
@@ -0,0 +1,143 @@
+//! Where the cache and the word overlay live, and what happens when that
+//! place can't be written.
+//!
+//! Both normally live in the platform data directory, or in `--cache-dir`.
+//! That directory may be read-only: a shared install, a Nix store path, a
+//! locked-down machine. A read-only directory that already holds word lists
+//! is read as it is, while the user's own words go to the platform cache
+//! directory (e.g. `~/.cache/phraseforge`). A read-only platform data
+//! directory without word lists is passed over for the cache directory
+//! altogether.
+//!
+//! Distro packages can also ship a complete, pre-generated cache in a
+//! system-wide directory, `/usr/share/phraseforge` unless
+//! `PHRASEFORGE_SYSTEM_DATADIR` names another at build time. It holds what
+//! a `v2` cache directory does, manifest included, e.g. as built by
+//! `phraseforge --cache-dir build --count 1` (then install `build/v2/*`).
+//! It is read whenever the user has no current cache of their own and it
+//! was generated with the settings in use, so first runs need no network.
+
+use crate::cache;
+use crate::exit;
+use directories::ProjectDirs;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where a package installs pre-generated word lists, if anywhere.
+pub const SYSTEM_DATADIR: Option<&str> = match option_env!("PHRASEFORGE_SYSTEM_DATADIR") {
+    Some(dir) => Some(dir),
+    None if cfg!(unix) => Some("/usr/share/phraseforge"),
+    None => None,
+};
+
+fn project_dirs() -> ProjectDirs {
+    ProjectDirs::from("com", "tynsol", "phraseforge").expect("Failed to get data directory")
+}
+
+/// The platform data directory, e.g. `~/.local/share/phraseforge` or
+/// `%LOCALAPPDATA%\tynsol\phraseforge\data`.
+pub fn platform_root() -> PathBuf {
+    project_dirs().data_local_dir().to_path_buf()
+}
+
+/// The per-user directory used when the data directory is read-only.
+pub fn fallback_root() -> PathBuf {
+    project_dirs().cache_dir().to_path_buf()
+}
+
+/// Creates `dir` if needed and checks that a file can be written in it.
+pub fn writable(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(".write-probe");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// The versioned cache under `root`, unless `root` is read-only: then the
+/// cache already there if it holds word lists, or else one under the
+/// fallback root. `explicit` is set when `root` came from `--cache-dir`,
+/// which is never silently passed over: an unusable one is an invalid
+/// argument.
+pub fn cache_dir(root: &Path, explicit: bool) -> Result<PathBuf, String> {
+    let error = match writable(root) {
+        Ok(()) => return Ok(cache::versioned_dir(root)),
+        Err(e) => e,
+    };
+    let existing = root.join(format!("v{}", cache::CACHE_VERSION));
+    if crate::word_lists_exist(&existing) {
+        log::debug!(
+            "{} is read-only; using its word lists as they are",
+            existing.display()
+        );
+        return Ok(existing);
+    }
+    if explicit {
+        exit::fail(
+            exit::INVALID_ARGUMENTS,
+            format!(
+                "--cache-dir {} is not writable and holds no word lists: {}",
+                root.display(),
+                error
+            ),
+        );
+    }
+    let fallback = fallback_root();
+    writable(&fallback).map_err(|fallback_error| {
+        format!(
+            "neither {} ({}) nor {} ({}) is writable; pass --cache-dir with a writable directory",
+            root.display(),
+            error,
+            fallback.display(),
+            fallback_error
+        )
+    })?;
+    log::info!(
+        "{} is not writable ({}); keeping the cache in {} instead",
+        root.display(),
+        error,
+        fallback.display()
+    );
+    Ok(cache::versioned_dir(&fallback))
+}
+
+/// The user's own cache, if one is already current for `params`.
+pub fn current_user_cache(params: &cache::GenerationParams) -> Option<PathBuf> {
+    [platform_root(), fallback_root()]
+        .into_iter()
+        .map(|root| root.join(format!("v{}", cache::CACHE_VERSION)))
+        .find(|dir| crate::word_lists_exist(dir) && cache::check(dir, params).is_ok())
+}
+
+/// The system-wide cache, if one is installed and was generated with
+/// `params`.
+pub fn system_cache(params: &cache::GenerationParams) -> Option<PathBuf> {
+    let dir = PathBuf::from(SYSTEM_DATADIR?);
+    (crate::word_lists_exist(&dir) && cache::check(&dir, params).is_ok()).then_some(dir)
+}
+
+/// Where the user's word overlay lives: `root` when it is writable,
+/// otherwise the fallback root.
+pub fn overlay_dir(root: &Path) -> PathBuf {
+    if writable(root).is_ok() {
+        root.to_path_buf()
+    } else {
+        fallback_root()
+    }
+}
+
+/// Exits with an explanation unless `dir` is writable, before `action`
+/// tries to write there.
+pub fn ensure_writable(dir: &Path, action: &str) {
+    if let Err(e) = writable(dir) {
+        exit::fail(
+            exit::FAILURE,
+            format!(
+                "cannot {}: {} is not writable ({})\nPass --cache-dir with a writable directory to keep a cache of your own.",
+                action,
+                dir.display(),
+                e
+            ),
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Stateless site-specific passphrases (`phraseforge derive`).
+//!
+//! Like LessPass, nothing is stored: the same master secret, site and counter
+//! always produce the same phrase. The secret is stretched with Argon2id using
+//! the site as salt, and the output is read as one large number whose base-7776
+//! digits pick words from the EFF large wordlist, so the phrase is reproducible
+//! on any machine with that list.
+
+use crate::diceware::EFF_LARGE_WORDLIST_SIZE;
+use argon2::{Algorithm, Argon2, Params, Version};
+
+/// Bumping this changes every derived phrase; it exists so the scheme can
+/// evolve without silently changing phrases people already rely on.
+const SALT_PREFIX: &str = "phraseforge-derive-v1";
+
+/// Argon2id cost: 64 MiB, three passes, one lane.
+const MEMORY_KIB: u32 = 64 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+
+/// Extra output beyond what the word indices need, so reducing the number
+/// modulo 7776 introduces no measurable bias.
+const SLACK_BYTES: usize = 32;
+
+/// Site names are case-insensitive and ignore surrounding whitespace, so
+/// `GitHub.com` and `github.com ` derive the same phrase.
+pub fn normalize_site(site: &str) -> String {
+    site.trim().to_lowercase()
+}
+
+fn salt(site: &str, counter: u32) -> Vec<u8> {
+    format!("{}\0{}\0{}", SALT_PREFIX, normalize_site(site), counter).into_bytes()
+}
+
+/// Divides a big-endian number in place, returning the remainder.
+fn div_rem(number: &mut [u8], divisor: u32) -> u32 {
+    let mut remainder = 0u32;
+    for byte in number.iter_mut() {
+        let value = (remainder << 8) | u32::from(*byte);
+        *byte = (value / divisor) as u8;
+        remainder = value % divisor;
+    }
+    remainder
+}
+
+/// Indices into the EFF list for `words` words derived from `secret`.
+pub fn word_indices(secret: &[u8], site: &str, counter: u32, words: usize) -> Vec<usize> {
+    let params = Params::new(MEMORY_KIB, ITERATIONS, PARALLELISM, None)
+        .expect("Argon2 parameters are valid");
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    // Each word consumes just under 13 bits.
+    let mut output = vec![0u8; (words * 13).div_ceil(8) + SLACK_BYTES];
+    argon2
+        .hash_password_into(secret, &salt(site, counter), &mut output)
+        .expect("Failed to derive key with Argon2");
+
+    (0..words)
+        .map(|_| div_rem(&mut output, EFF_LARGE_WORDLIST_SIZE as u32) as usize)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derivation_is_deterministic_per_site() {
+        let github = word_indices(b"correct horse", "GitHub.com", 1, 4);
+        assert_eq!(github, word_indices(b"correct horse", " github.com", 1, 4));
+        assert_ne!(github, word_indices(b"correct horse", "github.com", 2, 4));
+        assert_ne!(github, word_indices(b"correct horse", "gitlab.com", 1, 4));
+        assert!(github.iter().all(|&i| i < EFF_LARGE_WORDLIST_SIZE));
+    }
+}
@@ -0,0 +1,137 @@
+//! Diceware from physical dice (`phraseforge dice`).
+//!
+//! The randomness comes entirely from the user's dice: each group of five
+//! rolls read from stdin selects one word from the EFF large wordlist, which
+//! is downloaded once and cached alongside the WordNet data.
+
+use crate::datadir;
+use crate::entropy::{slot_entropy, Entropy};
+use crate::exit;
+use crate::sources::Sources;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const EFF_LARGE_WORDLIST_URL: &str =
+    "https://www.eff.org/files/2016/07/18/eff_large_wordlist.txt";
+pub const EFF_LARGE_WORDLIST_FILE: &str = "eff_large_wordlist.txt";
+
+/// Dice rolled per word.
+const ROLLS_PER_WORD: usize = 5;
+
+/// 6^5 words, one per possible group of rolls.
+pub const EFF_LARGE_WORDLIST_SIZE: usize = 7776;
+
+pub fn download_eff_word_list(data_dir: &Path, sources: &Sources) -> Result<(), String> {
+    log::info!("Downloading EFF Diceware Word List...");
+    let fail = |e: reqwest::Error| format!("cannot download {}: {}", sources.eff_word_list, e);
+    let bytes = sources
+        .get(&sources.eff_word_list)
+        .and_then(|response| response.bytes())
+        .map_err(fail)?;
+    sources.verify(&sources.eff_word_list, &bytes)?;
+    fs::write(data_dir.join(EFF_LARGE_WORDLIST_FILE), &bytes)
+        .expect("Failed to save EFF word list file");
+    Ok(())
+}
+
+/// Loads the cached EFF list, downloading it first if missing or `force`d.
+pub fn load_or_download_eff_word_list(
+    data_dir: &Path,
+    sources: &Sources,
+    force: bool,
+) -> HashMap<String, String> {
+    let path = data_dir.join(EFF_LARGE_WORDLIST_FILE);
+    if !path.exists() || force {
+        datadir::ensure_writable(data_dir, "download the EFF word list");
+        download_eff_word_list(data_dir, sources).unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+    }
+    let words = load_eff_word_list(&path);
+    if words.len() != EFF_LARGE_WORDLIST_SIZE {
+        exit::fail(
+            exit::CACHE_CORRUPTION,
+            format!(
+                "{} has {} words instead of {}; run again with --redownload to fetch a fresh copy",
+                path.display(),
+                words.len(),
+                EFF_LARGE_WORDLIST_SIZE
+            ),
+        );
+    }
+    words
+}
+
+/// Maps each roll group (e.g. `43621`) to its word.
+pub fn load_eff_word_list(path: &Path) -> HashMap<String, String> {
+    let contents = fs::read_to_string(path).expect("Failed to read EFF word list file");
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (rolls, word) = line.split_once(char::is_whitespace)?;
+            Some((rolls.to_string(), word.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Splits a line of input into groups of five rolls, rejecting anything that
+/// is not a die face.
+pub fn parse_rolls(line: &str) -> Result<Vec<String>, String> {
+    let digits: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Some(bad) = digits.chars().find(|c| !('1'..='6').contains(c)) {
+        return Err(format!("'{}' is not a die face (expected 1-6)", bad));
+    }
+    if !digits.len().is_multiple_of(ROLLS_PER_WORD) {
+        return Err(format!(
+            "{} rolls is not a multiple of {}; each word needs {} rolls",
+            digits.len(),
+            ROLLS_PER_WORD,
+            ROLLS_PER_WORD
+        ));
+    }
+    Ok(digits
+        .as_bytes()
+        .chunks(ROLLS_PER_WORD)
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect())
+}
+
+/// The roll group that selects word `index` (0-based) of the EFF list.
+pub fn roll_for_index(index: usize) -> String {
+    let mut rolls = vec![b'1'; ROLLS_PER_WORD];
+    let mut rest = index;
+    for roll in rolls.iter_mut().rev() {
+        *roll += (rest % 6) as u8;
+        rest /= 6;
+    }
+    String::from_utf8(rolls).expect("Dice faces are ASCII")
+}
+
+/// Entropy of a phrase of `words` dice-chosen words, assuming fair dice.
+pub fn entropy(words: usize) -> Entropy {
+    let slots: Vec<_> = (1..=words)
+        .map(|i| slot_entropy(&format!("word-{}", i), EFF_LARGE_WORDLIST_SIZE))
+        .collect();
+    let bits_per_phrase = slots.iter().map(|s| s.bits).sum();
+    Entropy {
+        slots,
+        bits_per_phrase,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_roll_groups() {
+        assert_eq!(parse_rolls("43621 13426").unwrap(), vec!["43621", "13426"]);
+        assert!(parse_rolls("43627").is_err());
+        assert!(parse_rolls("4362").is_err());
+    }
+
+    #[test]
+    fn indexes_map_to_rolls() {
+        assert_eq!(roll_for_index(0), "11111");
+        assert_eq!(roll_for_index(EFF_LARGE_WORDLIST_SIZE - 1), "66666");
+    }
+}
@@ -0,0 +1,113 @@
+//! Where the word lists' parts of speech come from, chosen with
+//! `--dictionary`.
+//!
+//! WordNet is the default. SCOWL (Spell Checker Oriented Word Lists) sorts
+//! English words into size tiers, from 10 (the most common) to 95 (nearly
+//! everything), so a smaller tier keeps obscure words out; it records no
+//! parts of speech, so its words are tagged with Moby's. Moby's
+//! part-of-speech list can also be used on its own. Whatever the
+//! dictionary, the frequency list decides how common each word is.
+//!
+//! The modes that read WordNet's synsets (`--theme`, `--concrete`,
+//! `--max-senses`, `--explain`) need the WordNet dictionary.
+
+use crate::{moby, normalize, scowl, wordnet, PartOfSpeech};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// SCOWL's size tiers.
+pub const SCOWL_SIZES: [u32; 10] = [10, 20, 35, 40, 50, 55, 60, 70, 80, 95];
+/// The tier spell checkers ship by default.
+const DEFAULT_SCOWL_SIZE: u32 = 50;
+pub const DEFAULT_DICTIONARY: &str = "wordnet";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dictionary {
+    WordNet,
+    /// SCOWL words up to this size tier.
+    Scowl(u32),
+    Moby,
+}
+
+/// A dictionary's words for each part of speech, in `PartOfSpeech::ALL`
+/// order.
+pub struct Pools {
+    /// Each word with how strongly the dictionary ties it to this part of
+    /// speech, which `--assign-part-of-speech` compares.
+    pub words: Vec<HashMap<String, u32>>,
+    /// Lowercased nouns the dictionary only lists capitalized.
+    pub proper_nouns: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Parses `wordnet`, `moby`, `scowl` or `scowl-<size>`.
+    pub fn from_name(name: &str) -> Result<Dictionary, String> {
+        let sizes = || {
+            SCOWL_SIZES
+                .iter()
+                .map(|size| size.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match name {
+            "wordnet" => Ok(Dictionary::WordNet),
+            "moby" => Ok(Dictionary::Moby),
+            "scowl" => Ok(Dictionary::Scowl(DEFAULT_SCOWL_SIZE)),
+            _ => match name.strip_prefix("scowl-").map(str::parse) {
+                Some(Ok(size)) if SCOWL_SIZES.contains(&size) => Ok(Dictionary::Scowl(size)),
+                Some(_) => Err(format!(
+                    "unknown SCOWL size in '{}' (expected one of: {})",
+                    name,
+                    sizes()
+                )),
+                None => Err(format!(
+                    "unknown dictionary '{}' (expected wordnet, moby, scowl or scowl-<size>)",
+                    name
+                )),
+            },
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Dictionary::WordNet => "wordnet".to_string(),
+            Dictionary::Scowl(size) => format!("scowl-{}", size),
+            Dictionary::Moby => "moby".to_string(),
+        }
+    }
+
+    pub fn uses_scowl(&self) -> bool {
+        matches!(self, Dictionary::Scowl(_))
+    }
+
+    pub fn uses_moby(&self) -> bool {
+        matches!(self, Dictionary::Scowl(_) | Dictionary::Moby)
+    }
+
+    /// Reads the dictionary's sources from `data_dir`, keeping words of
+    /// letters alone, accented ones included, at least `minimum_word_length`
+    /// long.
+    pub fn pools(&self, data_dir: &Path, minimum_word_length: usize) -> Pools {
+        let keep = |word: &str| {
+            normalize::is_word(word) && normalize::letters(word) >= minimum_word_length
+        };
+        match self {
+            Dictionary::WordNet => wordnet::pools(&data_dir.join("dict"), keep),
+            Dictionary::Moby => moby::pools(&data_dir.join(moby::POS_FILE), keep),
+            Dictionary::Scowl(size) => {
+                let words = scowl::words(&data_dir.join(scowl::SCOWL_DIR), *size);
+                moby::pools(&data_dir.join(moby::POS_FILE), |word| {
+                    keep(word) && words.contains(word)
+                })
+            }
+        }
+    }
+}
+
+/// The index of `pos` in `Pools::words`.
+pub fn pool_index(pos: PartOfSpeech) -> usize {
+    PartOfSpeech::ALL
+        .iter()
+        .position(|&other| other == pos)
+        .expect("Every part of speech is in ALL")
+}
@@ -0,0 +1,401 @@
+//! `phraseforge doctor`: diagnoses the environment instead of panicking in it.
+//!
+//! Each check prints one line with its outcome and, when something is wrong,
+//! the command or setting that fixes it. Nothing is modified, so the output
+//! is safe to paste into a bug report.
+
+use crate::cache;
+use crate::config::{self, Config, SECONDS_PER_DAY};
+use crate::datadir;
+use crate::diceware::{EFF_LARGE_WORDLIST_FILE, EFF_LARGE_WORDLIST_SIZE};
+use crate::sources::Sources;
+use crate::update;
+use crate::{bundled, generation_params, validate_word_list, GENERATED_FILES};
+use crate::{word_lists_exist, MIN_WORD_LIST_ENTRIES, PROPER_NOUNS_FILE};
+use clap::ArgMatches;
+use reqwest::StatusCode;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long each reachability check waits before calling a source down.
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+#[derive(Default)]
+struct Report {
+    warnings: usize,
+    failures: usize,
+}
+
+impl Report {
+    fn line(&mut self, status: Status, check: &str, detail: impl Display, fix: Option<String>) {
+        let tag = match status {
+            Status::Ok => "ok",
+            Status::Warn => {
+                self.warnings += 1;
+                "warn"
+            }
+            Status::Fail => {
+                self.failures += 1;
+                "FAIL"
+            }
+        };
+        println!("{:<5} {:<22} {}", tag, check, detail);
+        if let Some(fix) = fix {
+            println!("{:<5} {:<22} fix: {}", "", "", fix);
+        }
+    }
+}
+
+/// Loads the config file the way a normal run would, reporting instead of
+/// exiting on errors so the remaining checks still run.
+fn check_config(matches: &ArgMatches, report: &mut Report) -> Config {
+    let explicit = matches.get_one::<PathBuf>("config");
+    let path = explicit.cloned().or_else(config::default_config_path);
+    match &path {
+        Some(path) if explicit.is_some() || path.exists() => match Config::load(Some(path)) {
+            Ok(config) => {
+                report.line(Status::Ok, "config", path.display(), None);
+                return config;
+            }
+            Err(e) => report.line(
+                Status::Fail,
+                "config",
+                e,
+                Some(format!(
+                    "correct {} or move it aside; defaults are used below",
+                    path.display()
+                )),
+            ),
+        },
+        Some(path) => report.line(
+            Status::Ok,
+            "config",
+            format!("none at {} (defaults)", path.display()),
+            None,
+        ),
+        None => report.line(Status::Ok, "config", "no config directory (defaults)", None),
+    }
+    Config::default()
+}
+
+fn check_sources(matches: &ArgMatches, config: &Config, report: &mut Report) -> Sources {
+    let sources = Sources::new(matches, &config.sources).unwrap_or_else(|e| {
+        report.line(
+            Status::Fail,
+            "sources",
+            e,
+            Some("fix the [sources] section of the config file".to_string()),
+        );
+        Sources::new(matches, &Default::default()).expect("Default sources are valid")
+    });
+    report.line(Status::Ok, "dictionary", sources.dictionary.name(), None);
+    for (name, url) in sources.dictionary_sources() {
+        report.line(Status::Ok, &name.to_lowercase(), url, None);
+    }
+    report.line(
+        Status::Ok,
+        "frequency list",
+        format!(
+            "{} ({})",
+            sources.frequency_list, sources.frequency_list_variant
+        ),
+        None,
+    );
+    report.line(Status::Ok, "eff word list", &sources.eff_word_list, None);
+    report.line(
+        Status::Ok,
+        "common passwords",
+        &sources.common_passwords,
+        None,
+    );
+    match (&sources.trust_key, &sources.signed_manifest) {
+        (Some(key), Some(manifest)) => report.line(
+            Status::Ok,
+            "signed manifest",
+            format!("{} (signed by {})", manifest, key.describe()),
+            None,
+        ),
+        _ => report.line(
+            Status::Ok,
+            "signed manifest",
+            "none; downloads are not authenticated",
+            None,
+        ),
+    }
+    if let Some(proxy) = sources.proxy_url() {
+        match reqwest::Proxy::all(proxy) {
+            Ok(_) => report.line(Status::Ok, "proxy", proxy, None),
+            Err(e) => report.line(
+                Status::Fail,
+                "proxy",
+                format!("{}: {}", proxy, e),
+                Some("use a URL like http://proxy.example.com:3128".to_string()),
+            ),
+        }
+    }
+    sources
+}
+
+/// Reports on the data directory, returning the cache directory that
+/// phrase generation would use, if there is one yet.
+fn check_data_dir(root: &Path, report: &mut Report) -> Option<PathBuf> {
+    if !root.exists() {
+        report.line(
+            Status::Warn,
+            "data directory",
+            format!("{} does not exist yet", root.display()),
+            Some("it is created on the first run that generates phrases".to_string()),
+        );
+        return None;
+    }
+    let cache_dir = |root: &Path| root.join(format!("v{}", cache::CACHE_VERSION));
+    let probe = root.join(".doctor-probe");
+    let error = match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            report.line(
+                Status::Ok,
+                "data directory",
+                format!("{} (writable)", root.display()),
+                None,
+            );
+            return Some(cache_dir(root));
+        }
+        Err(e) => e,
+    };
+    if word_lists_exist(&cache_dir(root)) {
+        report.line(
+            Status::Ok,
+            "data directory",
+            format!(
+                "{} (read-only; its word lists are used as they are)",
+                root.display()
+            ),
+            None,
+        );
+        return Some(cache_dir(root));
+    }
+    let fallback = datadir::fallback_root();
+    let fallback_writable = datadir::writable(&fallback).is_ok();
+    report.line(
+        if fallback_writable {
+            Status::Warn
+        } else {
+            Status::Fail
+        },
+        "data directory",
+        if fallback_writable {
+            format!(
+                "{} is not writable ({}); the cache is kept in {} instead",
+                root.display(),
+                error,
+                fallback.display()
+            )
+        } else {
+            format!("{} is not writable: {}", root.display(), error)
+        },
+        Some(if cfg!(windows) {
+            format!(
+                "give your account write access to {} in its Properties > Security, or pass --cache-dir",
+                root.display()
+            )
+        } else {
+            format!(
+                "run `chmod u+rwx {}`, or pass --cache-dir with a writable directory",
+                root.display()
+            )
+        }),
+    );
+    Some(cache_dir(&fallback)).filter(|dir| dir.exists())
+}
+
+fn check_cache(dir: &Path, params: &cache::GenerationParams, report: &mut Report) {
+    match cache::check(dir, params) {
+        Ok(()) => report.line(Status::Ok, "cache manifest", "up to date", None),
+        Err(stale) if stale.sources_damaged => report.line(
+            Status::Fail,
+            "cache manifest",
+            stale.reason,
+            Some("run `phraseforge --redownload`".to_string()),
+        ),
+        Err(stale) => report.line(
+            Status::Warn,
+            "cache manifest",
+            stale.reason,
+            Some("the word lists are rebuilt automatically on the next run".to_string()),
+        ),
+    }
+
+    match cache::corrupted_files(dir) {
+        None => {}
+        Some((checked, corrupted)) if corrupted.is_empty() => report.line(
+            Status::Ok,
+            "checksums",
+            format!("{} files match the manifest", checked),
+            None,
+        ),
+        Some((_, corrupted)) => report.line(
+            Status::Fail,
+            "checksums",
+            format!("{} changed since download", corrupted.join(", ")),
+            Some("run `phraseforge --redownload`".to_string()),
+        ),
+    }
+}
+
+fn check_age(dir: &Path, config: &Config, sources: &Sources, report: &mut Report) {
+    let Some(refreshed) = update::last_refreshed(dir, &sources.frequency_list_file()) else {
+        return;
+    };
+    let age = refreshed.elapsed().unwrap_or_default();
+    let detail = format!(
+        "frequency data {} days old",
+        age.as_secs() / SECONDS_PER_DAY
+    );
+    if age <= config.cache_max_age() {
+        report.line(Status::Ok, "word list age", detail, None);
+    } else {
+        report.line(
+            Status::Warn,
+            "word list age",
+            detail,
+            Some(
+                "run `phraseforge update`, or set cache_auto_refresh = true in the config file"
+                    .to_string(),
+            ),
+        );
+    }
+}
+
+fn check_word_lists(dir: &Path, report: &mut Report) {
+    for file in GENERATED_FILES {
+        let path = dir.join(file);
+        if !path.exists() {
+            let fix = if bundled::AVAILABLE {
+                "the bundled lists are used until a run with network access generates it"
+            } else {
+                "run phraseforge once with network access to generate it"
+            };
+            report.line(Status::Warn, file, "missing", Some(fix.to_string()));
+            continue;
+        }
+        let min_entries = if file == PROPER_NOUNS_FILE {
+            0
+        } else {
+            MIN_WORD_LIST_ENTRIES
+        };
+        match validate_word_list(&path, min_entries) {
+            Ok(()) => {
+                let count = fs::read_to_string(&path)
+                    .map(|text| text.lines().count())
+                    .unwrap_or(0);
+                report.line(Status::Ok, file, format!("{} words", count), None);
+            }
+            Err(e) => report.line(
+                Status::Fail,
+                file,
+                e,
+                Some("run `phraseforge --redownload`".to_string()),
+            ),
+        }
+    }
+
+    let eff = dir.join(EFF_LARGE_WORDLIST_FILE);
+    match fs::read_to_string(&eff) {
+        Ok(text) if text.lines().count() == EFF_LARGE_WORDLIST_SIZE => report.line(
+            Status::Ok,
+            EFF_LARGE_WORDLIST_FILE,
+            format!("{} words", EFF_LARGE_WORDLIST_SIZE),
+            None,
+        ),
+        Ok(text) => report.line(
+            Status::Fail,
+            EFF_LARGE_WORDLIST_FILE,
+            format!(
+                "{} words, expected {}",
+                text.lines().count(),
+                EFF_LARGE_WORDLIST_SIZE
+            ),
+            Some("run `phraseforge dice --redownload`".to_string()),
+        ),
+        Err(_) => report.line(
+            Status::Ok,
+            EFF_LARGE_WORDLIST_FILE,
+            "not downloaded (only `dice` needs it)",
+            None,
+        ),
+    }
+}
+
+fn check_network(sources: &Sources, report: &mut Report) {
+    let fix = if sources.proxy_url().is_some() {
+        "check that the proxy is up and allows this host"
+    } else {
+        "check your connection, set --proxy, or point [sources] mirror at a reachable copy"
+    };
+    let client = sources.client();
+    let mut targets: Vec<(String, &str)> = sources
+        .dictionary_sources()
+        .into_iter()
+        .map(|(name, url)| (format!("reach {}", name.to_lowercase()), url))
+        .collect();
+    targets.push(("reach frequency".to_string(), &sources.frequency_list));
+    targets.push(("reach eff".to_string(), &sources.eff_word_list));
+    targets.push(("reach common".to_string(), &sources.common_passwords));
+    for (name, url) in targets {
+        match client.head(url).timeout(NETWORK_TIMEOUT).send() {
+            // Some servers refuse HEAD but would serve the GET.
+            Ok(response)
+                if response.status().is_success()
+                    || response.status() == StatusCode::METHOD_NOT_ALLOWED =>
+            {
+                report.line(Status::Ok, &name, response.status(), None)
+            }
+            Ok(response) => report.line(
+                Status::Fail,
+                &name,
+                format!("{} returned {}", url, response.status()),
+                Some(fix.to_string()),
+            ),
+            Err(e) => report.line(
+                Status::Fail,
+                &name,
+                format!("cannot reach {}: {}", url, e),
+                Some(fix.to_string()),
+            ),
+        }
+    }
+}
+
+/// Runs every check and prints the report; returns false if any failed.
+pub fn run(matches: &ArgMatches, root: &Path, network: bool) -> bool {
+    let mut report = Report::default();
+    println!("phraseforge {}", env!("CARGO_PKG_VERSION"));
+
+    let config = check_config(matches, &mut report);
+    let sources = check_sources(matches, &config, &mut report);
+    let params = generation_params(&sources, matches.get_flag("assign-part-of-speech"));
+    if let Some(dir) = datadir::system_cache(&params) {
+        report.line(Status::Ok, "system word lists", dir.display(), None);
+    }
+    if let Some(dir) = check_data_dir(root, &mut report) {
+        check_cache(&dir, &params, &mut report);
+        check_age(&dir, &config, &sources, &mut report);
+        check_word_lists(&dir, &mut report);
+    }
+    if network {
+        check_network(&sources, &mut report);
+    }
+
+    println!("{} problems, {} warnings", report.failures, report.warnings);
+    report.failures == 0
+}
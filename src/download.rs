@@ -0,0 +1,228 @@
+//! Fetches several sources at once, streaming each straight to disk.
+//!
+//! First-run setup needs both the WordNet archive and the frequency list;
+//! downloading them concurrently roughly halves the wait on fast
+//! connections. Archives are unpacked as they arrive, so the tarball itself
+//! never touches the disk and only the entries asked for are written.
+
+use crate::provenance;
+use crate::update;
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinSet;
+
+/// Chunks buffered between the network and the unpacker.
+const UNPACK_BACKLOG: usize = 16;
+
+pub enum Destination {
+    /// Saved as-is at this path.
+    File(PathBuf),
+    /// A `.tar.gz` unpacked into this directory, writing only the entries
+    /// `keep` accepts.
+    Unpacked {
+        directory: PathBuf,
+        keep: fn(&Path) -> bool,
+    },
+}
+
+pub struct Download {
+    pub name: &'static str,
+    pub url: String,
+    pub destination: Destination,
+    /// The SHA-256 a signed manifest lists for it; anything else is
+    /// rejected.
+    pub sha256: Option<String>,
+}
+
+/// Unpacks the entries of a gzipped tarball that `keep` accepts. It reads
+/// from any source, so it is tested on every platform without a network.
+pub fn unpack(archive: impl Read, directory: &Path, keep: fn(&Path) -> bool) -> io::Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if keep(&entry.path()?) {
+            entry.unpack_in(directory)?;
+        }
+    }
+    Ok(())
+}
+
+/// Presents the chunks of a response arriving on a channel as a `Read`.
+struct ChunkReader {
+    chunks: Receiver<Bytes>,
+    current: Bytes,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.chunks.recv() {
+                Ok(chunk) => self.current = chunk,
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current.split_to(n));
+        Ok(n)
+    }
+}
+
+/// Compares what was downloaded with the signed manifest's hash, if any.
+fn check(download: &Download, hasher: Sha256) -> Result<(), String> {
+    match &download.sha256 {
+        Some(expected) => {
+            provenance::check_sha256(&download.url, expected, &format!("{:x}", hasher.finalize()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Streams `download` to its destination. Files are written beside their
+/// final path and moved into place once complete, so an interrupted
+/// download never leaves a truncated file under the real name.
+async fn fetch(client: Client, download: Download) -> Result<(), String> {
+    log::info!("Downloading {}...", download.name);
+    let fail = |e: &dyn std::fmt::Display| format!("cannot download {}: {}", download.url, e);
+
+    let mut response = client
+        .get(&download.url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| fail(&e))?;
+
+    match &download.destination {
+        Destination::File(path) => {
+            let mut temporary = path.as_os_str().to_owned();
+            temporary.push(".tmp");
+            let mut file = File::create(&temporary).await.map_err(|e| fail(&e))?;
+            let mut hasher = Sha256::new();
+            while let Some(chunk) = response.chunk().await.map_err(|e| fail(&e))? {
+                hasher.update(&chunk);
+                file.write_all(&chunk).await.map_err(|e| fail(&e))?;
+            }
+            file.flush().await.map_err(|e| fail(&e))?;
+            drop(file);
+            if let Err(e) = check(&download, hasher) {
+                let _ = tokio::fs::remove_file(&temporary).await;
+                return Err(e);
+            }
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || update::rename(Path::new(&temporary), &path))
+                .await
+                .map_err(|e| fail(&e))?
+                .map_err(|e| fail(&e))?;
+        }
+        Destination::Unpacked { directory, keep } => {
+            let (sender, chunks) = sync_channel(UNPACK_BACKLOG);
+            let directory = directory.clone();
+            let keep = *keep;
+            let unpacker = tokio::task::spawn_blocking(move || {
+                let reader = ChunkReader {
+                    chunks,
+                    current: Bytes::new(),
+                };
+                unpack(reader, &directory, keep)
+            });
+            let mut hasher = Sha256::new();
+            while let Some(chunk) = response.chunk().await.map_err(|e| fail(&e))? {
+                hasher.update(&chunk);
+                // A send only fails once the unpacker has given up; its
+                // error is reported below.
+                if sender.send(chunk).is_err() {
+                    break;
+                }
+            }
+            drop(sender);
+            unpacker
+                .await
+                .map_err(|e| fail(&e))?
+                .map_err(|e| fail(&e))?;
+            // What was unpacked stays in the staging directory, which is
+            // discarded, so a mismatch installs nothing.
+            check(&download, hasher)?;
+        }
+    }
+    log::debug!("Downloaded {}", download.name);
+    Ok(())
+}
+
+/// Runs every download concurrently and waits for all of them, reporting
+/// the first failure.
+pub fn download_all(client: &Client, downloads: Vec<Download>) -> Result<(), String> {
+    if downloads.is_empty() {
+        return Ok(());
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to start download runtime");
+
+    runtime.block_on(async {
+        let mut tasks = JoinSet::new();
+        for download in downloads {
+            tasks.spawn(fetch(client.clone(), download));
+        }
+        let mut result = Ok(());
+        while let Some(outcome) = tasks.join_next().await {
+            let outcome = outcome.map_err(|e| e.to_string()).and_then(|r| r);
+            if result.is_ok() {
+                result = outcome;
+            }
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+
+    fn archive(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn index_file(path: &Path) -> bool {
+        path.parent() == Some(Path::new("dict")) && path.file_stem() == Some("index".as_ref())
+    }
+
+    #[test]
+    fn unpacks_only_kept_entries_with_native_paths() {
+        let directory =
+            std::env::temp_dir().join(format!("phraseforge-unpack-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let archive = archive(&[
+            ("dict/index.noun", "otter n 1 1"),
+            ("dict/data.noun", "00001 otter"),
+            ("README", "WordNet"),
+        ]);
+
+        unpack(&archive[..], &directory, index_file).unwrap();
+
+        let unpacked = directory.join("dict").join("index.noun");
+        assert_eq!(fs::read_to_string(&unpacked).unwrap(), "otter n 1 1");
+        assert!(!directory.join("dict").join("data.noun").exists());
+        assert!(!directory.join("README").exists());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
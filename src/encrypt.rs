@@ -0,0 +1,67 @@
+//! Encrypted output files (`--output`), so bulk-generated secrets are never
+//! written to disk in cleartext. Encryption is delegated to `gpg` or `age`,
+//! whichever the recipients are for.
+
+use crate::store::run_with_input;
+use std::path::PathBuf;
+use std::process::Command;
+
+pub enum Recipients {
+    /// GnuPG key IDs, fingerprints or user IDs.
+    Gpg(Vec<String>),
+    /// age public keys (`age1...`) or SSH public keys.
+    Age(Vec<String>),
+}
+
+impl Recipients {
+    /// `gpg` or `age`, set up to encrypt stdin to these recipients.
+    pub fn encrypt_command(&self) -> Command {
+        let mut command;
+        match self {
+            Recipients::Gpg(key_ids) => {
+                command = Command::new("gpg");
+                command.args(["--batch", "--yes", "--encrypt"]);
+                for key_id in key_ids {
+                    command.arg("--recipient").arg(key_id);
+                }
+            }
+            Recipients::Age(recipients) => {
+                command = Command::new("age");
+                command.arg("--encrypt");
+                for recipient in recipients {
+                    command.arg("--recipient").arg(recipient);
+                }
+            }
+        }
+        command
+    }
+}
+
+/// Everything that would have gone to stdout, held in memory until it can be
+/// encrypted in one go.
+pub struct EncryptedOutput {
+    pub path: PathBuf,
+    pub recipients: Recipients,
+    pub buffer: String,
+}
+
+impl EncryptedOutput {
+    pub fn new(path: PathBuf, recipients: Recipients) -> Self {
+        EncryptedOutput {
+            path,
+            recipients,
+            buffer: String::new(),
+        }
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+    }
+
+    pub fn write(&self) -> Result<(), String> {
+        let mut command = self.recipients.encrypt_command();
+        command.arg("--output").arg(&self.path);
+        run_with_input(command, &self.buffer)
+    }
+}
@@ -0,0 +1,281 @@
+//! Entropy estimates for generated phrases.
+//!
+//! Estimates are min-entropy: when a mode makes some phrases more likely than
+//! others (alliteration picks a letter first, so letters with few words yield
+//! more predictable phrases), the figure reported is for the worst case.
+//! Slots that are chosen together, like a rhyming pair, count as one slot
+//! whose pool is the set of valid combinations.
+
+use crate::caps::{self, CapsPattern};
+use crate::generate::{
+    alliteration_letters, count_eligible, eligible, number_choices, render_slot, rhyme_groups,
+    rhyme_slots, slot_initials, GenerationOptions, MinFrequency,
+};
+use crate::template::{Slot, Token};
+use crate::{leet, mutate, phonetic, WordLists};
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Serialize)]
+pub struct SlotEntropy {
+    pub slot: String,
+    pub pool_size: usize,
+    pub bits: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Entropy {
+    pub slots: Vec<SlotEntropy>,
+    pub bits_per_phrase: f64,
+}
+
+pub fn slot_entropy(slot: &str, pool_size: usize) -> SlotEntropy {
+    let bits = if pool_size > 0 {
+        (pool_size as f64).log2()
+    } else {
+        0.0
+    };
+    SlotEntropy {
+        slot: slot.to_string(),
+        pool_size,
+        bits,
+    }
+}
+
+/// Per-slot pool sizes for one alliteration letter (or none), ignoring
+/// rhyme, leet and mutation adjustments. Unless repeats are allowed, each
+/// word slot's pool counts as shrunk by every word chosen before it; under
+/// `--distinct-sounds`, by as many words as share a sound in that pool. A
+/// `--series` phrase's pools are short the words the others took too.
+pub fn template_slots(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    letter: Option<char>,
+) -> Vec<SlotEntropy> {
+    let mut words_before = 0;
+    options
+        .template
+        .slots()
+        .zip(slot_initials(options, letter))
+        .map(|(slot, initial)| {
+            let pool_size = match word_lists.pool(slot) {
+                Some(entries) => {
+                    let min_frequency = options.min_frequency.of_slot(slot);
+                    let eligible_count = count_eligible(entries, &min_frequency, initial);
+                    let excluded = if options.distinct_sounds {
+                        let alike = phonetic::largest_group(
+                            eligible(entries, &min_frequency, initial)
+                                .map(|entry| entry.word.as_str()),
+                        );
+                        words_before * alike
+                    } else if options.allow_repeats {
+                        0
+                    } else {
+                        words_before
+                    };
+                    words_before += 1;
+                    eligible_count.saturating_sub(excluded + options.series_taken)
+                }
+                None => number_choices(options).len(),
+            };
+            slot_entropy(slot.name(), pool_size)
+        })
+        .collect()
+}
+
+fn total_bits(slots: &[SlotEntropy]) -> f64 {
+    slots.iter().map(|s| s.bits).sum()
+}
+
+/// Length of the shortest word `slot` can produce. Irregular plurals and
+/// past tenses can be a letter shorter than their lemma ("mouse" and
+/// "mice"), so noun and verb slots allow for that.
+fn shortest_word(word_lists: &WordLists, options: &GenerationOptions, slot: Slot) -> usize {
+    let shortest = word_lists
+        .pool(slot)
+        .and_then(|entries| {
+            eligible(entries, &options.min_frequency.of_slot(slot), None)
+                .map(|entry| entry.word.chars().count())
+                .min()
+        })
+        .unwrap_or(0);
+    match slot {
+        Slot::Noun | Slot::Verb(_) => shortest.saturating_sub(1),
+        _ => shortest,
+    }
+}
+
+/// Length of the shortest phrase the template can produce.
+fn min_phrase_length(word_lists: &WordLists, options: &GenerationOptions) -> usize {
+    options
+        .template
+        .tokens()
+        .iter()
+        .map(|token| match token {
+            Token::Literal(text) => text.graphemes(true).count(),
+            Token::Slot(Slot::Number) => 1,
+            Token::Slot(slot) => shortest_word(word_lists, options, *slot),
+        })
+        .sum()
+}
+
+/// Bits from `--caps-pattern`'s random choices: which slots `random:N`
+/// capitalizes, and each `?` the slot's shortest word reaches.
+fn caps_bits(word_lists: &WordLists, options: &GenerationOptions, pattern: &CapsPattern) -> f64 {
+    let word_slots: Vec<Slot> = options
+        .template
+        .slots()
+        .filter(|&slot| slot != Slot::Number)
+        .collect();
+    let either: f64 = match pattern {
+        CapsPattern::Masks(masks) => word_slots
+            .iter()
+            .zip(masks)
+            .map(|(&slot, mask)| caps::either_bits(mask, shortest_word(word_lists, options, slot)))
+            .sum(),
+        CapsPattern::Random(_) => 0.0,
+    };
+    either + pattern.slot_bits(word_slots.len())
+}
+
+/// Entropy of a single phrase, assuming each template slot is drawn
+/// uniformly from the words that pass the frequency filter and any active
+/// constraints.
+pub fn estimate(word_lists: &WordLists, options: &GenerationOptions) -> Entropy {
+    let slots = if options.alliterate {
+        let letters = alliteration_letters(word_lists, options);
+        let worst = letters
+            .iter()
+            .map(|&letter| template_slots(word_lists, options, Some(letter)))
+            .min_by(|a, b| total_bits(a).total_cmp(&total_bits(b)))
+            .unwrap_or_default();
+
+        let mut slots = vec![slot_entropy("alliteration-letter", letters.len())];
+        slots.extend(worst);
+        slots
+    } else if let (true, Some((first, second))) = (options.rhyme, rhyme_slots(options)) {
+        let pairs = rhyme_groups(word_lists, options).map_or(0, |g| g.pair_count());
+        let mut slots = template_slots(word_lists, options, None);
+        slots.remove(second);
+        slots[first] = slot_entropy("rhyme-pair", pairs);
+        slots
+    } else {
+        template_slots(word_lists, options, None)
+    };
+
+    let mut slots = slots;
+    if let Some(level) = options.leet {
+        // Letters are swapped in the rendered words, so every form a noun
+        // can take counts: singular, plural after a number, or both.
+        let mut plural_forms: Vec<bool> = if options.template.has_number() {
+            number_choices(options).iter().map(|&n| n > 1).collect()
+        } else {
+            vec![false]
+        };
+        plural_forms.sort();
+        plural_forms.dedup();
+        let bits: usize = options
+            .template
+            .slots()
+            .filter_map(|slot| Some((slot, word_lists.pool(slot)?)))
+            .map(|(slot, entries)| {
+                let min_frequency = options.min_frequency.of_slot(slot);
+                let rendered = eligible(entries, &min_frequency, None).flat_map(|entry| {
+                    plural_forms
+                        .iter()
+                        .map(|&plural| render_slot(&entry.word, slot, plural, options))
+                });
+                leet::guaranteed_bits(rendered, level)
+            })
+            .sum();
+        slots.push(SlotEntropy {
+            slot: "leet".to_string(),
+            pool_size: 1 << bits.min(63),
+            bits: bits as f64,
+        });
+    }
+
+    if let Some(pattern) = &options.caps {
+        let bits = caps_bits(word_lists, options, pattern);
+        if bits > 0.0 {
+            slots.push(SlotEntropy {
+                slot: "capitalization".to_string(),
+                pool_size: bits.exp2() as usize,
+                bits,
+            });
+        }
+    }
+
+    if options.mutations > 0 {
+        let bits = mutate::position_bits(min_phrase_length(word_lists, options), options.mutations);
+        slots.push(SlotEntropy {
+            slot: "mutation-positions".to_string(),
+            pool_size: bits.exp2() as usize,
+            bits,
+        });
+    }
+
+    let bits_per_phrase = total_bits(&slots);
+    Entropy {
+        slots,
+        bits_per_phrase,
+    }
+}
+
+/// Changes to `options` that would raise the estimate, with the bits each
+/// adds, largest first: what to suggest when `--min-entropy` is not met.
+/// Word filters are left out, since undoing one means reloading the lists.
+pub fn relaxations(word_lists: &WordLists, options: &GenerationOptions) -> Vec<(String, f64)> {
+    let mut candidates: Vec<(String, GenerationOptions)> = Vec::new();
+    let mut relax = |label: &str, change: fn(&mut GenerationOptions)| {
+        let mut relaxed = options.clone();
+        change(&mut relaxed);
+        candidates.push((label.to_string(), relaxed));
+    };
+    if options.alliterate {
+        relax("drop --alliterate", |o| o.alliterate = false);
+    }
+    if options.rhyme {
+        relax("drop --rhyme", |o| o.rhyme = false);
+    }
+    if options.acrostic.is_some() {
+        relax("drop --acrostic", |o| o.acrostic = None);
+    }
+    if !options.min_frequency.is_zero() {
+        relax("lower --min-frequency to 0", |o| {
+            o.min_frequency = MinFrequency::uniform(0)
+        });
+    }
+    // Distinct sounds rule out repeats whatever --allow-repeats says.
+    if !options.allow_repeats && !options.distinct_sounds {
+        relax("pass --allow-repeats", |o| o.allow_repeats = true);
+    }
+    if options.distinct_sounds {
+        relax("drop --distinct-sounds", |o| o.distinct_sounds = false);
+    }
+    relax("add --mutate 2", |o| o.mutations += 2);
+
+    let current = estimate(word_lists, options).bits_per_phrase;
+    let mut gains: Vec<(String, f64)> = candidates
+        .into_iter()
+        .map(|(label, relaxed)| {
+            let gain = estimate(word_lists, &relaxed).bits_per_phrase - current;
+            (label, gain)
+        })
+        .collect();
+
+    // Another slot is worth about as much as a draw from its pool.
+    if let Some(nouns) = word_lists.pool(Slot::Noun) {
+        let count = count_eligible(nouns, &options.min_frequency.of_slot(Slot::Noun), None);
+        if count > 0 {
+            gains.push((
+                "add a {noun} slot to the template".to_string(),
+                (count as f64).log2(),
+            ));
+        }
+    }
+
+    gains.retain(|(_, gain)| *gain > 0.05);
+    gains.sort_by(|a, b| b.1.total_cmp(&a.1));
+    gains
+}
@@ -0,0 +1,127 @@
+//! Typing comfort scores.
+//!
+//! A phrase typed dozens of times a day should flow under the fingers. Each
+//! pair of adjacent letters is scored: switching hands (or thumbs, on a
+//! phone) is rewarded, while typing both letters with the same finger, or
+//! stretching between the top and bottom rows with one hand, is penalized.
+//! A word's score is the average over its letter pairs, and `--easy-to-type`
+//! keeps words scoring at least the layout's threshold, which roughly half
+//! of common English words meet.
+
+use crate::filter::WordFilter;
+use crate::PartOfSpeech;
+
+const ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+/// How far each row sits to the right on a phone keyboard, in key widths.
+const MOBILE_ROW_OFFSETS: [f64; 3] = [0.0, 0.5, 1.5];
+/// Touch-typing finger for each column, counted from the left pinky.
+const FINGERS: [u8; 10] = [0, 1, 2, 3, 3, 4, 4, 5, 6, 7];
+/// Columns from here on belong to the right hand (or right thumb).
+const RIGHT_HAND_COLUMN: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// Ten-finger touch typing on a QWERTY keyboard.
+    Qwerty,
+    /// Two-thumb typing on a phone's QWERTY keyboard.
+    Mobile,
+}
+
+impl Layout {
+    pub const NAMES: [&'static str; 2] = ["qwerty", "mobile"];
+
+    pub fn from_name(name: &str) -> Option<Layout> {
+        match name {
+            "qwerty" => Some(Layout::Qwerty),
+            "mobile" => Some(Layout::Mobile),
+            _ => None,
+        }
+    }
+
+    /// Median score of common English words on this layout.
+    fn threshold(&self) -> f64 {
+        match self {
+            Layout::Qwerty => 0.4,
+            Layout::Mobile => 0.15,
+        }
+    }
+}
+
+/// Row and column of a letter key.
+fn key(letter: char) -> Option<(usize, usize)> {
+    ROWS.iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.find(letter).map(|column| (row, column)))
+}
+
+fn pair_score(layout: Layout, a: (usize, usize), b: (usize, usize)) -> f64 {
+    let ((row_a, column_a), (row_b, column_b)) = (a, b);
+    if (column_a >= RIGHT_HAND_COLUMN) != (column_b >= RIGHT_HAND_COLUMN) {
+        return 1.0;
+    }
+    match layout {
+        Layout::Qwerty if FINGERS[column_a] == FINGERS[column_b] => -1.0,
+        Layout::Qwerty if row_a.abs_diff(row_b) == 2 => -0.5,
+        Layout::Qwerty => 0.0,
+        Layout::Mobile => {
+            // One thumb travelling; a neighbouring key costs a third.
+            let x = |row: usize, column: usize| column as f64 + MOBILE_ROW_OFFSETS[row];
+            let dx = x(row_a, column_a) - x(row_b, column_b);
+            let dy = row_a as f64 - row_b as f64;
+            -(dx * dx + dy * dy).sqrt() / 3.0
+        }
+    }
+}
+
+/// Average comfort of typing each adjacent pair of letters in `word`, from
+/// -1 (awkward throughout) to 1 (hands alternate throughout). Repeated
+/// letters count as neutral, and words too short to have a pair as perfect.
+pub fn score(layout: Layout, word: &str) -> f64 {
+    let keys: Vec<Option<(usize, usize)>> = word.chars().map(key).collect();
+    let pairs = keys.len().saturating_sub(1);
+    if pairs == 0 {
+        return 1.0;
+    }
+    let total: f64 = keys
+        .windows(2)
+        .map(|pair| match (pair[0], pair[1]) {
+            (Some(a), Some(b)) if a != b => pair_score(layout, a, b),
+            _ => 0.0,
+        })
+        .sum();
+    total / pairs as f64
+}
+
+pub struct ErgonomicsFilter {
+    layout: Layout,
+}
+
+impl ErgonomicsFilter {
+    pub fn new(layout: Layout) -> Self {
+        ErgonomicsFilter { layout }
+    }
+}
+
+impl WordFilter for ErgonomicsFilter {
+    fn name(&self) -> String {
+        format!("easy-to-type {:?}", self.layout).to_lowercase()
+    }
+
+    fn keep(&self, _pos: PartOfSpeech, word: &str) -> bool {
+        score(self.layout, word) >= self.layout.threshold()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternating_hands_beat_one_handed_words() {
+        // "social" alternates hands on every pair; "decrease" stays on the left
+        assert!(score(Layout::Qwerty, "social") > score(Layout::Qwerty, "decrease"));
+        // Both letters on the left index finger
+        assert_eq!(score(Layout::Qwerty, "fr"), -1.0);
+        assert!(score(Layout::Mobile, "social") > score(Layout::Mobile, "decrease"));
+    }
+}
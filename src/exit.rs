@@ -0,0 +1,30 @@
+//! Exit codes, a contract wrapper scripts can branch on instead of parsing
+//! error messages. They are listed at the end of `--help`.
+
+use std::fmt::Display;
+
+/// Any failure without a more specific code below.
+pub const FAILURE: i32 = 1;
+/// A bad option or value; clap uses the same code for its usage errors.
+pub const INVALID_ARGUMENTS: i32 = 2;
+/// A source could not be downloaded.
+pub const NETWORK: i32 = 3;
+/// The cached sources or word lists are damaged.
+pub const CACHE_CORRUPTION: i32 = 4;
+/// No phrase can satisfy the policy, acrostic or entropy requirement.
+pub const UNSATISFIABLE: i32 = 5;
+
+pub const HELP: &str = "\
+Exit status:
+  0  success
+  1  other failure
+  2  invalid arguments
+  3  network failure
+  4  cache corruption (run again with --redownload)
+  5  policy, acrostic or entropy requirement unsatisfiable";
+
+/// Prints `message` as an error and exits with `code`.
+pub fn fail(code: i32, message: impl Display) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(code)
+}
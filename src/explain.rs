@@ -0,0 +1,58 @@
+//! Word definitions for `--explain`.
+//!
+//! Each word is described by the gloss of its most frequent WordNet sense,
+//! without the example sentences that follow the definition.
+
+use crate::template::{Slot, Template};
+use crate::wordnet;
+use crate::PartOfSpeech;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub struct Glossary {
+    definitions: HashMap<PartOfSpeech, HashMap<String, String>>,
+}
+
+/// The definition part of a gloss, e.g. `a sturdy mammal; "badgers dig"`
+/// becomes `a sturdy mammal`.
+fn definition(gloss: &str) -> &str {
+    gloss.split("; \"").next().unwrap_or(gloss).trim()
+}
+
+fn load_definitions(dict_dir: &Path, pos: PartOfSpeech) -> HashMap<String, String> {
+    let suffix = pos.wordnet_suffix();
+    let synsets = wordnet::synsets_by_offset(&dict_dir.join(format!("data.{}", suffix)));
+    wordnet::primary_senses(&dict_dir.join(format!("index.{}", suffix)))
+        .into_iter()
+        .filter_map(|(lemma, offset)| {
+            let synset = synsets.get(&offset)?;
+            Some((lemma, definition(&synset.gloss).to_string()))
+        })
+        .collect()
+}
+
+impl Glossary {
+    /// Loads definitions for the parts of speech the template uses.
+    pub fn new(dict_dir: &Path, template: &Template) -> Self {
+        let mut definitions = HashMap::new();
+        for pos in template.slots().filter_map(|slot| slot.part_of_speech()) {
+            definitions
+                .entry(pos)
+                .or_insert_with(|| load_definitions(dict_dir, pos));
+        }
+        Glossary { definitions }
+    }
+
+    /// A one-line description such as `badger (noun): sturdy carnivorous mammal`.
+    pub fn describe(&self, slot: Slot, word: &str) -> String {
+        let Some(pos) = slot.part_of_speech() else {
+            return word.to_string();
+        };
+        let definition = self
+            .definitions
+            .get(&pos)
+            .and_then(|defs| defs.get(word))
+            .map_or("no definition available", String::as_str);
+        format!("{} ({}): {}", word, pos.name(), definition)
+    }
+}
@@ -0,0 +1,188 @@
+//! `phraseforge explore`: how the number of words, the number slot and the
+//! frequency cutoff trade entropy against length, for picking a setup that
+//! fits a site's length limit while meeting an entropy goal.
+//!
+//! Each row is the current options with another template and cutoff. Bits
+//! are the estimate `--verbose` prints. The average length is taken over the
+//! eligible words as they would be rendered (plural after a number, in the
+//! verb form), and the maximum is the longest phrase the row can produce,
+//! which is what a length limit has to allow.
+
+use crate::entropy;
+use crate::generate::{eligible, number_choices, render_slot, GenerationOptions, MinFrequency};
+use crate::template::{Template, Token};
+use crate::WordLists;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Three and four words, each with and without the number.
+const TEMPLATES: [&str; 4] = [
+    "{adjective}-{noun}-{verb}",
+    "{number}-{adjective}-{noun}-{verb}",
+    "{adjective}-{noun}-{verb}-{adverb}",
+    "{number}-{adjective}-{noun}-{verb}-{adverb}",
+];
+/// From obscure words allowed to only everyday ones.
+const CUTOFFS: [u32; 4] = [1000, 10000, 50000, 200000];
+
+pub struct Row {
+    pub template: Template,
+    pub min_frequency: MinFrequency,
+    pub bits: f64,
+    /// `None` when a slot has no eligible words.
+    pub average_length: Option<f64>,
+    pub max_length: Option<usize>,
+}
+
+/// Average and longest rendered length of a template token.
+fn token_lengths(
+    token: &Token,
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+) -> Option<(f64, usize)> {
+    let slot = match token {
+        Token::Literal(text) => {
+            let length = text.graphemes(true).count();
+            return Some((length as f64, length));
+        }
+        Token::Slot(slot) => *slot,
+    };
+    let lengths: Vec<usize> = match word_lists.pool(slot) {
+        None => number_choices(options)
+            .iter()
+            .map(|n| n.to_string().len())
+            .collect(),
+        Some(entries) => {
+            let plural = options.template.has_number();
+            eligible(entries, &options.min_frequency.of_slot(slot), None)
+                .map(|entry| {
+                    render_slot(&entry.word, slot, plural, options)
+                        .graphemes(true)
+                        .count()
+                })
+                .collect()
+        }
+    };
+    let max = *lengths.iter().max()?;
+    Some((
+        lengths.iter().sum::<usize>() as f64 / lengths.len() as f64,
+        max,
+    ))
+}
+
+fn row(word_lists: &WordLists, options: GenerationOptions) -> Row {
+    let lengths: Option<Vec<(f64, usize)>> = options
+        .template
+        .tokens()
+        .iter()
+        .map(|token| token_lengths(token, word_lists, &options))
+        .collect();
+    Row {
+        bits: entropy::estimate(word_lists, &options).bits_per_phrase,
+        average_length: lengths
+            .as_ref()
+            .map(|lengths| lengths.iter().map(|(average, _)| average).sum()),
+        max_length: lengths.map(|lengths| lengths.iter().map(|(_, max)| max).sum()),
+        template: options.template,
+        min_frequency: options.min_frequency,
+    }
+}
+
+/// Every template and cutoff, plus the current ones, with `separator`
+/// between the variants' slots if given.
+pub fn rows(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    separator: Option<&str>,
+) -> Vec<Row> {
+    let mut templates: Vec<Template> = TEMPLATES
+        .iter()
+        .map(|text| {
+            let template = Template::parse(text).expect("Explored templates are valid");
+            match separator {
+                Some(separator) => template.with_separator(separator),
+                None => template,
+            }
+        })
+        .collect();
+    let current = options.template.to_string();
+    if !templates
+        .iter()
+        .any(|template| template.to_string() == current)
+    {
+        templates.push(options.template.clone());
+    }
+    let mut cutoffs: Vec<MinFrequency> = CUTOFFS.map(MinFrequency::uniform).to_vec();
+    if !cutoffs.contains(&options.min_frequency) {
+        cutoffs.push(options.min_frequency.clone());
+    }
+
+    let mut rows = Vec::new();
+    for template in &templates {
+        for min_frequency in &cutoffs {
+            let mut variant = options.clone();
+            variant.template = template.clone();
+            variant.min_frequency = min_frequency.clone();
+            rows.push(row(word_lists, variant));
+        }
+    }
+    rows
+}
+
+/// Prints `rows` as a table. With a length limit or an entropy goal, a last
+/// column says whether each row meets them.
+pub fn print(rows: &[Row], max_length: Option<usize>, min_entropy: Option<f64>) {
+    let template_width = rows
+        .iter()
+        .map(|row| row.template.to_string().graphemes(true).count())
+        .max()
+        .unwrap_or(0)
+        .max("template".len());
+    let cutoff_width = rows
+        .iter()
+        .map(|row| row.min_frequency.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max("min freq".len());
+    let goals = max_length.is_some() || min_entropy.is_some();
+    println!(
+        "{:<template_width$}  {:>cutoff_width$}  {:>6}  {:>7}  {:>7}{}",
+        "template",
+        "min freq",
+        "bits",
+        "avg len",
+        "max len",
+        if goals { "  fits" } else { "" }
+    );
+    for row in rows {
+        let template = row.template.to_string();
+        let padding = template_width - template.graphemes(true).count();
+        let (average, max) = match (row.average_length, row.max_length) {
+            (Some(average), Some(max)) => (format!("{:.1}", average), max.to_string()),
+            _ => ("-".to_string(), "-".to_string()),
+        };
+        let mut problems = Vec::new();
+        match (row.max_length, max_length) {
+            (None, _) => problems.push("no words"),
+            (Some(max), Some(limit)) if max > limit => problems.push("too long"),
+            _ => {}
+        }
+        if row.max_length.is_some() && min_entropy.is_some_and(|goal| row.bits < goal) {
+            problems.push("too weak");
+        }
+        let fits = match (goals, problems.is_empty()) {
+            (false, _) => String::new(),
+            (true, true) => "  yes".to_string(),
+            (true, false) => format!("  {}", problems.join(", ")),
+        };
+        println!(
+            "{}{}  {:>cutoff_width$}  {:>6.1}  {:>7}  {:>7}{}",
+            template,
+            " ".repeat(padding),
+            row.min_frequency.to_string(),
+            row.bits,
+            average,
+            max,
+            fits
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! `phraseforge export`: the effective word pools, for security review.
+//!
+//! Each pool is exactly what the generator would draw from with the same
+//! flags: the cached (or bundled) lists after the user's overlay, every filter
+//! and the `--min-frequency` cutoff. Per-phrase constraints such as
+//! `--alliterate`, `--acrostic` or `--rhyme` narrow single slots further and
+//! are reported by the entropy estimate instead.
+
+use crate::generate::{eligible, MinFrequency};
+use crate::{PartOfSpeech, WordLists};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Txt,
+    Json,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 2] = ["txt", "json"];
+
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "txt" => Some(Format::Txt),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedWord {
+    pub word: String,
+    pub frequency: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pool {
+    pub part_of_speech: &'static str,
+    /// The frequency a word had to exceed to be counted.
+    pub min_frequency: u32,
+    pub count: usize,
+    /// log2 of `count`: what one uniformly drawn word from this pool is worth.
+    pub bits_per_word: f64,
+    pub words: Vec<ExportedWord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Export {
+    pub phraseforge_version: &'static str,
+    /// The cutoff when every pool shares one; each pool records its own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_frequency: Option<u32>,
+    /// Filters applied, in order.
+    pub filters: Vec<String>,
+    pub pools: Vec<Pool>,
+}
+
+impl Export {
+    pub fn new(
+        word_lists: &WordLists,
+        pools: &HashSet<PartOfSpeech>,
+        min_frequency: &MinFrequency,
+        filters: Vec<String>,
+    ) -> Self {
+        let pools = PartOfSpeech::ALL
+            .into_iter()
+            .filter(|pos| pools.contains(pos))
+            .map(|pos| {
+                let threshold = min_frequency.of(pos);
+                let words: Vec<ExportedWord> =
+                    eligible(word_lists.entries_of(pos), &threshold, None)
+                        .map(|entry| ExportedWord {
+                            word: entry.word.clone(),
+                            frequency: entry.frequency,
+                        })
+                        .collect();
+                Pool {
+                    part_of_speech: pos.name(),
+                    min_frequency: threshold,
+                    count: words.len(),
+                    bits_per_word: if words.is_empty() {
+                        0.0
+                    } else {
+                        (words.len() as f64).log2()
+                    },
+                    words,
+                }
+            })
+            .collect();
+        Export {
+            phraseforge_version: env!("CARGO_PKG_VERSION"),
+            min_frequency: min_frequency.uniform_threshold(),
+            filters,
+            pools,
+        }
+    }
+
+    pub fn write(&self, format: Format) -> io::Result<()> {
+        let mut out = BufWriter::new(io::stdout().lock());
+        match format {
+            Format::Json => {
+                serde_json::to_writer_pretty(&mut out, self)?;
+                writeln!(out)?;
+            }
+            Format::Txt => {
+                let filters = if self.filters.is_empty() {
+                    "none".to_string()
+                } else {
+                    self.filters.join(", ")
+                };
+                let min_frequency = self
+                    .min_frequency
+                    .map_or("per pool".to_string(), |threshold| threshold.to_string());
+                writeln!(
+                    out,
+                    "# phraseforge {}, min frequency {}, filters: {}",
+                    self.phraseforge_version, min_frequency, filters
+                )?;
+                for pool in &self.pools {
+                    writeln!(
+                        out,
+                        "# {}: {} words above {}, {:.2} bits each",
+                        pool.part_of_speech, pool.count, pool.min_frequency, pool.bits_per_word
+                    )?;
+                    for word in &pool.words {
+                        writeln!(out, "{} {}", word.word, word.frequency)?;
+                    }
+                }
+            }
+        }
+        out.flush()
+    }
+}
@@ -0,0 +1,77 @@
+//! The word filter pipeline.
+//!
+//! Filters run over the loaded word pools before any phrase is generated, so
+//! entropy estimates always reflect the words that can actually be drawn.
+//!
+//! A new screen implements `WordFilter`, loading whatever it needs up front
+//! in its constructor, and is pushed onto the list `prepare_word_lists`
+//! builds; its name then shows up in `--verbose`, `--dry-run`, `export` and
+//! the `--min-entropy` advice. Screens kept outside the source tree plug in
+//! through `--filter-script` instead (see `script`).
+
+use crate::{PartOfSpeech, WordLists};
+
+pub trait WordFilter {
+    fn name(&self) -> String;
+
+    /// Whether `word` from the `pos` pool should stay in the pool.
+    fn keep(&self, pos: PartOfSpeech, word: &str) -> bool;
+}
+
+/// How many words each pool held after one stage of the pipeline, in
+/// `PartOfSpeech::ALL` order.
+pub struct Stage {
+    pub name: String,
+    pub counts: [usize; 4],
+}
+
+impl Stage {
+    pub fn new(name: impl Into<String>, word_lists: &WordLists) -> Stage {
+        Stage {
+            name: name.into(),
+            counts: PartOfSpeech::ALL.map(|pos| word_lists.entries_of(pos).len()),
+        }
+    }
+}
+
+/// The stages the word lists went through: as loaded, after any `--pack`,
+/// after the user's overlay, then after each filter. `--dry-run` prints
+/// them.
+pub struct Pipeline {
+    pub loaded: Stage,
+    pub pack: Option<Stage>,
+    pub overlay: Stage,
+    pub filters: Vec<Stage>,
+}
+
+impl Pipeline {
+    /// The filters applied, in order.
+    pub fn names(&self) -> Vec<String> {
+        self.filters
+            .iter()
+            .map(|stage| stage.name.clone())
+            .collect()
+    }
+}
+
+/// Runs `filters` in order, returning the pools' sizes after each.
+pub fn apply_filters(word_lists: &mut WordLists, filters: &[Box<dyn WordFilter>]) -> Vec<Stage> {
+    let mut stages = Vec::new();
+    for filter in filters {
+        for pool in word_lists.pools_mut() {
+            let pos = pool.part_of_speech();
+            let entries = pool.entries_mut();
+            let before = entries.len();
+            entries.retain(|entry| filter.keep(pos, &entry.word));
+            log::debug!(
+                "Filter {} kept {} of {} {:?} entries",
+                filter.name(),
+                entries.len(),
+                before,
+                pos
+            );
+        }
+        stages.push(Stage::new(filter.name(), word_lists));
+    }
+    stages
+}
@@ -0,0 +1,141 @@
+//! Parsing word frequency lists: the downloaded one the word lists are built
+//! from, a custom one given with `--frequency-list-url`, and the generated
+//! lists themselves.
+//!
+//! Upstream lists are strictly `word count` per line, but exported or
+//! hand-made ones vary, so the parser accepts a byte order mark, blank lines
+//! and `#` comments, tabs, spaces, commas or semicolons between the word and
+//! its count, extra columns after the count, and thousands separators in the
+//! count (`12,345`, `12.345`, `12_345`, `12'345`). Words are lowercased and
+//! composed (NFC) to match the dictionaries. Lines it still can't read are
+//! skipped and reported, with their numbers, so a custom list can be fixed.
+
+use crate::normalize;
+use std::io::BufRead;
+
+/// How many skipped lines a report quotes.
+const QUOTED_SKIPS: usize = 3;
+
+#[derive(Debug, Default)]
+pub struct Parsed {
+    pub entries: Vec<(String, u32)>,
+    /// Line number and why each unreadable line was skipped.
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// A count, allowing one kind of thousands separator between groups of
+/// three digits. Decimals such as `0.5` are rejected rather than misread.
+fn parse_count(text: &str) -> Option<u32> {
+    if text.chars().all(|c| c.is_ascii_digit()) {
+        return text.parse().ok();
+    }
+    let separator = text.chars().find(|c| !c.is_ascii_digit())?;
+    if ![',', '.', '_', '\''].contains(&separator) {
+        return None;
+    }
+    let groups: Vec<&str> = text.split(separator).collect();
+    let digits = |group: &str| group.chars().all(|c| c.is_ascii_digit());
+    let grouped = (1..=3).contains(&groups[0].len())
+        && groups[1..].iter().all(|group| group.len() == 3)
+        && groups.iter().all(|group| digits(group));
+    grouped.then(|| groups.concat().parse().ok()).flatten()
+}
+
+/// The word and count on one line, or why there aren't any.
+fn parse_line(line: &str) -> Result<(String, u32), String> {
+    let mut fields = if line.contains(char::is_whitespace) {
+        line.split_whitespace().collect::<Vec<_>>()
+    } else {
+        line.split([',', ';']).collect()
+    };
+    fields.retain(|field| !field.is_empty());
+    let [word, count, ..] = fields[..] else {
+        return Err("expected a word and a count".to_string());
+    };
+    if !normalize::is_word(word) {
+        return Err(format!("'{}' is not a word", word));
+    }
+    let count = parse_count(count).ok_or_else(|| format!("'{}' is not a count", count))?;
+    Ok((normalize::word(word), count))
+}
+
+pub fn parse(reader: impl BufRead) -> Parsed {
+    let mut parsed = Parsed::default();
+    for (index, line) in reader.lines().enumerate() {
+        let number = index + 1;
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                parsed.skipped.push((number, e.to_string()));
+                continue;
+            }
+        };
+        let line = if number == 1 {
+            line.trim_start_matches('\u{feff}')
+        } else {
+            &line
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(entry) => parsed.entries.push(entry),
+            Err(reason) => parsed.skipped.push((number, reason)),
+        }
+    }
+    parsed
+}
+
+/// Warns about the lines of `name` that `parse` skipped, quoting the first
+/// few.
+pub fn report_skipped(name: &str, parsed: &Parsed) {
+    if parsed.skipped.is_empty() {
+        return;
+    }
+    let quoted: Vec<String> = parsed
+        .skipped
+        .iter()
+        .take(QUOTED_SKIPS)
+        .map(|(number, reason)| format!("line {}: {}", number, reason))
+        .collect();
+    log::warn!(
+        "{}: skipped {} unreadable lines of {} ({}{})",
+        name,
+        parsed.skipped.len(),
+        parsed.skipped.len() + parsed.entries.len(),
+        quoted.join("; "),
+        if parsed.skipped.len() > QUOTED_SKIPS {
+            "; ..."
+        } else {
+            ""
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerates_common_variations() {
+        let text = "\u{feff}# exported from a spreadsheet\n\
+                    the 22,038,615\n\
+                    Otter\t1.234\n\
+                    \n\
+                    badger,12_345,0.01%\n\
+                    ferret 0.5\n\
+                    two words\n";
+        let parsed = parse(text.as_bytes());
+        assert_eq!(
+            parsed.entries,
+            [
+                ("the".to_string(), 22_038_615),
+                ("otter".to_string(), 1234),
+                ("badger".to_string(), 12_345)
+            ]
+        );
+        let skipped: Vec<usize> = parsed.skipped.iter().map(|(number, _)| *number).collect();
+        assert_eq!(skipped, [6, 7]);
+    }
+}
@@ -0,0 +1,754 @@
+//! The generation core: phrases from word lists, options and a random
+//! number generator.
+//!
+//! Nothing here reads files, touches the network or exits the process; the
+//! caller loads the word lists, supplies the RNG and decides what to do with
+//! an error. With a seeded RNG every function is deterministic, which is what
+//! the property tests below rely on.
+
+use crate::ambiguity::Ambiguity;
+use crate::caps::{self, CapsPattern};
+use crate::inflect::{conjugate, pluralize, VerbForm};
+use crate::leet::{self, LeetLevel};
+use crate::policy::{Policy, Violation};
+use crate::rhyme::RhymeGroups;
+use crate::template::{Slot, Template, Token};
+use crate::{mutate, normalize, phonetic, sentence, strength, PartOfSpeech, WordEntry, WordLists};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// `--min-frequency`: how often a word must have been seen to be drawn. One
+/// threshold suits every pool poorly, since the adverb pool is a fraction of
+/// the noun pool, so each part of speech can have its own, e.g.
+/// `adj=5000,noun=20000,adv=2000`. Parts of speech not named keep the
+/// default, which a bare number among them replaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinFrequency {
+    default: u32,
+    overrides: Vec<(PartOfSpeech, u32)>,
+}
+
+impl MinFrequency {
+    pub const DEFAULT: u32 = 10000;
+
+    pub fn uniform(threshold: u32) -> MinFrequency {
+        MinFrequency {
+            default: threshold,
+            overrides: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<MinFrequency, String> {
+        let mut thresholds = MinFrequency::uniform(MinFrequency::DEFAULT);
+        for part in text.split(',').map(str::trim) {
+            let number = |value: &str| {
+                value
+                    .parse::<u32>()
+                    .map_err(|_| format!("'{}' is not a frequency", value))
+            };
+            match part.split_once('=') {
+                None => thresholds.default = number(part)?,
+                Some((name, value)) => {
+                    let pos = PartOfSpeech::from_name(name.trim()).ok_or_else(|| {
+                        format!(
+                            "unknown part of speech '{}' (expected adj, noun, verb or adv)",
+                            name.trim()
+                        )
+                    })?;
+                    if thresholds.overrides.iter().any(|(other, _)| *other == pos) {
+                        return Err(format!("{} is given more than once", pos.name()));
+                    }
+                    thresholds.overrides.push((pos, number(value.trim())?));
+                }
+            }
+        }
+        Ok(thresholds)
+    }
+
+    pub fn of(&self, pos: PartOfSpeech) -> u32 {
+        self.overrides
+            .iter()
+            .find(|(other, _)| *other == pos)
+            .map_or(self.default, |(_, threshold)| *threshold)
+    }
+
+    /// The threshold for the pool `slot` draws from; the number slot has
+    /// none, so it gets the default.
+    pub fn of_slot(&self, slot: Slot) -> u32 {
+        PartOfSpeech::of_slot(slot).map_or(self.default, |pos| self.of(pos))
+    }
+
+    /// The threshold, if every part of speech has the same one.
+    pub fn uniform_threshold(&self) -> Option<u32> {
+        let threshold = self.of(PartOfSpeech::ALL[0]);
+        PartOfSpeech::ALL
+            .iter()
+            .all(|&pos| self.of(pos) == threshold)
+            .then_some(threshold)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.uniform_threshold() == Some(0)
+    }
+}
+
+/// The single threshold, or every part of speech's when they differ.
+impl fmt::Display for MinFrequency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(threshold) = self.uniform_threshold() {
+            return write!(f, "{}", threshold);
+        }
+        let parts: Vec<String> = PartOfSpeech::ALL
+            .iter()
+            .map(|&pos| format!("{}={}", pos.wordnet_suffix(), self.of(pos)))
+            .collect();
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+/// Settings that control how each passphrase is assembled.
+#[derive(Debug, Clone)]
+pub struct GenerationOptions {
+    pub template: Template,
+    pub verb_form: VerbForm,
+    pub min_frequency: MinFrequency,
+    pub alliterate: bool,
+    /// Required initial letters for the word slots, in order.
+    pub acrostic: Option<Vec<char>>,
+    /// Make the first two word slots rhyme.
+    pub rhyme: bool,
+    pub leet: Option<LeetLevel>,
+    /// Which letters of each word are capitalized.
+    pub caps: Option<CapsPattern>,
+    /// Random character mutations applied to the finished phrase.
+    pub mutations: usize,
+    /// Complexity rules every phrase must satisfy.
+    pub policy: Option<Policy>,
+    /// Let the same word fill more than one slot of a phrase.
+    pub allow_repeats: bool,
+    /// Keep words with the same Metaphone code, like "bare" and "bear", out
+    /// of the same phrase; this rules out repeats too.
+    pub distinct_sounds: bool,
+    /// Confusable sequences the number slot must avoid; the word pools are
+    /// filtered separately.
+    pub unambiguous: Option<Ambiguity>,
+    /// Render the words of `sentence::TEMPLATE` as a grammatical sentence.
+    pub sentence: bool,
+    /// Bits zxcvbn must rate each finished phrase at, on top of the
+    /// combinatorial estimate checked before generating.
+    pub min_entropy: Option<f64>,
+    /// The number every phrase of a `--series` shares; being known once
+    /// one phrase is, it counts for no entropy.
+    pub number: Option<u32>,
+    /// How many words the other phrases of a `--series` can take out of the
+    /// pools before this one is drawn, since the phrases share none.
+    pub series_taken: usize,
+}
+
+/// Entries above `min_frequency`, optionally restricted to words that start
+/// with `initial`, diacritics aside.
+pub fn eligible<'a>(
+    word_entries: &'a [WordEntry],
+    min_frequency: &u32,
+    initial: Option<char>,
+) -> impl Iterator<Item = &'a WordEntry> + 'a {
+    let min_frequency = *min_frequency;
+    word_entries.iter().filter(move |entry| {
+        entry.frequency > min_frequency
+            && initial.is_none_or(|c| normalize::initial(&entry.word) == Some(c))
+    })
+}
+
+pub fn count_eligible(
+    word_entries: &[WordEntry],
+    min_frequency: &u32,
+    initial: Option<char>,
+) -> usize {
+    eligible(word_entries, min_frequency, initial).count()
+}
+
+/// Picks an eligible word that isn't in `used` and whose Metaphone code
+/// isn't in `sounds`, or `None` if no word is.
+pub fn pick_random_above_frequency(
+    word_entries: &[WordEntry],
+    min_frequency: &u32,
+    initial: Option<char>,
+    used: &[String],
+    sounds: &[String],
+    rng: &mut impl Rng,
+) -> Option<String> {
+    let filtered: Vec<&WordEntry> = eligible(word_entries, min_frequency, initial)
+        .filter(|entry| !used.contains(&entry.word))
+        .filter(|entry| sounds.is_empty() || !sounds.contains(&phonetic::metaphone(&entry.word)))
+        .collect();
+
+    filtered.choose(rng).map(|entry| entry.word.clone())
+}
+
+/// Letters that start at least one eligible word for every word slot in the
+/// template, in order.
+pub fn alliteration_letters(word_lists: &WordLists, options: &GenerationOptions) -> Vec<char> {
+    let mut letters: Vec<char> = options
+        .template
+        .slots()
+        .find_map(|slot| word_lists.pool(slot).map(|entries| (slot, entries)))
+        .map(|(slot, entries)| {
+            eligible(entries, &options.min_frequency.of_slot(slot), None)
+                .filter_map(|entry| normalize::initial(&entry.word))
+                .collect::<BTreeSet<char>>()
+                .into_iter()
+                .collect()
+        })
+        // Without a word slot any letter will do.
+        .unwrap_or_else(|| ('a'..='z').collect());
+    letters.retain(|&letter| {
+        options.template.slots().all(|slot| {
+            word_lists.pool(slot).is_none_or(|entries| {
+                count_eligible(entries, &options.min_frequency.of_slot(slot), Some(letter)) > 0
+            })
+        })
+    });
+    letters
+}
+
+/// The letter each template slot must start with, in slot order. Acrostic
+/// letters go to word slots in turn; otherwise every word slot gets `letter`.
+pub fn slot_initials(options: &GenerationOptions, letter: Option<char>) -> Vec<Option<char>> {
+    let mut acrostic = options.acrostic.iter().flatten();
+    options
+        .template
+        .slots()
+        .map(|slot| match slot {
+            Slot::Number => None,
+            _ if options.acrostic.is_some() => acrostic.next().copied(),
+            _ => letter,
+        })
+        .collect()
+}
+
+/// Describes every acrostic letter that cannot be satisfied, so the user can
+/// fix them all at once.
+pub fn acrostic_problems(word_lists: &WordLists, options: &GenerationOptions) -> Vec<String> {
+    let Some(acrostic) = &options.acrostic else {
+        return Vec::new();
+    };
+
+    let word_slots = options
+        .template
+        .slots()
+        .filter(|&slot| slot != Slot::Number)
+        .count();
+    if acrostic.len() != word_slots {
+        return vec![format!(
+            "acrostic has {} letters but the template has {} word slots",
+            acrostic.len(),
+            word_slots
+        )];
+    }
+
+    options
+        .template
+        .slots()
+        .zip(slot_initials(options, None))
+        .filter_map(|(slot, initial)| {
+            let entries = word_lists.pool(slot)?;
+            let letter = initial?;
+            let min_frequency = options.min_frequency.of_slot(slot);
+            (count_eligible(entries, &min_frequency, Some(letter)) == 0).then(|| {
+                format!(
+                    "no {} starting with '{}' is available at --min-frequency {}",
+                    slot.name(),
+                    letter,
+                    min_frequency
+                )
+            })
+        })
+        .collect()
+}
+
+/// Positions, among the template's slots, of the first two word slots; these
+/// are the ones `--rhyme` makes rhyme.
+pub fn rhyme_slots(options: &GenerationOptions) -> Option<(usize, usize)> {
+    let mut word_slots = options
+        .template
+        .slots()
+        .enumerate()
+        .filter(|(_, slot)| *slot != Slot::Number)
+        .map(|(i, _)| i);
+    Some((word_slots.next()?, word_slots.next()?))
+}
+
+/// Rhyming candidates for the two `--rhyme` slots.
+pub fn rhyme_groups<'a>(
+    word_lists: &'a WordLists,
+    options: &GenerationOptions,
+) -> Option<RhymeGroups<'a>> {
+    let (first, second) = rhyme_slots(options)?;
+    let slots: Vec<Slot> = options.template.slots().collect();
+    let min_frequency = |slot: Slot| options.min_frequency.of_slot(slot);
+    Some(RhymeGroups::new(
+        eligible(
+            word_lists.pool(slots[first])?,
+            &min_frequency(slots[first]),
+            None,
+        ),
+        eligible(
+            word_lists.pool(slots[second])?,
+            &min_frequency(slots[second]),
+            None,
+        ),
+    ))
+}
+
+/// A generated passphrase along with the lemma drawn for each word slot.
+#[derive(Debug)]
+pub struct GeneratedPhrase {
+    pub text: String,
+    pub words: Vec<(Slot, String)>,
+    /// The rendered text of each template token, before mutations and policy
+    /// repairs; used for coloring when `text` is still their concatenation.
+    pub pieces: Vec<(Option<Slot>, String)>,
+}
+
+/// `word` as it appears in `slot`: pluralized after a number above one, or
+/// conjugated to the slot's verb form. `--leet` is applied separately.
+pub fn render_slot(word: &str, slot: Slot, plural: bool, options: &GenerationOptions) -> String {
+    match slot {
+        Slot::Noun if plural => pluralize(word),
+        Slot::Verb(form) => conjugate(word, form.unwrap_or(options.verb_form)),
+        _ => word.to_string(),
+    }
+}
+
+/// Values the number slot can take: 1 to 998, less any containing a
+/// confusable sequence under `--unambiguous`, or just a series' number.
+pub fn number_choices(options: &GenerationOptions) -> Vec<u32> {
+    if let Some(number) = options.number {
+        return vec![number];
+    }
+    (1..999)
+        .filter(|n| {
+            options
+                .unambiguous
+                .as_ref()
+                .is_none_or(|ambiguity| ambiguity.allows(&n.to_string()))
+        })
+        .collect()
+}
+
+/// Generates one phrase, or explains which slot no word is left for.
+pub fn generate_password(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    rng: &mut impl Rng,
+) -> Result<GeneratedPhrase, String> {
+    // Drawn only for templates that use it; main has checked that some
+    // number survives [unambiguous].
+    let num: u32 = if options.template.has_number() {
+        *number_choices(options)
+            .choose(rng)
+            .expect("Some number avoids every ambiguous sequence")
+    } else {
+        0
+    };
+    let plural = num > 1;
+
+    let letter = if options.alliterate {
+        let letters = alliteration_letters(word_lists, options);
+        let letter = letters.choose(rng).copied().expect(
+            "No letter starts an eligible word for every slot; try a lower --min-frequency",
+        );
+        Some(letter)
+    } else {
+        None
+    };
+    let mut initials = slot_initials(options, letter).into_iter();
+
+    // Words chosen up front for slots that depend on each other, by slot position
+    let mut chosen: Vec<Option<String>> = vec![None; options.template.slots().count()];
+    if options.rhyme {
+        if let (Some((first, second)), Some(groups)) =
+            (rhyme_slots(options), rhyme_groups(word_lists, options))
+        {
+            let (a, b) = groups
+                .choose(rng)
+                .expect("No rhyming words available; try a lower --min-frequency");
+            chosen[first] = Some(a.to_string());
+            chosen[second] = Some(b.to_string());
+        }
+    }
+    let mut chosen = chosen.into_iter();
+    let word_slots = options
+        .template
+        .slots()
+        .filter(|&slot| slot != Slot::Number)
+        .count();
+    let mut masks = options
+        .caps
+        .as_ref()
+        .map(|pattern| pattern.masks(word_slots, rng))
+        .unwrap_or_default()
+        .into_iter();
+
+    let mut phrase = String::new();
+    let mut words = Vec::new();
+    let mut pieces = Vec::new();
+    for token in options.template.tokens() {
+        let slot = match token {
+            Token::Literal(text) => {
+                phrase.push_str(text);
+                pieces.push((None, text.clone()));
+                continue;
+            }
+            Token::Slot(slot) => *slot,
+        };
+        let initial = initials.next().flatten();
+        let preselected = chosen.next().flatten();
+        let Some(entries) = word_lists.pool(slot) else {
+            phrase.push_str(&num.to_string());
+            pieces.push((Some(slot), num.to_string()));
+            continue;
+        };
+        let mask = masks.next();
+
+        let word = preselected.or_else(|| {
+            // Words in several pools ("light") could otherwise appear twice
+            let used: Vec<String> = if options.allow_repeats {
+                Vec::new()
+            } else {
+                words
+                    .iter()
+                    .map(|(_, word): &(Slot, String)| word.clone())
+                    .collect()
+            };
+            let sounds: Vec<String> = if options.distinct_sounds {
+                words
+                    .iter()
+                    .map(|(_, word)| phonetic::metaphone(word))
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let min_frequency = options.min_frequency.of_slot(slot);
+            pick_random_above_frequency(entries, &min_frequency, initial, &used, &sounds, rng)
+        });
+        let Some(word) = word else {
+            return Err(format!(
+                "no eligible {} is left for the template; lower --min-frequency or loosen the word filters",
+                slot.name()
+            ));
+        };
+        let rendered = render_slot(&word, slot, plural, options);
+        let rendered = match &mask {
+            Some(mask) => caps::apply(&rendered, mask, rng),
+            None => rendered,
+        };
+        let rendered = match options.leet {
+            Some(level) => leet::apply(&rendered, level, rng),
+            None => rendered,
+        };
+        phrase.push_str(&rendered);
+        pieces.push((Some(slot), rendered));
+        words.push((slot, word));
+    }
+    if options.sentence {
+        pieces = sentence::compose(num, &words);
+        phrase = pieces.iter().map(|(_, text)| text.as_str()).collect();
+    }
+    if options.mutations > 0 {
+        phrase = mutate::apply(&phrase, options.mutations, rng);
+    }
+    if let Some(policy) = &options.policy {
+        phrase = policy.repair(&phrase, rng);
+    }
+    Ok(GeneratedPhrase {
+        text: phrase,
+        words,
+        pieces,
+    })
+}
+
+/// Generates a phrase that satisfies `options.policy` and that zxcvbn rates
+/// at least `options.min_entropy` bits, or explains which requirement the
+/// phrases keep failing.
+pub fn generate_compliant(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    rng: &mut impl Rng,
+) -> Result<GeneratedPhrase, String> {
+    if options.policy.is_none() && options.min_entropy.is_none() {
+        return generate_password(word_lists, options, rng);
+    }
+    let max_attempts = options
+        .policy
+        .as_ref()
+        .map_or(Policy::default().max_attempts, |policy| policy.max_attempts);
+    // rule -> (attempts it failed, latest explanation)
+    let mut failures: BTreeMap<&str, (usize, String)> = BTreeMap::new();
+    for _ in 0..max_attempts {
+        let phrase = generate_password(word_lists, options, rng)?;
+        let mut problems = options
+            .policy
+            .as_ref()
+            .map(|policy| policy.problems(&phrase.text))
+            .unwrap_or_default();
+        if let Some(min_entropy) = options.min_entropy {
+            let (bits, reason) = strength::weaker(f64::INFINITY, &phrase.text);
+            if bits < min_entropy {
+                problems.push(Violation {
+                    rule: "min_entropy",
+                    // Never the phrase itself: this may end up in the terminal.
+                    detail: format!(
+                        "zxcvbn rated one {:.1} bits: {}",
+                        bits,
+                        reason.unwrap_or_default()
+                    ),
+                });
+            }
+        }
+        if problems.is_empty() {
+            return Ok(phrase);
+        }
+        for violation in problems {
+            let failure = failures.entry(violation.rule).or_default();
+            failure.0 += 1;
+            failure.1 = violation.detail;
+        }
+    }
+
+    let (rule, (count, detail)) = failures
+        .into_iter()
+        .max_by_key(|(_, (count, _))| *count)
+        .expect("Failed attempts record at least one violation");
+    Err(format!(
+        "no phrase met the requirements in {} attempts: '{}' failed {} times (e.g. {}), which this template and word list cannot satisfy",
+        max_attempts, rule, count, detail
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entropy;
+    use crate::WordType;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn entries(words: &[&str]) -> Vec<WordEntry> {
+        words
+            .iter()
+            .map(|word| WordEntry {
+                word: word.to_string(),
+                frequency: 1000,
+            })
+            .collect()
+    }
+
+    fn word_lists() -> WordLists {
+        WordLists {
+            adjectives: WordType::Adjective(entries(&["brave", "quiet", "eager", "amber"])),
+            nouns: WordType::Noun(entries(&["otter", "badger", "mouse", "kite", "anchor"])),
+            verbs: WordType::Verb(entries(&["bounce", "sing", "catch"])),
+            adverbs: WordType::Adverb(entries(&["briskly", "softly"])),
+        }
+    }
+
+    fn options(template: &str) -> GenerationOptions {
+        GenerationOptions {
+            template: Template::parse(template).unwrap(),
+            verb_form: VerbForm::Base,
+            min_frequency: MinFrequency::uniform(0),
+            alliterate: false,
+            acrostic: None,
+            rhyme: false,
+            leet: None,
+            caps: None,
+            mutations: 0,
+            policy: None,
+            allow_repeats: false,
+            distinct_sounds: false,
+            unambiguous: None,
+            sentence: false,
+            min_entropy: None,
+            number: None,
+            series_taken: 0,
+        }
+    }
+
+    const TEMPLATES: [&str; 4] = [
+        crate::template::DEFAULT_TEMPLATE,
+        "{noun}.{noun}.{noun}",
+        "{adjective}{noun}{number}",
+        "<{verb:past}|{verb:gerund}> {adverb}",
+    ];
+
+    #[test]
+    fn phrases_always_follow_their_template() {
+        let word_lists = word_lists();
+        for template in TEMPLATES {
+            let options = options(template);
+            for seed in 0..500 {
+                let phrase =
+                    generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(seed))
+                        .unwrap();
+                let rendered: String = phrase
+                    .pieces
+                    .iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect();
+                assert_eq!(rendered, phrase.text);
+                assert_eq!(phrase.pieces.len(), options.template.tokens().len());
+
+                let number = phrase
+                    .pieces
+                    .iter()
+                    .find(|(slot, _)| *slot == Some(Slot::Number))
+                    .map(|(_, text)| text.parse::<u32>().unwrap());
+                let plural = number.is_some_and(|n| n > 1);
+                let mut words = phrase.words.iter();
+                for (token, (slot, text)) in options.template.tokens().iter().zip(&phrase.pieces) {
+                    match token {
+                        Token::Literal(literal) => assert_eq!((slot, text), (&None, literal)),
+                        Token::Slot(Slot::Number) => assert!((1..999).contains(&number.unwrap())),
+                        Token::Slot(expected) => {
+                            let (word_slot, lemma) = words.next().unwrap();
+                            assert_eq!((slot, word_slot), (&Some(*expected), expected));
+                            assert!(word_lists
+                                .pool(*expected)
+                                .unwrap()
+                                .iter()
+                                .any(|e| e.word == *lemma));
+                            assert_eq!(*text, render_slot(lemma, *expected, plural, &options));
+                        }
+                    }
+                }
+
+                let mut lemmas: Vec<&String> = phrase.words.iter().map(|(_, word)| word).collect();
+                lemmas.sort();
+                lemmas.dedup();
+                assert_eq!(
+                    lemmas.len(),
+                    phrase.words.len(),
+                    "repeated word in {}",
+                    phrase.text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn min_frequency_per_part_of_speech() {
+        let thresholds = MinFrequency::parse("adj=5000,noun=20000,adverb=2000").unwrap();
+        assert_eq!(thresholds.of(PartOfSpeech::Adjective), 5000);
+        assert_eq!(thresholds.of(PartOfSpeech::Verb), MinFrequency::DEFAULT);
+        assert_eq!(thresholds.of_slot(Slot::Adverb), 2000);
+        let thresholds = MinFrequency::parse("500, noun=0").unwrap();
+        assert_eq!(thresholds.of(PartOfSpeech::Verb), 500);
+        assert_eq!(thresholds.of(PartOfSpeech::Noun), 0);
+        assert_eq!(
+            MinFrequency::parse("0").unwrap().uniform_threshold(),
+            Some(0)
+        );
+        assert!(MinFrequency::parse("noun=1,noun=2").is_err());
+        assert!(MinFrequency::parse("pronoun=1").is_err());
+    }
+
+    #[test]
+    fn same_seed_same_phrase() {
+        let word_lists = word_lists();
+        let options = options(crate::template::DEFAULT_TEMPLATE);
+        for seed in 0..50 {
+            let a =
+                generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let b =
+                generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(a.text, b.text);
+        }
+    }
+
+    #[test]
+    fn an_empty_pool_is_an_error_not_a_missing_word() {
+        let mut word_lists = word_lists();
+        word_lists.nouns = WordType::Noun(Vec::new());
+        let options = options(crate::template::DEFAULT_TEMPLATE);
+        let error =
+            generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(0)).unwrap_err();
+        assert!(error.contains("noun"), "{}", error);
+        assert!(generate_compliant(&word_lists, &options, &mut StdRng::seed_from_u64(0)).is_err());
+    }
+
+    #[test]
+    fn entropy_matches_pool_sizes() {
+        let word_lists = word_lists();
+        let options = options("{number}-{adjective}-{noun}-{noun}");
+        let estimate = entropy::estimate(&word_lists, &options);
+        // Without repeats each word slot loses one choice per word drawn
+        // before it, since a word can sit in more than one pool.
+        let sizes: Vec<usize> = estimate.slots.iter().map(|s| s.pool_size).collect();
+        assert_eq!(sizes, [998, 4, 5 - 1, 5 - 2]);
+        let expected: f64 = sizes.iter().map(|&n| (n as f64).log2()).sum();
+        assert!((estimate.bits_per_phrase - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn leet_bits_count_the_rendered_words() {
+        let word_lists = WordLists {
+            adjectives: WordType::Adjective(Vec::new()),
+            // "mice" and "took" have fewer letters to swap than their lemmas.
+            nouns: WordType::Noun(entries(&["mouse"])),
+            verbs: WordType::Verb(entries(&["take"])),
+            adverbs: WordType::Adverb(Vec::new()),
+        };
+        let options = GenerationOptions {
+            leet: Some(LeetLevel::Light),
+            ..options("{number}-{noun}-{verb:past}")
+        };
+        let estimate = entropy::estimate(&word_lists, &options);
+        let leet = estimate.slots.iter().find(|s| s.slot == "leet").unwrap();
+        assert_eq!(leet.bits, 1.0);
+    }
+
+    #[test]
+    fn no_number_is_drawn_for_a_template_without_one() {
+        let word_lists = word_lists();
+        let digits = (0..10).map(|digit| digit.to_string()).collect();
+        let options = GenerationOptions {
+            unambiguous: Some(crate::ambiguity::Ambiguity::new(digits)),
+            ..options("{adjective}-{noun}")
+        };
+        assert!(number_choices(&options).is_empty());
+        for seed in 0..10 {
+            let phrase =
+                generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert_eq!(phrase.words.len(), 2);
+        }
+    }
+
+    #[test]
+    fn a_series_shares_its_number_and_claims_no_bits_for_it() {
+        let word_lists = word_lists();
+        let options = GenerationOptions {
+            number: Some(42),
+            ..options("{number}-{adjective}-{noun}")
+        };
+        for seed in 0..50 {
+            let phrase =
+                generate_password(&word_lists, &options, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(phrase.text.starts_with("42-"));
+        }
+        let estimate = entropy::estimate(&word_lists, &options);
+        assert_eq!(estimate.slots[0].bits, 0.0);
+        // The noun can't repeat the adjective, as in entropy_matches_pool_sizes.
+        assert!((estimate.bits_per_phrase - (4.0f64 * (5.0 - 1.0)).log2()).abs() < 1e-9);
+
+        // A second phrase of two words can take two from every pool.
+        let options = GenerationOptions {
+            series_taken: 2,
+            ..options
+        };
+        let estimate = entropy::estimate(&word_lists, &options);
+        assert!((estimate.bits_per_phrase - (2.0f64 * (5.0 - 1.0 - 2.0)).log2()).abs() < 1e-9);
+    }
+}
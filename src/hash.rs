@@ -0,0 +1,56 @@
+//! Password hashes of generated secrets.
+//!
+//! Each hash is salted freshly and written in the format the matching tool
+//! expects: PHC strings for Argon2, `$2b$` for bcrypt and `$6$` for
+//! SHA-512-crypt, which `chpasswd -e` and most LDAP servers accept directly.
+
+use argon2::password_hash::{PasswordHasher, SaltString};
+use argon2::Argon2;
+use rand::Rng;
+use sha_crypt::{sha512_simple, Sha512Params};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Argon2,
+    Bcrypt,
+    Sha512Crypt,
+}
+
+/// Matches the default in glibc and `mkpasswd`.
+const SHA512_CRYPT_ROUNDS: usize = 5_000;
+
+impl HashAlgorithm {
+    pub const NAMES: [&'static str; 3] = ["argon2", "bcrypt", "sha512-crypt"];
+
+    pub fn from_name(name: &str) -> Option<HashAlgorithm> {
+        match name {
+            "argon2" => Some(HashAlgorithm::Argon2),
+            "bcrypt" => Some(HashAlgorithm::Bcrypt),
+            "sha512-crypt" => Some(HashAlgorithm::Sha512Crypt),
+            _ => None,
+        }
+    }
+
+    /// Hashes `secret`, or explains why this algorithm cannot.
+    pub fn hash(&self, secret: &str) -> Result<String, String> {
+        match self {
+            HashAlgorithm::Argon2 => {
+                let salt_bytes: [u8; 16] = rand::rng().random();
+                let salt = SaltString::encode_b64(&salt_bytes).expect("Salt length is valid");
+                Argon2::default()
+                    .hash_password(secret.as_bytes(), &salt)
+                    .map(|hash| hash.to_string())
+                    .map_err(|e| format!("argon2 hashing failed: {}", e))
+            }
+            // bcrypt ignores everything past 72 bytes, so refuse rather than
+            // silently weaken a long phrase.
+            HashAlgorithm::Bcrypt => bcrypt::non_truncating_hash(secret, bcrypt::DEFAULT_COST)
+                .map_err(|e| format!("bcrypt cannot hash this secret: {}", e)),
+            HashAlgorithm::Sha512Crypt => {
+                let params = Sha512Params::new(SHA512_CRYPT_ROUNDS).expect("Round count is valid");
+                sha512_simple(secret, &params)
+                    .map_err(|e| format!("sha512-crypt hashing failed: {:?}", e))
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! `--hibp`: rejects phrases found in Have I Been Pwned's breach corpus,
+//! checked through its k-anonymity range API. Only the first five hex digits
+//! of a phrase's SHA-1 leave the machine; the response lists every breached
+//! hash sharing them, padded with decoys so its size reveals nothing either.
+//!
+//! A batch is looked up concurrently, at most `CONCURRENCY` requests at a
+//! time, and a `429 Too Many Requests` waits out its `Retry-After` before
+//! trying again, so a large `--count` finishes quickly without hammering the
+//! API. Responses are cached by prefix for the rest of the run, but never on
+//! disk: the prefixes asked for are 20 bits of each generated secret's hash.
+
+use reqwest::{Client, StatusCode};
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+pub const HIBP_RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+/// Requests in flight at once.
+const CONCURRENCY: usize = 8;
+/// Rate-limited retries of one prefix before giving up.
+const MAX_RETRIES: u32 = 5;
+/// The wait when a `429` doesn't say how long to wait.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(2);
+
+/// The phrase's SHA-1 in upper-case hex, split into the prefix sent to the
+/// API and the suffix looked for in its response.
+fn split_hash(phrase: &str) -> (String, String) {
+    let hash: String = Sha1::digest(phrase.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect();
+    let (prefix, suffix) = hash.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// The breached hash suffixes in a range response, skipping the padding
+/// entries, which have a count of zero.
+fn parse_range(body: &str) -> HashSet<String> {
+    body.lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .filter(|(_, count)| count.trim() != "0")
+        .map(|(suffix, _)| suffix.to_ascii_uppercase())
+        .collect()
+}
+
+async fn fetch_range(
+    client: Client,
+    range_url: String,
+    prefix: String,
+) -> Result<(String, HashSet<String>), String> {
+    let url = format!("{}{}", range_url, prefix);
+    let fail = |e: &dyn std::fmt::Display| format!("cannot check {}: {}", range_url, e);
+    for _ in 0..=MAX_RETRIES {
+        let response = client
+            .get(&url)
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .map_err(|e| fail(&e))?;
+        if response.status() == StatusCode::TOO_MANY_REQUESTS {
+            let wait = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_RETRY_AFTER);
+            log::debug!("HIBP rate limit reached; waiting {}s", wait.as_secs());
+            tokio::time::sleep(wait).await;
+            continue;
+        }
+        let body = response
+            .error_for_status()
+            .map_err(|e| fail(&e))?
+            .text()
+            .await
+            .map_err(|e| fail(&e))?;
+        return Ok((prefix, parse_range(&body)));
+    }
+    Err(fail(&"still rate limited after several retries"))
+}
+
+pub struct Checker {
+    client: Client,
+    range_url: String,
+    /// Breached suffixes for each prefix already fetched.
+    ranges: HashMap<String, HashSet<String>>,
+}
+
+impl Checker {
+    pub fn new(client: Client, range_url: &str) -> Checker {
+        let mut range_url = range_url.to_string();
+        if !range_url.ends_with('/') {
+            range_url.push('/');
+        }
+        Checker {
+            client,
+            range_url,
+            ranges: HashMap::new(),
+        }
+    }
+
+    /// Whether each of `phrases`, in order, appears in a known breach.
+    pub fn breached(&mut self, phrases: &[String]) -> Result<Vec<bool>, String> {
+        let hashes: Vec<(String, String)> = phrases.iter().map(|p| split_hash(p)).collect();
+        let missing: HashSet<&String> = hashes
+            .iter()
+            .map(|(prefix, _)| prefix)
+            .filter(|prefix| !self.ranges.contains_key(*prefix))
+            .collect();
+        if !missing.is_empty() {
+            log::debug!("Checking {} hash prefixes with HIBP", missing.len());
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to start HIBP runtime");
+            let fetched = runtime.block_on(async {
+                let mut tasks = JoinSet::new();
+                let mut fetched = Vec::new();
+                for prefix in missing {
+                    if tasks.len() == CONCURRENCY {
+                        fetched.push(tasks.join_next().await.unwrap());
+                    }
+                    tasks.spawn(fetch_range(
+                        self.client.clone(),
+                        self.range_url.clone(),
+                        prefix.clone(),
+                    ));
+                }
+                while let Some(result) = tasks.join_next().await {
+                    fetched.push(result);
+                }
+                fetched
+            });
+            for result in fetched {
+                let (prefix, suffixes) = result.map_err(|e| e.to_string())??;
+                self.ranges.insert(prefix, suffixes);
+            }
+        }
+        Ok(hashes
+            .iter()
+            .map(|(prefix, suffix)| self.ranges[prefix].contains(suffix))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_breached_suffixes_and_skips_padding() {
+        // SHA-1 of "password" is 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8.
+        let (prefix, suffix) = split_hash("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+        let body = "1E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\r\n\
+                    0018A45C4D1DEF81644B54AB7F969B88D65:0\r\n";
+        let breached = parse_range(body);
+        assert!(breached.contains(&suffix));
+        assert!(!breached.contains("0018A45C4D1DEF81644B54AB7F969B88D65"));
+    }
+}
@@ -0,0 +1,244 @@
+//! Opt-in encrypted history of generated secrets (`--log-history`).
+//!
+//! Each run appends one ASCII-armored message to the history file, encrypted
+//! to the recipients in the config file, so writing needs only public keys.
+//! `phraseforge history` decrypts it to tell whether, and when, a phrase found
+//! written down was generated. By default only salted Argon2 hashes are kept,
+//! so even a decrypted history does not list the phrases themselves.
+//!
+//! ```toml
+//! [history]
+//! age_recipients = ["age1..."]   # or encrypt_to = ["KEYID"] for GnuPG
+//! record = "hashes"              # or "phrases"
+//! file = "/path/to/history.asc"  # defaults to the data directory
+//! ```
+
+use crate::encrypt::Recipients;
+use crate::hash::HashAlgorithm;
+use crate::store::run_capturing;
+use argon2::password_hash::{PasswordHash, PasswordVerifier};
+use argon2::Argon2;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GPG_HEADER: &str = "-----BEGIN PGP MESSAGE-----";
+const AGE_HEADER: &str = "-----BEGIN AGE ENCRYPTED FILE-----";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Record {
+    Hashes,
+    Phrases,
+}
+
+impl Record {
+    pub const NAMES: [&'static str; 2] = ["hashes", "phrases"];
+
+    pub fn from_name(name: &str) -> Option<Record> {
+        match name {
+            "hashes" => Some(Record::Hashes),
+            "phrases" => Some(Record::Phrases),
+            _ => None,
+        }
+    }
+}
+
+/// One generated secret, as logged.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub created_at_unix: u64,
+    pub phraseforge_version: String,
+    /// `phrase`, or the subcommand that produced the secret.
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phrase: Option<String>,
+    /// Argon2 PHC string, when only hashes are recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argon2: Option<String>,
+}
+
+impl Entry {
+    pub fn matches(&self, secret: &str) -> bool {
+        if let Some(phrase) = &self.phrase {
+            return phrase == secret;
+        }
+        self.argon2
+            .as_deref()
+            .and_then(|hash| PasswordHash::new(hash).ok())
+            .is_some_and(|hash| {
+                Argon2::default()
+                    .verify_password(secret.as_bytes(), &hash)
+                    .is_ok()
+            })
+    }
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "tynsol", "phraseforge")
+        .map(|dirs| dirs.data_local_dir().join("history.asc"))
+}
+
+/// Entries collected during one run, appended together by `append`.
+pub struct HistoryLog {
+    pub path: PathBuf,
+    pub recipients: Recipients,
+    pub record: Record,
+    /// Recorded as each entry's `kind`.
+    pub kind: String,
+    entries: Vec<Entry>,
+}
+
+impl HistoryLog {
+    pub fn new(path: PathBuf, recipients: Recipients, record: Record) -> Self {
+        HistoryLog {
+            path,
+            recipients,
+            record,
+            kind: "phrase".to_string(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, label: Option<&str>, secret: &str) {
+        let (phrase, argon2) = match self.record {
+            Record::Phrases => (Some(secret.to_string()), None),
+            Record::Hashes => (
+                None,
+                Some(
+                    HashAlgorithm::Argon2
+                        .hash(secret)
+                        .expect("Argon2 hashes any secret"),
+                ),
+            ),
+        };
+        self.entries.push(Entry {
+            created_at_unix: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            phraseforge_version: env!("CARGO_PKG_VERSION").to_string(),
+            kind: self.kind.clone(),
+            label: label.map(str::to_string),
+            phrase,
+            argon2,
+        });
+    }
+
+    /// Encrypts this run's entries and appends them as one message.
+    pub fn append(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for entry in &self.entries {
+            lines.push_str(&serde_json::to_string(entry).expect("Entry serializes"));
+            lines.push('\n');
+        }
+
+        let mut command = self.recipients.encrypt_command();
+        command.arg("--armor");
+        let armored = run_capturing(command, &lines)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(&armored))
+            .map_err(|e| format!("cannot append to {}: {}", self.path.display(), e))
+    }
+}
+
+/// The armored messages in a history file, in the order they were appended.
+fn messages(text: &str) -> Vec<&str> {
+    let mut starts: Vec<usize> = text
+        .match_indices(GPG_HEADER)
+        .chain(text.match_indices(AGE_HEADER))
+        .map(|(start, _)| start)
+        .collect();
+    starts.sort_unstable();
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| &text[start..starts.get(i + 1).copied().unwrap_or(text.len())])
+        .collect()
+}
+
+/// Decrypts the history at `path` and returns the entries whose secret is
+/// `secret`, along with how many entries were checked. age messages need the
+/// `identity` file; GnuPG ones use the keyring.
+pub fn search(
+    path: &Path,
+    identity: Option<&Path>,
+    secret: &str,
+) -> Result<(Vec<Entry>, usize), String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read history {}: {}", path.display(), e))?;
+
+    let mut found = Vec::new();
+    let mut checked = 0;
+    for message in messages(&text) {
+        let command = if message.starts_with(GPG_HEADER) {
+            let mut command = Command::new("gpg");
+            command.args(["--quiet", "--decrypt"]);
+            command
+        } else {
+            let identity = identity
+                .ok_or("the history contains age messages; pass --identity with the age key")?;
+            let mut command = Command::new("age");
+            command.arg("--decrypt").arg("--identity").arg(identity);
+            command
+        };
+        let plaintext = run_capturing(command, message)?;
+        for line in String::from_utf8_lossy(&plaintext).lines() {
+            let entry: Entry = serde_json::from_str(line)
+                .map_err(|e| format!("unreadable history entry: {}", e))?;
+            checked += 1;
+            if entry.matches(secret) {
+                found.push(entry);
+            }
+        }
+    }
+    Ok((found, checked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_appended_messages() {
+        let text = format!(
+            "{g}\nabc\n-----END PGP MESSAGE-----\n{a}\ndef\n-----END AGE ENCRYPTED FILE-----\n",
+            g = GPG_HEADER,
+            a = AGE_HEADER
+        );
+        let messages = messages(&text);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with(GPG_HEADER) && messages[0].contains("abc"));
+        assert!(messages[1].starts_with(AGE_HEADER) && !messages[1].contains("abc"));
+    }
+
+    #[test]
+    fn hashed_entries_match_only_their_phrase() {
+        let mut log = HistoryLog::new(
+            PathBuf::from("history.asc"),
+            Recipients::Age(Vec::new()),
+            Record::Hashes,
+        );
+        log.push(Some("alice"), "4-brave-badgers-bounce-briskly");
+        let entry = &log.entries[0];
+        assert!(entry.phrase.is_none());
+        assert!(entry.matches("4-brave-badgers-bounce-briskly"));
+        assert!(!entry.matches("5-brave-badgers-bounce-briskly"));
+    }
+}
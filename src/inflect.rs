@@ -0,0 +1,520 @@
+//! English inflection for generated words.
+//!
+//! WordNet only provides lemmas, so any form other than the base form has to
+//! be derived here: tables of irregular verbs and nouns cover the exceptions
+//! and a handful of spelling rules cover everything else.
+
+/// The verb forms a template can ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerbForm {
+    Base,
+    Past,
+    Gerund,
+    ThirdPerson,
+}
+
+impl VerbForm {
+    pub const NAMES: [&'static str; 4] = ["base", "past", "gerund", "third-person"];
+
+    pub fn from_name(name: &str) -> Option<VerbForm> {
+        match name {
+            "base" => Some(VerbForm::Base),
+            "past" => Some(VerbForm::Past),
+            "gerund" => Some(VerbForm::Gerund),
+            "third-person" => Some(VerbForm::ThirdPerson),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VerbForm::Base => "base",
+            VerbForm::Past => "past",
+            VerbForm::Gerund => "gerund",
+            VerbForm::ThirdPerson => "third-person",
+        }
+    }
+}
+
+/// Irregular simple past forms, keyed by lemma.
+const IRREGULAR_PAST: &[(&str, &str)] = &[
+    ("arise", "arose"),
+    ("awake", "awoke"),
+    ("bear", "bore"),
+    ("beat", "beat"),
+    ("become", "became"),
+    ("begin", "began"),
+    ("bend", "bent"),
+    ("bet", "bet"),
+    ("bid", "bid"),
+    ("bind", "bound"),
+    ("bite", "bit"),
+    ("bleed", "bled"),
+    ("blow", "blew"),
+    ("break", "broke"),
+    ("breed", "bred"),
+    ("bring", "brought"),
+    ("broadcast", "broadcast"),
+    ("build", "built"),
+    ("burst", "burst"),
+    ("buy", "bought"),
+    ("cast", "cast"),
+    ("catch", "caught"),
+    ("choose", "chose"),
+    ("cling", "clung"),
+    ("come", "came"),
+    ("cost", "cost"),
+    ("creep", "crept"),
+    ("cut", "cut"),
+    ("deal", "dealt"),
+    ("dig", "dug"),
+    ("do", "did"),
+    ("draw", "drew"),
+    ("dream", "dreamt"),
+    ("drink", "drank"),
+    ("drive", "drove"),
+    ("dwell", "dwelt"),
+    ("eat", "ate"),
+    ("fall", "fell"),
+    ("feed", "fed"),
+    ("feel", "felt"),
+    ("fight", "fought"),
+    ("find", "found"),
+    ("flee", "fled"),
+    ("fling", "flung"),
+    ("fly", "flew"),
+    ("forbid", "forbade"),
+    ("forecast", "forecast"),
+    ("forget", "forgot"),
+    ("forgive", "forgave"),
+    ("forsake", "forsook"),
+    ("freeze", "froze"),
+    ("get", "got"),
+    ("give", "gave"),
+    ("go", "went"),
+    ("grind", "ground"),
+    ("grow", "grew"),
+    ("hang", "hung"),
+    ("have", "had"),
+    ("hear", "heard"),
+    ("hide", "hid"),
+    ("hit", "hit"),
+    ("hold", "held"),
+    ("hurt", "hurt"),
+    ("keep", "kept"),
+    ("kneel", "knelt"),
+    ("know", "knew"),
+    ("lead", "led"),
+    ("leap", "leapt"),
+    ("leave", "left"),
+    ("lend", "lent"),
+    ("let", "let"),
+    ("light", "lit"),
+    ("lose", "lost"),
+    ("make", "made"),
+    ("mean", "meant"),
+    ("meet", "met"),
+    ("mislead", "misled"),
+    ("overcome", "overcame"),
+    ("pay", "paid"),
+    ("put", "put"),
+    ("quit", "quit"),
+    ("read", "read"),
+    ("ride", "rode"),
+    ("ring", "rang"),
+    ("rise", "rose"),
+    ("run", "ran"),
+    ("say", "said"),
+    ("see", "saw"),
+    ("seek", "sought"),
+    ("sell", "sold"),
+    ("send", "sent"),
+    ("set", "set"),
+    ("shake", "shook"),
+    ("shed", "shed"),
+    ("shine", "shone"),
+    ("shoot", "shot"),
+    ("shrink", "shrank"),
+    ("shut", "shut"),
+    ("sing", "sang"),
+    ("sink", "sank"),
+    ("sit", "sat"),
+    ("slay", "slew"),
+    ("sleep", "slept"),
+    ("slide", "slid"),
+    ("sling", "slung"),
+    ("speak", "spoke"),
+    ("speed", "sped"),
+    ("spend", "spent"),
+    ("spin", "spun"),
+    ("split", "split"),
+    ("spread", "spread"),
+    ("spring", "sprang"),
+    ("stand", "stood"),
+    ("steal", "stole"),
+    ("stick", "stuck"),
+    ("sting", "stung"),
+    ("stink", "stank"),
+    ("stride", "strode"),
+    ("strike", "struck"),
+    ("string", "strung"),
+    ("strive", "strove"),
+    ("swear", "swore"),
+    ("sweep", "swept"),
+    ("swim", "swam"),
+    ("swing", "swung"),
+    ("take", "took"),
+    ("teach", "taught"),
+    ("tear", "tore"),
+    ("tell", "told"),
+    ("think", "thought"),
+    ("throw", "threw"),
+    ("thrust", "thrust"),
+    ("tread", "trod"),
+    ("understand", "understood"),
+    ("undertake", "undertook"),
+    ("wake", "woke"),
+    ("wear", "wore"),
+    ("weave", "wove"),
+    ("weep", "wept"),
+    ("win", "won"),
+    ("wind", "wound"),
+    ("withdraw", "withdrew"),
+    ("withhold", "withheld"),
+    ("wring", "wrung"),
+    ("write", "wrote"),
+];
+
+/// Prefixes that leave a verb's past form irregular, so "mistake" becomes
+/// "mistook" because "take" becomes "took".
+const PAST_PREFIXES: &[&str] = &[
+    "be", "fore", "mis", "out", "over", "re", "under", "up", "with",
+];
+
+/// Verbs that only look like a prefixed irregular one.
+const REGULAR_LOOKALIKES: &[&str] = &["behave"];
+
+/// Irregular third-person singular forms, keyed by lemma.
+const IRREGULAR_THIRD_PERSON: &[(&str, &str)] = &[("have", "has"), ("be", "is")];
+
+fn lookup(table: &[(&str, &'static str)], word: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(lemma, _)| *lemma == word)
+        .map(|(_, form)| *form)
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+/// True for short consonant-vowel-consonant endings ("stop", "plan") whose
+/// final consonant doubles before a vowel suffix.
+fn doubles_final_consonant(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    let n = chars.len();
+    if n < 3 {
+        return false;
+    }
+    let (a, b, c) = (chars[n - 3], chars[n - 2], chars[n - 1]);
+    let vowel_groups = chars
+        .windows(2)
+        .filter(|pair| !is_vowel(pair[0]) && is_vowel(pair[1]))
+        .count()
+        + usize::from(is_vowel(chars[0]));
+    !is_vowel(a)
+        && is_vowel(b)
+        && !is_vowel(c)
+        && !matches!(c, 'w' | 'x' | 'y')
+        && vowel_groups == 1
+}
+
+fn ends_with_consonant_y(word: &str) -> bool {
+    let mut chars = word.chars().rev();
+    matches!((chars.next(), chars.next()), (Some('y'), Some(c)) if !is_vowel(c))
+}
+
+/// The irregular past of `verb`, or of the verb it is a prefix away from.
+fn irregular_past(verb: &str) -> Option<String> {
+    if let Some(form) = lookup(IRREGULAR_PAST, verb) {
+        return Some(form.to_string());
+    }
+    if REGULAR_LOOKALIKES.contains(&verb) {
+        return None;
+    }
+    PAST_PREFIXES.iter().find_map(|prefix| {
+        let base = verb.strip_prefix(prefix)?;
+        Some(format!("{}{}", prefix, irregular_past(base)?))
+    })
+}
+
+fn past(verb: &str) -> String {
+    if let Some(form) = irregular_past(verb) {
+        return form;
+    }
+    if verb.ends_with('e') {
+        format!("{}d", verb)
+    } else if ends_with_consonant_y(verb) {
+        format!("{}ied", &verb[..verb.len() - 1])
+    } else if doubles_final_consonant(verb) {
+        format!("{}{}ed", verb, &verb[verb.len() - 1..])
+    } else {
+        format!("{}ed", verb)
+    }
+}
+
+fn gerund(verb: &str) -> String {
+    if let Some(stem) = verb.strip_suffix("ie") {
+        format!("{}ying", stem)
+    } else if verb.ends_with('e') && !["ee", "ye", "oe"].iter().any(|s| verb.ends_with(s)) {
+        format!("{}ing", &verb[..verb.len() - 1])
+    } else if doubles_final_consonant(verb) {
+        format!("{}{}ing", verb, &verb[verb.len() - 1..])
+    } else {
+        format!("{}ing", verb)
+    }
+}
+
+fn third_person(verb: &str) -> String {
+    if let Some(form) = lookup(IRREGULAR_THIRD_PERSON, verb) {
+        return form.to_string();
+    }
+    if ["s", "x", "z", "ch", "sh", "o"]
+        .iter()
+        .any(|s| verb.ends_with(s))
+    {
+        format!("{}es", verb)
+    } else if ends_with_consonant_y(verb) {
+        format!("{}ies", &verb[..verb.len() - 1])
+    } else {
+        format!("{}s", verb)
+    }
+}
+
+/// Conjugates a verb lemma into the requested form.
+pub fn conjugate(verb: &str, form: VerbForm) -> String {
+    match form {
+        VerbForm::Base => verb.to_string(),
+        VerbForm::Past => past(verb),
+        VerbForm::Gerund => gerund(verb),
+        VerbForm::ThirdPerson => third_person(verb),
+    }
+}
+
+/// Nouns with irregular plurals, keyed by singular.
+const IRREGULAR_PLURALS: &[(&str, &str)] = &[
+    ("alumnus", "alumni"),
+    ("cactus", "cacti"),
+    ("calf", "calves"),
+    ("child", "children"),
+    ("criterion", "criteria"),
+    ("elf", "elves"),
+    ("foot", "feet"),
+    ("fungus", "fungi"),
+    ("goose", "geese"),
+    ("half", "halves"),
+    ("knife", "knives"),
+    ("leaf", "leaves"),
+    ("life", "lives"),
+    ("loaf", "loaves"),
+    ("louse", "lice"),
+    ("man", "men"),
+    ("mouse", "mice"),
+    ("nucleus", "nuclei"),
+    ("ox", "oxen"),
+    ("person", "people"),
+    ("phenomenon", "phenomena"),
+    ("radius", "radii"),
+    ("scarf", "scarves"),
+    ("self", "selves"),
+    ("sheaf", "sheaves"),
+    ("shelf", "shelves"),
+    ("stimulus", "stimuli"),
+    ("syllabus", "syllabi"),
+    ("thief", "thieves"),
+    ("tooth", "teeth"),
+    ("wife", "wives"),
+    ("wolf", "wolves"),
+    ("woman", "women"),
+];
+
+/// Nouns whose plural is identical to the singular.
+const UNCHANGED_PLURALS: &[&str] = &[
+    "aircraft", "bison", "cod", "deer", "fish", "moose", "salmon", "series", "sheep", "shrimp",
+    "species", "swine", "trout",
+];
+
+/// Uncountable nouns, which read oddly when pluralized and are left alone.
+const MASS_NOUNS: &[&str] = &[
+    "advice",
+    "baggage",
+    "bread",
+    "butter",
+    "clothing",
+    "equipment",
+    "evidence",
+    "furniture",
+    "garbage",
+    "gold",
+    "homework",
+    "honey",
+    "information",
+    "jewelry",
+    "knowledge",
+    "luggage",
+    "machinery",
+    "milk",
+    "money",
+    "mud",
+    "music",
+    "news",
+    "oxygen",
+    "poetry",
+    "pollution",
+    "research",
+    "rice",
+    "sand",
+    "silver",
+    "snow",
+    "software",
+    "steam",
+    "traffic",
+    "water",
+    "weather",
+    "wheat",
+    "wool",
+];
+
+/// Nouns ending in a consonant followed by "o" that take "-es".
+const O_ES_PLURALS: &[&str] = &[
+    "buffalo", "domino", "echo", "hero", "mosquito", "potato", "tomato", "torpedo", "veto",
+    "volcano",
+];
+
+/// True if the noun has no sensible plural form.
+pub fn is_mass_noun(noun: &str) -> bool {
+    MASS_NOUNS.contains(&noun)
+}
+
+/// Pluralizes a singular noun, leaving mass nouns unchanged.
+pub fn pluralize(noun: &str) -> String {
+    if let Some(plural) = lookup(IRREGULAR_PLURALS, noun) {
+        return plural.to_string();
+    }
+    if is_mass_noun(noun) || UNCHANGED_PLURALS.contains(&noun) {
+        return noun.to_string();
+    }
+    if let Some(stem) = noun.strip_suffix("is") {
+        format!("{}es", stem)
+    } else if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|s| noun.ends_with(s))
+    {
+        format!("{}es", noun)
+    } else if ends_with_consonant_y(noun) {
+        format!("{}ies", &noun[..noun.len() - 1])
+    } else if O_ES_PLURALS.contains(&noun) {
+        format!("{}es", noun)
+    } else {
+        format!("{}s", noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pluralizes_irregular_nouns() {
+        assert_eq!(pluralize("person"), "people");
+        assert_eq!(pluralize("child"), "children");
+        assert_eq!(pluralize("knife"), "knives");
+        assert_eq!(pluralize("wolf"), "wolves");
+        assert_eq!(pluralize("mouse"), "mice");
+        assert_eq!(pluralize("cactus"), "cacti");
+        assert_eq!(pluralize("criterion"), "criteria");
+    }
+
+    #[test]
+    fn leaves_unchanged_plurals_alone() {
+        assert_eq!(pluralize("sheep"), "sheep");
+        assert_eq!(pluralize("deer"), "deer");
+        assert_eq!(pluralize("species"), "species");
+    }
+
+    #[test]
+    fn skips_mass_nouns() {
+        assert_eq!(pluralize("information"), "information");
+        assert_eq!(pluralize("furniture"), "furniture");
+        assert!(is_mass_noun("rice"));
+        assert!(!is_mass_noun("badger"));
+    }
+
+    #[test]
+    fn pluralizes_regular_nouns() {
+        assert_eq!(pluralize("badger"), "badgers");
+        assert_eq!(pluralize("church"), "churches");
+        assert_eq!(pluralize("box"), "boxes");
+        assert_eq!(pluralize("city"), "cities");
+        assert_eq!(pluralize("monkey"), "monkeys");
+        assert_eq!(pluralize("potato"), "potatoes");
+        assert_eq!(pluralize("piano"), "pianos");
+        assert_eq!(pluralize("analysis"), "analyses");
+    }
+
+    #[test]
+    fn conjugates_regular_verbs() {
+        let cases = [
+            ("bake", VerbForm::Past, "baked"),
+            ("carry", VerbForm::Past, "carried"),
+            ("play", VerbForm::Past, "played"),
+            ("stop", VerbForm::Past, "stopped"),
+            ("jump", VerbForm::Past, "jumped"),
+            ("tie", VerbForm::Gerund, "tying"),
+            ("bake", VerbForm::Gerund, "baking"),
+            ("flee", VerbForm::Gerund, "fleeing"),
+            ("stop", VerbForm::Gerund, "stopping"),
+            ("jump", VerbForm::Gerund, "jumping"),
+            ("watch", VerbForm::ThirdPerson, "watches"),
+            ("go", VerbForm::ThirdPerson, "goes"),
+            ("carry", VerbForm::ThirdPerson, "carries"),
+            ("play", VerbForm::ThirdPerson, "plays"),
+            ("jump", VerbForm::ThirdPerson, "jumps"),
+            ("jump", VerbForm::Base, "jump"),
+        ];
+        for (verb, form, expected) in cases {
+            assert_eq!(conjugate(verb, form), expected, "{} as {:?}", verb, form);
+        }
+    }
+
+    #[test]
+    fn conjugates_irregular_verbs() {
+        let cases = [
+            ("understand", VerbForm::Past, "understood"),
+            ("overcome", VerbForm::Past, "overcame"),
+            ("undertake", VerbForm::Past, "undertook"),
+            ("withdraw", VerbForm::Past, "withdrew"),
+            ("mislead", VerbForm::Past, "misled"),
+            ("withhold", VerbForm::Past, "withheld"),
+            ("thrust", VerbForm::Past, "thrust"),
+            ("broadcast", VerbForm::Past, "broadcast"),
+            ("forecast", VerbForm::Past, "forecast"),
+            ("mistake", VerbForm::Past, "mistook"),
+            ("overtake", VerbForm::Past, "overtook"),
+            ("foresee", VerbForm::Past, "foresaw"),
+            ("undergo", VerbForm::Past, "underwent"),
+            ("upset", VerbForm::Past, "upset"),
+            ("misunderstand", VerbForm::Past, "misunderstood"),
+            ("wring", VerbForm::Past, "wrung"),
+            ("stride", VerbForm::Past, "strode"),
+            ("slay", VerbForm::Past, "slew"),
+            ("behave", VerbForm::Past, "behaved"),
+            ("remain", VerbForm::Past, "remained"),
+            ("have", VerbForm::ThirdPerson, "has"),
+            ("be", VerbForm::ThirdPerson, "is"),
+        ];
+        for (verb, form, expected) in cases {
+            assert_eq!(conjugate(verb, form), expected, "{} as {:?}", verb, form);
+        }
+    }
+}
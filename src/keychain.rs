@@ -0,0 +1,25 @@
+//! Storing secrets in the OS keyring (`--keyring`, `phraseforge recall`):
+//! the Secret Service on Linux, Keychain on macOS and Credential Manager on
+//! Windows.
+
+use keyring::Entry;
+
+/// Service name every PhraseForge entry is filed under; the user's label is
+/// the account name.
+const SERVICE: &str = "phraseforge";
+
+fn entry(label: &str) -> Result<Entry, String> {
+    Entry::new(SERVICE, label).map_err(|e| format!("cannot open keyring entry '{}': {}", label, e))
+}
+
+pub fn save(label: &str, secret: &str) -> Result<(), String> {
+    entry(label)?
+        .set_password(secret)
+        .map_err(|e| format!("cannot store '{}' in the keyring: {}", label, e))
+}
+
+pub fn recall(label: &str) -> Result<String, String> {
+    entry(label)?
+        .get_password()
+        .map_err(|e| format!("cannot recall '{}' from the keyring: {}", label, e))
+}
@@ -0,0 +1,80 @@
+//! Leetspeak substitutions.
+//!
+//! Each letter that has a substitute is swapped with probability one half,
+//! so every substitutable letter in a phrase adds one bit of entropy. The
+//! estimate only credits the letters every possible word is guaranteed to
+//! have, which for most pools is very little.
+
+use rand::Rng;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeetLevel {
+    Light,
+    Heavy,
+}
+
+const LIGHT: &[(char, char)] = &[('a', '4'), ('e', '3'), ('s', '$')];
+
+const HEAVY: &[(char, char)] = &[
+    ('a', '4'),
+    ('b', '8'),
+    ('e', '3'),
+    ('g', '9'),
+    ('i', '1'),
+    ('o', '0'),
+    ('s', '$'),
+    ('t', '7'),
+];
+
+impl LeetLevel {
+    pub const NAMES: [&'static str; 2] = ["light", "heavy"];
+
+    pub fn from_name(name: &str) -> Option<LeetLevel> {
+        match name {
+            "light" => Some(LeetLevel::Light),
+            "heavy" => Some(LeetLevel::Heavy),
+            _ => None,
+        }
+    }
+
+    fn substitutions(&self) -> &'static [(char, char)] {
+        match self {
+            LeetLevel::Light => LIGHT,
+            LeetLevel::Heavy => HEAVY,
+        }
+    }
+
+    fn substitute(&self, c: char) -> Option<char> {
+        self.substitutions()
+            .iter()
+            .find(|(from, _)| *from == c)
+            .map(|(_, to)| *to)
+    }
+
+    /// Number of letters in `word` that could be substituted.
+    pub fn substitutable(&self, word: &str) -> usize {
+        word.chars()
+            .filter(|&c| self.substitute(c).is_some())
+            .count()
+    }
+}
+
+/// Substitutes a random subset of the substitutable letters in `word`.
+pub fn apply(word: &str, level: LeetLevel, rng: &mut impl Rng) -> String {
+    word.chars()
+        .map(|c| match level.substitute(c) {
+            Some(sub) if rng.random_bool(0.5) => sub,
+            _ => c,
+        })
+        .collect()
+}
+
+/// Bits of entropy leetspeak is guaranteed to add to a slot whose words, as
+/// rendered (plural or conjugated), are `words`: one per substitutable letter
+/// in the word with the fewest.
+pub fn guaranteed_bits(words: impl Iterator<Item = String>, level: LeetLevel) -> usize {
+    words
+        .map(|word| level.substitutable(&word))
+        .min()
+        .unwrap_or(0)
+}
@@ -1,19 +1,206 @@
 //! # PhraseForge: A Passphrase Generator
 //!
 //! PhraseForge generates easy-to-remember passphrases using words from WordNet.
-//! The phrases follow the structure: `adjective-noun-verb-adverb`.
+//! By default phrases follow the structure `number-adjective-noun-verb-adverb`;
+//! `--template` accepts any arrangement of `{number}`, `{adjective}`, `{noun}`,
+//! `{verb}` and `{adverb}` slots, and `{verb:past}` style modifiers select a
+//! verb form. `--preset` names a common layout instead (`templates list`
+//! shows them, along with any under `[templates]` in the config file).
+//! `--separator` swaps the `-` between slots for any Unicode string, emoji
+//! included.
 //!
 //! ## Features
-//! - Downloads and extracts WordNet word lists.
-//! - Caches word lists locally for offline use.
-//! - Generates passphrases using randomly selected words.
-//! - Supports re-downloading word lists with a flag.
+//! - Downloads WordNet and the frequency list concurrently, streamed to disk,
+//!   keeping only the WordNet files the requested features read.
+//! - Optionally embeds a small curated word list (the `bundled-words` cargo
+//!   feature) so phrases work on first run without network access.
+//! - Downloads through proxies and from configurable mirrors.
+//! - Caches word lists locally for offline use, in a versioned layout with a
+//!   manifest so interrupted downloads are detected and rebuilt.
+//! - Runs confined with `--sandbox DIR`: no network access and no writes
+//!   outside `DIR`, for services under strict seccomp or AppArmor profiles.
+//! - Asks on a first run at a terminal which spelling, theme and policy to
+//!   default to and whether to download or import the sources, saving the
+//!   answers under `[defaults]` in the config file (`--yes` skips it).
+//! - Suggests `update` once the frequency data is older than `cache_max_age`
+//!   days (180 by default), or runs it in the background with
+//!   `cache_auto_refresh`.
+//! - Generates passphrases using randomly selected words, streaming huge
+//!   `--count`s (or `--infinite`) in constant memory.
+//! - Excludes proper nouns and acronyms unless asked to keep them.
+//! - Keeps favorite words in and disliked ones out with `words add` and
+//!   `words remove`, across re-downloads.
+//! - Installs community word packs, themed or in other languages, with
+//!   `pack install` and draws from one with `--pack NAME`; each pack's files
+//!   are checked against the checksums its `pack.toml` lists.
+//! - Never repeats a word within a phrase, and can keep each word in only its
+//!   most used part of speech.
+//! - Lists a batch most memorable first with `--sort-by memorability`, scored
+//!   from word frequency ranks, lengths and concreteness.
+//! - Keeps sound-alike words ("bare" and "bear") out of the same phrase with
+//!   `--distinct-sounds`, comparing their Metaphone codes.
+//! - Restricts nouns to a semantic theme using WordNet's hypernym hierarchy.
+//! - Optionally keeps only concrete, easily pictured nouns and adjectives.
+//! - Standardizes on American or British spellings with `--spelling`.
+//! - Avoids easily confused glyphs like "rn" and "1" with `--unambiguous`.
+//! - Prefers words that are comfortable to type on a keyboard or phone with
+//!   `--easy-to-type`.
+//! - Drops highly ambiguous words with many WordNet senses via `--max-senses`.
+//! - Screens words through your own program with `--filter-script`, e.g.
+//!   against brand names or a compliance list.
+//! - Keeps the 100,000 most common breached passwords out of one-word
+//!   templates with `--reject-common`, and warns if a phrase is one.
+//! - Alliterative phrases, with the reduced entropy reported honestly.
+//! - Acrostic phrases whose initials spell a word of your choosing.
+//! - Rhyming word pairs as a memorization aid.
+//! - Explains each word with its WordNet definition to help form a mental image.
+//! - Spells phrases in the NATO phonetic alphabet for reading over the phone.
+//! - Leetspeak substitutions for sites that want digits and symbols.
+//! - Random character mutations so phrases are not purely dictionary words.
+//! - Classic random-character passwords via the `random` subcommand.
+//! - Numeric PINs that avoid repeats, sequences and years via `pin`.
+//! - Diceware phrases from physical dice rolls read on stdin via `dice`.
+//! - Stateless per-site phrases derived from a master secret via `derive`.
+//! - Verifies downloads against a mirror's signed `SHA256SUMS` with
+//!   `--trust-key`, for authenticated provenance beyond cache checksums.
+//! - Supports re-downloading word lists with a flag, or refreshing only the
+//!   sources that changed upstream with `update`.
+//! - Builds the word lists without network access from a directory of
+//!   pre-fetched sources with `bootstrap`, for container images and CI.
+//! - Diagnoses broken installs with `doctor`, suggesting a fix for each
+//!   problem found.
 //! - Allows generating multiple passphrases at once.
+//! - Provisions one phrase per account name piped on stdin with `--labels`.
+//! - Numbers the phrases of a batch; with `--session`, `--reroll N` redraws
+//!   result N (or one word of it with `--slot`) and `--pick N` prints it,
+//!   both reusing the batch's flags.
+//! - Sets several passwords in one sitting with `--series`: phrases sharing a
+//!   template and number but not words, each rated without the shared part.
+//! - Prints Argon2, bcrypt or SHA-512-crypt hashes ready for `chpasswd -e`.
+//! - Shows secrets as QR codes in the terminal or a PNG for scanning by phone.
+//! - Guarantees phrases meet NIST, Active Directory or PCI DSS policies, or
+//!   custom ones declared in the config file.
+//! - Writes secrets only in encrypted form, to GnuPG or age recipients.
+//! - Keeps an opt-in encrypted history with `--log-history` (hashes by
+//!   default), and checks a found phrase against it with `history`.
+//! - Never issues the same phrase twice with `--avoid-file`, a shared file of
+//!   salted hashes of every phrase issued so far.
+//! - Saves secrets in the OS keyring with `--keyring` and reads them back with
+//!   `recall`.
+//! - Notes labeled secrets for rotation with `--remind 90d` (a hash, never
+//!   the secret) and lists the ones due with `reminders`.
+//! - Stores phrases directly in `pass` or KeePassXC via `store`, unseen.
+//! - Colors each part of speech on interactive terminals (`NO_COLOR` aware).
+//! - Reads well with a screen reader under `--screen-reader`: each phrase is
+//!   followed by its digits, separators and capitals in words, without color.
+//! - Explains, with `--verbose`, which pools each phrase drew from and the
+//!   entropy each slot contributed.
+//! - Exports a reviewable session transcript (never containing the phrases).
+//! - Appends a JSON record per issued phrase with `--audit-json` (template,
+//!   entropy and checks passed, never the phrase) for SIEM ingestion.
+//! - Refuses to generate anything weaker than `--min-entropy` bits, naming the
+//!   constraints to relax.
+//! - Cross-checks every phrase with zxcvbn and reports the weaker score, so a
+//!   phrase that happens to spell a famous quote or keyboard walk is flagged;
+//!   `check` rates existing passphrases the same way.
+//! - Dumps the exact word pools in effect with `export`, for review.
+//! - Shows with `--dry-run` how many words each filter leaves in every pool,
+//!   to find the flag that empties one.
+//! - Lists what the binary supports, and the cached word lists' checksums,
+//!   with `capabilities --format json` for provisioning pipelines.
+//! - Checks with `selftest` that phrases are drawn uniformly from the
+//!   filtered pools (chi-squared and repeat-rate tests), entirely offline.
+//! - Writes whole sentences with `--sentence`, with the article, agreement
+//!   and punctuation right, for the most memorable phrases.
+//! - Offers variations on a phrase you almost liked with `similar`, swapping
+//!   a word or two while keeping the rest.
+//! - Rates each word common, uncommon or rare with `--annotate`, with its
+//!   frequency rank, for tuning `--min-frequency`.
+//! - Prints each phrase's initials with `--abbrev`, numbers and capitals
+//!   intact, as an innocuous written hint.
+//! - Flags, with `--typo-report`, words a single typo turns into another word
+//!   of the same pool, for phrases that will be dictated or fuzzily matched.
+//! - Drills a new phrase into memory with `practice`: retype it from memory
+//!   at growing intervals, told which words were wrong.
+//! - Lets someone who'd rather click choose a phrase on a local web page with
+//!   `web --once`: copy, reroll or pick, then the page shuts down.
+//! - Writes arbitrary bytes, such as a key or API token, as words from your
+//!   own pools with `encode`, and reads them back with `decode`.
+//! - Splits a recovery phrase among trustees with `--split 3-of-5`, as
+//!   Shamir shares written in words, and rebuilds it with `combine`.
 //!
 //! ## Usage
 //! ```sh
 //! phraseforge --count 5   # Generate 5 passphrases
+//! phraseforge --count 5 --session --template '{adjective}-{noun}'  # Then:
+//! phraseforge --reroll 3 --slot 2  # A new second word for result 3
+//! phraseforge --pick 3 --keyring github  # Keep result 3, end the session
+//! phraseforge --series 5 --series-hint  # 5 phrases sharing their number
+//! phraseforge --infinite --quiet | head -n 1000000  # Stream until the pipe closes
+//! cut -d: -f1 users.txt | phraseforge --labels  # alice<TAB>phrase, one per line
 //! phraseforge --redownload  # Force re-download of WordNet data
+//! phraseforge update  # Re-download only sources that changed upstream
+//! phraseforge --mirror https://mirror.example --trust-key mirror.asc update
+//! phraseforge bootstrap --from-dir /seed  # Offline build, JSON diagnostics
+//! phraseforge --sandbox /srv/pf --output pw.gpg --encrypt-to ops@example.com
+//! phraseforge doctor  # Diagnose the data directory, cache, config and network
+//! phraseforge --frequency-list en_50k  # Build from the smaller frequency list
+//! pw=$(phraseforge --quiet)  # Status messages go to stderr; --quiet drops them
+//! phraseforge --transcript run.json  # Record configuration, hashes and entropy
+//! phraseforge --labels --policy nist --audit-json /var/log/pf.jsonl < users.txt
+//! phraseforge --min-entropy 60  # Fail instead of printing weaker phrases
+//! phraseforge check  # Rate an existing passphrase with zxcvbn, unechoed
+//! phraseforge --theme food --concrete --dry-run  # Pool sizes after each filter
+//! phraseforge export --pos all --min-frequency 10000 --format json  # Audit pools
+//! phraseforge selftest --samples 100000  # Is the sampling uniform?
+//! phraseforge --min-frequency adj=5000,noun=20000,adv=2000  # Per-pool cutoffs
+//! phraseforge --min-entropy 50 explore --max-length 32  # Templates that fit
+//! phraseforge --template '{noun}-{verb:past}-{adverb}'  # Custom phrase layout
+//! phraseforge --preset xkcd4  # correct horse battery staple
+//! phraseforge --sentence  # The 42 hungry otters quietly devour pancakes.
+//! phraseforge templates list  # Built-in presets and the config file's
+//! phraseforge capabilities --format json  # What this build supports
+//! phraseforge similar 7-brave-badgers-bounce-briskly  # 7-brave-otters-bounce-briskly
+//! phraseforge practice --rounds 5  # Memorize a new phrase by recalling it
+//! phraseforge web --once  # Choose a phrase in the browser, then stop
+//! phraseforge --split 3-of-5  # Five shares, any three of which rebuild it
+//! phraseforge combine < shares.txt  # Rebuild a phrase from its shares
+//! phraseforge encode --bytes deadbeef --pos noun  # Bytes as memorable words
+//! phraseforge decode --pos noun  # And back to hex, words typed unechoed
+//! phraseforge --separator 🦊  # 4🦊brave🦊badgers🦊bounce🦊briskly
+//! phraseforge --theme animals  # Only use nouns that are animals
+//! phraseforge words remove --pos adj moist  # Never use "moist" again
+//! phraseforge words list --pos noun --top 50  # The 50 most common nouns
+//! phraseforge pack install https://example.org/space.tar.gz  # Add a word pack
+//! phraseforge --pack space  # Draw nouns, say, from the pack
+//! phraseforge --concrete  # Prefer words that are easy to picture
+//! phraseforge --filter-script ./no-brands.sh  # Drop words your program rejects
+//! phraseforge --template noun --reject-common  # No "dragon" or "monkey" alone
+//! phraseforge --alliterate  # 7-brave-badgers-bounce-briskly
+//! phraseforge --acrostic safe  # 12-silent-anchors-fold-evenly
+//! phraseforge --rhyme  # 40-bright-kites-wander-slowly
+//! phraseforge --count 10 --sort-by memorability  # Easiest to remember first
+//! phraseforge --explain  # Print each word's definition under the phrase
+//! phraseforge --spell  # Print a NATO phonetic spelling under the phrase
+//! phraseforge --screen-reader  # 4-brave-otters, then "four, dash, brave, ..."
+//! phraseforge --annotate  # How common each word is, e.g. rank 412 of 6210
+//! phraseforge --abbrev  # Also print the initials (7bbbb) as a written hint
+//! phraseforge --typo-report  # Words one typo from another word in their pool
+//! phraseforge --leet heavy  # 512-br4v3-b4dg3r$-8ounc3-br1$kly
+//! phraseforge --mutate 2  # 88-quiet-otteRs-sin#g-softly
+//! phraseforge --hash sha512-crypt  # Hash on stdout, phrase on stderr
+//! phraseforge random --qr  # Scan a Wi-Fi passphrase instead of typing it
+//! phraseforge --count 500 --output batch.gpg --encrypt-to ops@example.com
+//! phraseforge --log-history && phraseforge history  # Was this phrase ours?
+//! phraseforge --count 50 --avoid-file /srv/issued.avoid  # No phrase twice
+//! phraseforge --keyring email && phraseforge recall email  # OS keyring
+//! phraseforge --keyring vpn --remind 90d  # Then `phraseforge reminders`
+//! phraseforge store --backend pass --entry sites/example  # Never printed
+//! phraseforge --policy ad-complex --template "{adjective}{noun}"  # quietOtters4
+//! phraseforge random --length 20 --charset alnum+symbols  # Character password
+//! phraseforge pin --digits 6 --count 10  # Ten 6-digit PINs
+//! echo "43621 13426 55215" | phraseforge dice  # One word per five rolls
+//! phraseforge derive --site github.com  # Same phrase every time for a site
 //! ```
 //!
 //! ## License
@@ -30,131 +217,423 @@
 //! You should have received a copy of the GNU General Public License
 //! along with this program. If not, see <https://www.gnu.org/licenses/>.
 //!
-use clap::{Arg, Command as clap_command};
-use directories::ProjectDirs;
-use inflector::string::pluralize::to_plural;
-use rand::prelude::*;
-use reqwest::blocking::get;
-use std::collections::HashSet;
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, Command as clap_command};
+use rand::seq::IndexedRandom;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use unicode_normalization::UnicodeNormalization;
+use unicode_width::UnicodeWidthStr;
+
+mod abbrev;
+mod ambiguity;
+mod annotate;
+mod audit;
+mod avoid;
+mod bootstrap;
+mod bundled;
+mod cache;
+mod capabilities;
+mod caps;
+mod codec;
+mod color;
+mod concrete;
+mod config;
+mod corpus;
+mod datadir;
+mod derive;
+mod diceware;
+mod dictionary;
+mod doctor;
+mod download;
+mod encrypt;
+mod entropy;
+mod ergonomics;
+mod exit;
+mod explain;
+mod explore;
+mod export;
+mod filter;
+mod frequency;
+mod generate;
+mod hash;
+mod hibp;
+mod history;
+mod inflect;
+mod keychain;
+mod leet;
+mod memorability;
+mod moby;
+mod mutate;
+mod normalize;
+mod overlay;
+mod pack;
+mod phonetic;
+mod pin;
+mod policy;
+mod practice;
+mod provenance;
+mod qr;
+mod random;
+mod reminders;
+mod rhyme;
+mod sandbox;
+mod scowl;
+mod script;
+mod secret;
+mod selftest;
+mod senses;
+mod sentence;
+mod session;
+mod setup;
+mod shamir;
+mod similar;
+mod sources;
+mod speech;
+mod spell;
+mod store;
+mod strength;
+mod template;
+mod theme;
+mod transcript;
+mod typo;
+mod update;
+mod variants;
+mod web;
+mod wordnet;
+
+use ambiguity::Ambiguity;
+use audit::AuditLog;
+use avoid::AvoidList;
+use concrete::ConcreteFilter;
+use config::{Config, SECONDS_PER_DAY};
+use corpus::CommonPasswords;
+use dictionary::Dictionary;
+use download::{Destination, Download};
+use encrypt::{EncryptedOutput, Recipients};
+use ergonomics::{ErgonomicsFilter, Layout};
+use explain::Glossary;
+use filter::{apply_filters, Pipeline, Stage, WordFilter};
+use generate::{
+    acrostic_problems, count_eligible, generate_compliant, number_choices, rhyme_slots,
+    GeneratedPhrase, GenerationOptions, MinFrequency,
+};
+use hash::HashAlgorithm;
+use history::{HistoryLog, Record};
+use inflect::VerbForm;
+use leet::LeetLevel;
+use overlay::{Overlay, OVERLAY_FILE};
+use pack::Pack;
+use policy::Policy;
+use random::Charset;
+use reminders::ReminderLog;
+use script::ScriptFilter;
+use senses::SenseFilter;
+use session::Session;
+use sources::Sources;
+use store::{Backend, StoreTarget};
+use template::{Slot, Template};
+use theme::{Theme, ThemeFilter};
+use transcript::Transcript;
+use variants::{Spelling, SpellingFilter};
 
 const WORDNET_URL: &str = "https://wordnetcode.princeton.edu/3.0/WNdb-3.0.tar.gz";
 const WORDNET_ARCHIVE: &str = "WNdb-3.0.tar.gz";
-const HERMIT_DAVES_FREQUENTLY_USED_WORD_LIST_URL: &str =
-    "https://raw.githubusercontent.com/hermitdave/FrequencyWords/refs/heads/master/content/2018/en/en_full.txt";
-const HERMIT_DAVES_FREQUENTLY_USED_WORD_LIST_ARCHIVE: &str = "en_full.txt";
+/// Directory of Hermit Dave's English frequency lists, each `<variant>.txt`.
+const HERMIT_DAVES_FREQUENTLY_USED_WORD_LISTS_URL: &str =
+    "https://raw.githubusercontent.com/hermitdave/FrequencyWords/refs/heads/master/content/2018/en";
+/// The frequency list variants: every word seen in the subtitle corpus, or
+/// only the 50,000 most frequent, a fraction of the size. WordNet is English,
+/// so only the English lists are of any use.
+const FREQUENCY_LISTS: [&str; 2] = ["en_full", "en_50k"];
+const DEFAULT_FREQUENCY_LIST: &str = "en_full";
 const MINIMUM_WORD_LENGTH: usize = 4;
+const PROPER_NOUNS_FILE: &str = "proper_nouns.txt";
+/// Fewer entries than this means a word list is truncated; the real lists have
+/// thousands.
+const MIN_WORD_LIST_ENTRIES: usize = 100;
 
-fn get_data_dir() -> PathBuf {
-    let proj_dirs =
-        ProjectDirs::from("com", "tynsol", "phraseforge").expect("Failed to get data directory");
-    let data_dir = proj_dirs.data_local_dir().to_path_buf();
-    fs::create_dir_all(&data_dir).expect("Failed to create data directory");
-    data_dir
-}
-
-fn download_and_extract_wordnet_dictionary(data_dir: &PathBuf) {
-    println!("Downloading WordNet Dictionary...");
-    let response = get(WORDNET_URL).expect("Failed to download WordNet");
-    let bytes = response.bytes().expect("Failed to read response bytes");
-
-    let archive_path = data_dir.join(WORDNET_ARCHIVE);
-    fs::write(&archive_path, &bytes).expect("Failed to save archive");
-
-    println!("Extracting WordNet Dictionary...");
-    Command::new("tar")
-        .arg("-xzf")
-        .arg(&archive_path)
-        .arg("-C")
-        .arg(data_dir)
-        .status()
-        .expect("Failed to extract WordNet");
-    fs::remove_file(&archive_path).expect("Failed to remove archive file");
-}
-
-fn download_master_word_list(data_dir: &PathBuf) {
-    println!("Downloading Frequently used Word List...");
-    let response = get(HERMIT_DAVES_FREQUENTLY_USED_WORD_LIST_URL)
-        .expect("Failed to download frequently used word list");
-
-    let archive_path = data_dir.join(HERMIT_DAVES_FREQUENTLY_USED_WORD_LIST_ARCHIVE);
-    fs::write(&archive_path, response.bytes().unwrap())
-        .expect("Failed to save frequently used word list file");
-}
-
-fn generate_word_list(dictionary: &PathBuf, master_word_list: &PathBuf) -> Vec<String> {
-    // Step 1: Collect valid first words from the dictionary file
-    let dictionary_file = File::open(dictionary).expect("Failed to open dictionary file");
-    let dictionary_reader = BufReader::new(dictionary_file);
-    let mut dictionary_words: HashSet<String> = HashSet::new();
-
-    for line in dictionary_reader.lines() {
-        let line = line.expect("Failed to read line");
-        // Extract the first word and check if it starts with an ASCII letter
-        let first_word = line.split_whitespace().next().unwrap_or("").to_string();
-        if !first_word.is_empty()
-            && first_word.len() >= MINIMUM_WORD_LENGTH
-            && first_word.chars().all(|c| c.is_ascii_alphabetic())
-        {
-            dictionary_words.insert(first_word);
+/// The directory holding the versioned cache and the word overlay:
+/// `--cache-dir` if given, else the platform data directory.
+fn data_root(matches: &clap::ArgMatches) -> PathBuf {
+    matches
+        .get_one::<PathBuf>("cache-dir")
+        .cloned()
+        .or_else(|| sandbox::root().map(Path::to_path_buf))
+        .unwrap_or_else(datadir::platform_root)
+}
+
+/// The cache to read word lists from: the user's own once it is current,
+/// else a system-wide install generated with the same settings, else the
+/// user's cache, built on demand. `synsets` is set when the run needs every
+/// WordNet synset file.
+fn word_list_dir(
+    matches: &clap::ArgMatches,
+    params: &cache::GenerationParams,
+    synsets: bool,
+) -> PathBuf {
+    if matches.contains_id("cache-dir") || matches.get_flag("redownload") {
+        return get_data_dir(matches);
+    }
+    if let Some(dir) = datadir::current_user_cache(params) {
+        return dir;
+    }
+    match datadir::system_cache(params) {
+        Some(dir) if !synsets || has_synset_data(&dir) => {
+            log::debug!("Using the system-wide word lists in {}", dir.display());
+            dir
         }
+        _ => get_data_dir(matches),
+    }
+}
+
+fn get_data_dir(matches: &clap::ArgMatches) -> PathBuf {
+    let explicit = matches.contains_id("cache-dir") || sandbox::root().is_some();
+    datadir::cache_dir(&data_root(matches), explicit)
+        .unwrap_or_else(|e| exit::fail(exit::FAILURE, e))
+}
+
+/// Where a frequency list variant is cached, e.g. `en_full.txt`.
+fn frequency_list_file(variant: &str) -> String {
+    format!("{}.txt", variant)
+}
+
+fn generation_params(sources: &Sources, assign_part_of_speech: bool) -> cache::GenerationParams {
+    cache::GenerationParams {
+        minimum_word_length: MINIMUM_WORD_LENGTH,
+        frequency_list: sources.frequency_list_variant.clone(),
+        dictionary: sources.dictionary.name(),
+        assign_part_of_speech,
+    }
+}
+
+/// An empty scratch directory inside the cache; rebuilds happen here and are
+/// moved into place once complete.
+fn staging_dir(data_dir: &Path) -> PathBuf {
+    let staging = data_dir.join(".staging");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).expect("Failed to clear stale staging directory");
+    }
+    fs::create_dir_all(&staging).expect("Failed to create staging directory");
+    staging
+}
+
+/// Whether `path` in the WordNet archive is one of the per-part-of-speech
+/// `dict` files whose prefix is among `kinds`.
+fn wordnet_file(path: &Path, kinds: &[&str]) -> bool {
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    let suffix = path.extension().and_then(|suffix| suffix.to_str());
+    path.parent() == Some(Path::new("dict"))
+        && stem.is_some_and(|stem| kinds.contains(&stem))
+        && suffix.is_some_and(|suffix| {
+            PartOfSpeech::ALL
+                .iter()
+                .any(|pos| pos.wordnet_suffix() == suffix)
+        })
+}
+
+/// The files the word lists are generated from: the `index.*` files, plus
+/// `data.noun` to tell proper nouns apart. The rest of the archive is
+/// several times larger and only some modes need it.
+fn wordnet_index_file(path: &Path) -> bool {
+    wordnet_file(path, &["index"]) || path == Path::new("dict/data.noun")
+}
+
+/// Every `index.*` and `data.*` file, for `--explain`, which reads glosses
+/// for each part of speech, and `--concrete`, which reads adjective synsets.
+fn wordnet_synset_file(path: &Path) -> bool {
+    wordnet_file(path, &["index", "data"])
+}
+
+fn has_synset_data(data_dir: &Path) -> bool {
+    let dict = data_dir.join("dict");
+    ["data.adj", "data.verb", "data.adv"]
+        .iter()
+        .all(|file| dict.join(file).exists())
+}
+
+/// Which WordNet files to extract: synsets only when asked for or when
+/// they're already cached, so a re-download doesn't drop them.
+fn wordnet_files_wanted(data_dir: &Path, synsets: bool) -> fn(&Path) -> bool {
+    if synsets || has_synset_data(data_dir) {
+        wordnet_synset_file
+    } else {
+        wordnet_index_file
+    }
+}
+
+/// Swaps the `dict` directory unpacked into `staging` in for the installed
+/// one.
+fn install_wordnet_dictionary(data_dir: &Path, staging: &Path) {
+    let dict = data_dir.join("dict");
+    if dict.exists() {
+        update::rename(&dict, &staging.join("dict.old")).expect("Failed to move old WordNet aside");
     }
+    update::rename(&staging.join("dict"), &dict).expect("Failed to install WordNet");
+}
+
+/// Keeps each word only in the dictionary where its senses were tagged most
+/// often, the earlier dictionary winning ties.
+fn assign_parts_of_speech(dictionaries: &[HashMap<String, u32>]) -> Vec<HashSet<String>> {
+    dictionaries
+        .iter()
+        .enumerate()
+        .map(|(i, dictionary)| {
+            dictionary
+                .iter()
+                .filter(|(word, &tagged)| {
+                    dictionaries.iter().enumerate().all(|(j, other)| {
+                        other.get(*word).is_none_or(|&other_tagged| {
+                            j == i || other_tagged < tagged || (other_tagged == tagged && j > i)
+                        })
+                    })
+                })
+                .map(|(word, _)| word.clone())
+                .collect()
+        })
+        .collect()
+}
 
-    // Step 2: Process the master word list file and include matching lines
+/// Sorts the frequency list's entries into one list per dictionary, in a
+/// single pass, as `word count` lines. A word in several dictionaries lands
+/// in each of their lists.
+fn split_master_word_list(
+    master_word_list: &Path,
+    dictionaries: &[HashSet<String>],
+) -> Vec<Vec<String>> {
     let master_word_list_file =
         File::open(master_word_list).expect("Failed to open word list file");
-    let master_word_list_reader = BufReader::new(master_word_list_file);
-    let mut word_list = Vec::new();
+    let parsed = frequency::parse(BufReader::new(master_word_list_file));
+    frequency::report_skipped(&master_word_list.display().to_string(), &parsed);
+    let mut word_lists = vec![Vec::new(); dictionaries.len()];
 
-    for line in master_word_list_reader.lines() {
-        let line = line.expect("Failed to read line");
-        // Extract the first word from the line in the word list
-        let first_word = line.split_whitespace().next().unwrap_or("").to_string();
-
-        // Only include the full line if the first word exists in the dictionary_words set
-        if !first_word.is_empty() && dictionary_words.contains(&first_word) {
-            word_list.push(line);
+    for (word, count) in &parsed.entries {
+        for (dictionary, word_list) in dictionaries.iter().zip(&mut word_lists) {
+            if dictionary.contains(word) {
+                word_list.push(format!("{} {}", word, count));
+            }
         }
     }
-
-    word_list
+    word_lists
 }
 
-fn save_word_list(words: &[String], file_path: &PathBuf) {
+fn save_word_list(words: &[String], file_path: &Path) {
     let mut file = fs::File::create(file_path).expect("Failed to create word list file");
     for word in words {
         writeln!(file, "{}", word).expect("Failed to write word to file");
     }
 }
 
-fn word_lists_exist(data_dir: &PathBuf) -> bool {
-    let word_files = vec!["adjectives.txt", "nouns.txt", "verbs.txt", "adverbs.txt"];
-    word_files.iter().all(|file| data_dir.join(file).exists())
+/// Splits word list lines into those whose word is not in `excluded` and those
+/// whose word is.
+fn partition_excluded(
+    lines: Vec<String>,
+    excluded: &HashSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    lines.into_iter().partition(|line| {
+        line.split_whitespace()
+            .next()
+            .is_some_and(|word| !excluded.contains(word))
+    })
 }
 
-fn generate_word_lists(data_dir: &PathBuf) {
-    let word_files = [
-        ("index.adj", "adjectives.txt"),
-        ("index.noun", "nouns.txt"),
-        ("index.verb", "verbs.txt"),
-        ("index.adv", "adverbs.txt"),
-    ];
+/// Files `generate_word_lists` derives from the downloaded sources.
+const GENERATED_FILES: [&str; 5] = [
+    "adjectives.txt",
+    "nouns.txt",
+    "verbs.txt",
+    "adverbs.txt",
+    PROPER_NOUNS_FILE,
+];
 
-    let dict_dir = data_dir.join("dict");
-    let word_list_path = data_dir.join(HERMIT_DAVES_FREQUENTLY_USED_WORD_LIST_ARCHIVE);
+fn word_lists_exist(data_dir: &Path) -> bool {
+    missing_word_lists(data_dir, &PartOfSpeech::ALL.into_iter().collect()).is_empty()
+}
 
-    let mut word_lists = std::collections::HashMap::new();
+/// The word list files for `pools` that `data_dir` lacks.
+fn missing_word_lists(data_dir: &Path, pools: &HashSet<PartOfSpeech>) -> Vec<&'static str> {
+    PartOfSpeech::ALL
+        .iter()
+        .filter(|pos| pools.contains(pos))
+        .map(PartOfSpeech::word_list_file)
+        .filter(|file| !data_dir.join(file).exists())
+        .collect()
+}
 
-    // Load all word lists into memory
-    for (dictionary_file, output_file) in &word_files {
-        let dictionary_path = dict_dir.join(dictionary_file);
-        let words = generate_word_list(&dictionary_path, &word_list_path);
-        word_lists.insert(*output_file, words);
+/// Loads the lists `pools` need from a cache holding only some of them,
+/// such as a partial custom install, instead of rebuilding it. Returns
+/// `None` for a cache with all of them or none, which the usual checks
+/// handle, and exits naming the files if a needed one is missing.
+fn load_partial_word_lists(
+    data_dir: &Path,
+    pools: &HashSet<PartOfSpeech>,
+    allow_proper_nouns: bool,
+) -> Option<WordLists> {
+    let absent = missing_word_lists(data_dir, &PartOfSpeech::ALL.into_iter().collect());
+    if absent.is_empty() || absent.len() == PartOfSpeech::ALL.len() {
+        return None;
     }
+    let missing = missing_word_lists(data_dir, pools);
+    if !missing.is_empty() {
+        exit::fail(
+            exit::CACHE_CORRUPTION,
+            format!(
+                "this run needs {}, missing from {}\nCopy in the missing lists, use a template without those words, or run again with --redownload to rebuild every list.",
+                missing.join(" and "),
+                data_dir.display()
+            ),
+        );
+    }
+    if let Err(problem) = validate_word_lists(data_dir, pools) {
+        exit::fail(
+            exit::CACHE_CORRUPTION,
+            format!(
+                "{}\nRun again with --redownload to rebuild every list.",
+                problem
+            ),
+        );
+    }
+    log::debug!(
+        "{} absent from {}, but this run doesn't need it",
+        absent.join(" and "),
+        data_dir.display()
+    );
+    Some(load_word_lists(data_dir, pools, allow_proper_nouns))
+}
+
+/// Builds the word lists from `dictionary` in `data_dir` and
+/// `frequency_list`, writing them to `output_dir`.
+fn generate_word_lists(
+    data_dir: &Path,
+    dictionary: &Dictionary,
+    frequency_list: &Path,
+    params: &cache::GenerationParams,
+    output_dir: &Path,
+) {
+    // Read the dictionary, then match the frequency list against all four
+    // parts of speech at once
+    let pools = dictionary.pools(data_dir, params.minimum_word_length);
+    let proper_nouns = pools.proper_nouns;
+    let dictionaries = if params.assign_part_of_speech {
+        assign_parts_of_speech(&pools.words)
+    } else {
+        pools
+            .words
+            .into_iter()
+            .map(|dictionary| dictionary.into_keys().collect())
+            .collect()
+    };
+    let mut word_lists: std::collections::HashMap<_, _> = PartOfSpeech::ALL
+        .iter()
+        .map(PartOfSpeech::word_list_file)
+        .zip(split_master_word_list(frequency_list, &dictionaries))
+        .collect();
 
     // Post-process nouns to exclude any words found in other categories
     let nouns = word_lists
@@ -183,74 +662,169 @@ fn generate_word_lists(data_dir: &PathBuf) {
         })
         .collect();
 
-    word_lists.insert("nouns.txt", filtered_nouns);
+    // Set proper nouns and acronyms aside so they can be re-enabled at load time
+    let (common_nouns, proper_nouns) = partition_excluded(filtered_nouns, &proper_nouns);
+
+    word_lists.insert("nouns.txt", common_nouns);
+    word_lists.insert(PROPER_NOUNS_FILE, proper_nouns);
 
     // Save all word lists
     for (output_file, words) in word_lists {
-        let out_path = data_dir.join(output_file);
+        let out_path = output_dir.join(output_file);
         save_word_list(&words, &out_path);
     }
 }
 
-fn pick_random_above_frequency(
-    word_entries: &[WordEntry],
-    min_frequency: &u32,
-    rng: &mut ThreadRng,
-) -> String {
-    let filtered: Vec<&WordEntry> = word_entries
-        .iter()
-        .filter(|entry| entry.frequency > *min_frequency)
-        .collect();
-
-    filtered
-        .choose(rng)
-        .map(|entry| entry.word.clone())
-        .unwrap_or_else(|| "".to_string())
+/// `generate::generate_compliant` with the thread RNG, exiting with its
+/// explanation if the requirements can't be met.
+fn generate_compliant_password(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+) -> GeneratedPhrase {
+    generate_compliant(word_lists, options, &mut rand::rng())
+        .unwrap_or_else(|e| exit::fail(exit::UNSATISFIABLE, e))
 }
 
-fn generate_password(word_lists: &WordLists, min_frequency: &u32) -> String {
-    let mut rng = rand::rng();
-    let num: u32 = rng.random_range(1..999);
+/// Rounds in a row that may accept no phrase before a batch gives up.
+const MAX_FUTILE_ROUNDS: usize = 20;
 
-    let adj = if let WordType::Adjective(entries) = &word_lists.adjectives {
-        pick_random_above_frequency(entries, min_frequency, &mut rng)
-    } else {
-        String::new()
-    };
+/// Generates `count` phrases for `--unique`, `--hibp`, `--avoid-file` and
+/// `--series`, which judge each phrase against the rest of the batch, a
+/// remote corpus and the phrases issued before. Each round generates the
+/// phrases still needed in parallel, policy checks and Argon2 hashes
+/// included, then looks them all up at once; rejected ones are replaced in
+/// the next round. A series' phrases are drawn one after another instead,
+/// each from the words the accepted ones and the round's earlier
+/// candidates left.
+fn generate_validated_batch(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    count: usize,
+    unique: bool,
+    series: bool,
+    mut hibp: Option<hibp::Checker>,
+    mut avoid: Option<&mut AvoidList>,
+) -> Vec<GeneratedPhrase> {
+    let mut unused = series.then(|| word_lists.clone());
+    let mut accepted = Vec::with_capacity(count);
+    let mut seen = HashSet::new();
+    let mut breached = 0;
+    let mut issued_before = 0;
+    let mut futile_rounds = 0;
+    while accepted.len() < count {
+        let candidates: Vec<GeneratedPhrase> = match &unused {
+            Some(unused) => {
+                let mut left = unused.clone();
+                (accepted.len()..count)
+                    .map(|_| {
+                        let phrase = generate_compliant_password(&left, options);
+                        left.remove_words(&phrase.words);
+                        phrase
+                    })
+                    .collect()
+            }
+            None => (accepted.len()..count)
+                .into_par_iter()
+                .map(|_| generate_compliant_password(word_lists, options))
+                .collect(),
+        };
+        let candidates: Vec<GeneratedPhrase> = candidates
+            .into_iter()
+            .filter(|phrase| !unique || seen.insert(phrase.text.clone()))
+            .collect();
+        let texts: Vec<String> = candidates
+            .iter()
+            .map(|phrase| phrase.text.clone())
+            .collect();
+        let verdicts = match &mut hibp {
+            Some(checker) => checker
+                .breached(&texts)
+                .unwrap_or_else(|e| exit::fail(exit::NETWORK, e)),
+            None => vec![false; candidates.len()],
+        };
+        let hashes: Option<Vec<avoid::Hash>> = avoid
+            .as_deref()
+            .map(|avoid| texts.par_iter().map(|text| avoid.hash(text)).collect());
 
-    let noun = if let WordType::Noun(entries) = &word_lists.nouns {
-        let n = pick_random_above_frequency(entries, min_frequency, &mut rng);
-        if num > 1 && !n.is_empty() {
-            to_plural(&n)
+        let before = accepted.len();
+        for (index, (phrase, is_breached)) in candidates.into_iter().zip(verdicts).enumerate() {
+            if is_breached {
+                breached += 1;
+                continue;
+            }
+            if let (Some(avoid), Some(hashes)) = (&mut avoid, &hashes) {
+                if !avoid.insert(hashes[index]) {
+                    issued_before += 1;
+                    continue;
+                }
+            }
+            if let Some(unused) = &mut unused {
+                unused.remove_words(&phrase.words);
+            }
+            accepted.push(phrase);
+        }
+        futile_rounds = if accepted.len() == before {
+            futile_rounds + 1
         } else {
-            n
+            0
+        };
+        if futile_rounds == MAX_FUTILE_ROUNDS {
+            exit::fail(
+                exit::UNSATISFIABLE,
+                format!(
+                    "only {} of {} phrases could be generated; every new one repeated an earlier phrase, had been issued before or was found in a breach. Lower --min-frequency or use a longer template.",
+                    accepted.len(),
+                    count
+                ),
+            );
         }
-    } else {
-        String::new()
-    };
-
-    let verb = if let WordType::Verb(entries) = &word_lists.verbs {
-        pick_random_above_frequency(entries, min_frequency, &mut rng)
-    } else {
-        String::new()
-    };
+    }
+    if breached > 0 {
+        log::info!("Replaced {} phrases found in HIBP", breached);
+    }
+    if issued_before > 0 {
+        log::info!("Replaced {} phrases issued before", issued_before);
+    }
+    accepted
+}
 
-    let adv = if let WordType::Adverb(entries) = &word_lists.adverbs {
-        pick_random_above_frequency(entries, min_frequency, &mut rng)
-    } else {
-        String::new()
-    };
+/// The acrostic's letters without their diacritics, which word initials are
+/// matched without too.
+fn parse_acrostic(text: &str) -> Result<Vec<char>, String> {
+    if !normalize::is_word(text) {
+        return Err("acrostic must consist of letters only".to_string());
+    }
+    Ok(text
+        .chars()
+        .filter_map(|c| normalize::initial(&c.to_string()))
+        .collect())
+}
 
-    format!("{}-{}-{}-{}-{}", num, adj, noun, verb, adv)
+/// Any Unicode, emoji included, but no control characters: a tab or newline
+/// in a password is almost never intended and rarely survives a paste. It is
+/// composed (NFC) like the words, and may not start with a combining mark,
+/// which would fuse with the last letter of the word before it.
+fn parse_separator(text: &str) -> Result<String, String> {
+    if text.chars().any(char::is_control) {
+        return Err("separator must not contain control characters".to_string());
+    }
+    if text
+        .chars()
+        .next()
+        .is_some_and(unicode_normalization::char::is_combining_mark)
+    {
+        return Err("separator must not start with a combining mark".to_string());
+    }
+    Ok(text.nfc().collect())
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct WordEntry {
     word: String,
     frequency: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum WordType {
     Adjective(Vec<WordEntry>),
     Noun(Vec<WordEntry>),
@@ -258,7 +832,113 @@ enum WordType {
     Adverb(Vec<WordEntry>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PartOfSpeech {
+    Adjective,
+    Noun,
+    Verb,
+    Adverb,
+}
+
+impl PartOfSpeech {
+    const ALL: [PartOfSpeech; 4] = [
+        PartOfSpeech::Adjective,
+        PartOfSpeech::Noun,
+        PartOfSpeech::Verb,
+        PartOfSpeech::Adverb,
+    ];
+
+    /// Suffix of the WordNet `index.*` and `data.*` files for this part of speech.
+    fn wordnet_suffix(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Adjective => "adj",
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adverb => "adv",
+        }
+    }
+
+    /// The part of speech a slot draws from, or `None` for the number slot.
+    fn of_slot(slot: Slot) -> Option<PartOfSpeech> {
+        match slot {
+            Slot::Number => None,
+            Slot::Adjective => Some(PartOfSpeech::Adjective),
+            Slot::Noun => Some(PartOfSpeech::Noun),
+            Slot::Verb(_) => Some(PartOfSpeech::Verb),
+            Slot::Adverb => Some(PartOfSpeech::Adverb),
+        }
+    }
+
+    /// The generated word list for this part of speech.
+    fn word_list_file(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Adjective => "adjectives.txt",
+            PartOfSpeech::Noun => "nouns.txt",
+            PartOfSpeech::Verb => "verbs.txt",
+            PartOfSpeech::Adverb => "adverbs.txt",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PartOfSpeech::Adjective => "adjective",
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adverb => "adverb",
+        }
+    }
+
+    /// Parses a name, or WordNet's abbreviation of one.
+    fn from_name(name: &str) -> Option<PartOfSpeech> {
+        PartOfSpeech::ALL
+            .into_iter()
+            .find(|pos| pos.name() == name || pos.wordnet_suffix() == name)
+    }
+
+    /// Part of speech names for clap, accepting WordNet's `adj` and `adv`
+    /// abbreviations too.
+    fn possible_values() -> [PossibleValue; 4] {
+        PartOfSpeech::ALL.map(|pos| {
+            let value = PossibleValue::new(pos.name());
+            if pos.wordnet_suffix() == pos.name() {
+                value
+            } else {
+                value.alias(pos.wordnet_suffix())
+            }
+        })
+    }
+}
+
+impl WordType {
+    fn entries(&self) -> &[WordEntry] {
+        match self {
+            WordType::Adjective(entries)
+            | WordType::Noun(entries)
+            | WordType::Verb(entries)
+            | WordType::Adverb(entries) => entries,
+        }
+    }
+
+    fn entries_mut(&mut self) -> &mut Vec<WordEntry> {
+        match self {
+            WordType::Adjective(entries)
+            | WordType::Noun(entries)
+            | WordType::Verb(entries)
+            | WordType::Adverb(entries) => entries,
+        }
+    }
+
+    fn part_of_speech(&self) -> PartOfSpeech {
+        match self {
+            WordType::Adjective(_) => PartOfSpeech::Adjective,
+            WordType::Noun(_) => PartOfSpeech::Noun,
+            WordType::Verb(_) => PartOfSpeech::Verb,
+            WordType::Adverb(_) => PartOfSpeech::Adverb,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct WordLists {
     adjectives: WordType,
     nouns: WordType,
@@ -266,28 +946,131 @@ struct WordLists {
     adverbs: WordType,
 }
 
-fn load_word_list(word_list: &PathBuf) -> Vec<WordEntry> {
+impl WordLists {
+    /// The word pool a slot draws from, or `None` for the number slot.
+    fn pool(&self, slot: Slot) -> Option<&[WordEntry]> {
+        match slot {
+            Slot::Number => None,
+            Slot::Adjective => Some(self.adjectives.entries()),
+            Slot::Noun => Some(self.nouns.entries()),
+            Slot::Verb(_) => Some(self.verbs.entries()),
+            Slot::Adverb => Some(self.adverbs.entries()),
+        }
+    }
+
+    fn entries_of(&self, pos: PartOfSpeech) -> &[WordEntry] {
+        match pos {
+            PartOfSpeech::Adjective => self.adjectives.entries(),
+            PartOfSpeech::Noun => self.nouns.entries(),
+            PartOfSpeech::Verb => self.verbs.entries(),
+            PartOfSpeech::Adverb => self.adverbs.entries(),
+        }
+    }
+
+    fn pools_mut(&mut self) -> [&mut WordType; 4] {
+        [
+            &mut self.adjectives,
+            &mut self.nouns,
+            &mut self.verbs,
+            &mut self.adverbs,
+        ]
+    }
+
+    /// Takes a phrase's words out of every pool, so no later phrase of a
+    /// `--series` repeats them.
+    fn remove_words(&mut self, words: &[(Slot, String)]) {
+        for pool in self.pools_mut() {
+            pool.entries_mut()
+                .retain(|entry| !words.iter().any(|(_, word)| *word == entry.word));
+        }
+    }
+}
+
+fn load_word_list(word_list: &Path) -> Vec<WordEntry> {
     let file = File::open(word_list).expect("Failed to open word list file.");
-    let reader = BufReader::new(file);
-
-    reader
-        .lines()
-        .filter_map(|line| {
-            let line = line.ok()?;
-            let mut parts = line.split_whitespace();
-            let word = parts.next()?.to_string();
-            let freq_str = parts.next()?;
-            let frequency = freq_str.parse::<u32>().ok()?;
-            Some(WordEntry { word, frequency })
-        })
+    let parsed = frequency::parse(BufReader::new(file));
+    frequency::report_skipped(&word_list.display().to_string(), &parsed);
+    word_entries(parsed)
+}
+
+fn word_entries(parsed: frequency::Parsed) -> Vec<WordEntry> {
+    parsed
+        .entries
+        .into_iter()
+        .map(|(word, frequency)| WordEntry { word, frequency })
         .collect()
 }
 
-fn load_all_word_lists(base_path: &PathBuf) -> WordLists {
-    let adjectives = load_word_list(&base_path.join("adjectives.txt"));
-    let nouns = load_word_list(&base_path.join("nouns.txt"));
-    let verbs = load_word_list(&base_path.join("verbs.txt"));
-    let adverbs = load_word_list(&base_path.join("adverbs.txt"));
+/// Checks that a word list is complete: newline-terminated, every line a
+/// `word frequency` pair, and at least `min_entries` of them.
+fn validate_word_list(path: &Path, min_entries: usize) -> Result<(), String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let text = fs::read_to_string(path).map_err(|e| format!("{} is unreadable: {}", name, e))?;
+    if !text.is_empty() && !text.ends_with('\n') {
+        return Err(format!("{} ends mid-line", name));
+    }
+    for (number, line) in text.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        let parsed = parts.next().is_some_and(normalize::is_word)
+            && parts.next().is_some_and(|freq| freq.parse::<u32>().is_ok())
+            && parts.next().is_none();
+        if !parsed {
+            return Err(format!(
+                "{} line {} is malformed: {:?}",
+                name,
+                number + 1,
+                line
+            ));
+        }
+    }
+    let entries = text.lines().count();
+    if entries < min_entries {
+        return Err(format!(
+            "{} has {} entries, expected at least {}",
+            name, entries, min_entries
+        ));
+    }
+    Ok(())
+}
+
+fn validate_word_lists(data_dir: &Path, pools: &HashSet<PartOfSpeech>) -> Result<(), String> {
+    for pos in pools {
+        validate_word_list(&data_dir.join(pos.word_list_file()), MIN_WORD_LIST_ENTRIES)?;
+    }
+    let proper_nouns = data_dir.join(PROPER_NOUNS_FILE);
+    if pools.contains(&PartOfSpeech::Noun) && proper_nouns.exists() {
+        validate_word_list(&proper_nouns, 0)?;
+    }
+    Ok(())
+}
+
+/// The parts of speech a template draws words from.
+fn template_pools(template: &Template) -> HashSet<PartOfSpeech> {
+    template.slots().filter_map(PartOfSpeech::of_slot).collect()
+}
+
+/// Loads the word lists for `pools`; the others are left empty, since a
+/// template that never uses them needn't pay to parse them.
+fn load_word_lists(
+    base_path: &Path,
+    pools: &HashSet<PartOfSpeech>,
+    allow_proper_nouns: bool,
+) -> WordLists {
+    let load = |pos: PartOfSpeech| {
+        if pools.contains(&pos) {
+            load_word_list(&base_path.join(pos.word_list_file()))
+        } else {
+            Vec::new()
+        }
+    };
+    let adjectives = load(PartOfSpeech::Adjective);
+    let mut nouns = load(PartOfSpeech::Noun);
+    let proper_nouns_path = base_path.join(PROPER_NOUNS_FILE);
+    if allow_proper_nouns && pools.contains(&PartOfSpeech::Noun) && proper_nouns_path.exists() {
+        nouns.extend(load_word_list(&proper_nouns_path));
+    }
+    let verbs = load(PartOfSpeech::Verb);
+    let adverbs = load(PartOfSpeech::Adverb);
     WordLists {
         adjectives: WordType::Adjective(adjectives),
         nouns: WordType::Noun(nouns),
@@ -296,62 +1079,3452 @@ fn load_all_word_lists(base_path: &PathBuf) -> WordLists {
     }
 }
 
-fn load_or_generate_word_lists(data_dir: &PathBuf, force_download: bool) -> WordLists {
-    if !word_lists_exist(data_dir) || force_download {
-        download_and_extract_wordnet_dictionary(data_dir);
-        download_master_word_list(data_dir);
-        generate_word_lists(data_dir);
+/// Whether `--sort-by memorability` rates concreteness, which reads
+/// WordNet's synsets and so needs the WordNet dictionary.
+fn memorability_synsets(matches: &clap::ArgMatches, sources: &Sources) -> bool {
+    matches.contains_id("sort-by") && sources.dictionary == Dictionary::WordNet
+}
+
+/// Loads the word lists for `pools`, applies any `--pack`, the user's
+/// overlay and then every filter the command line selects. The bundled lists
+/// stand in until the full ones are cached, unless a mode needs the WordNet
+/// dictionary itself. Returns the lists, whether they are the bundled ones,
+/// and the names of the filters applied.
+fn prepare_word_lists(
+    matches: &clap::ArgMatches,
+    data_dir: &Path,
+    sources: &Sources,
+    generation: &cache::GenerationParams,
+    pools: &HashSet<PartOfSpeech>,
+    unambiguous: Option<&Ambiguity>,
+    common_passwords: Option<&CommonPasswords>,
+) -> (WordLists, bool, Pipeline) {
+    let pack = matches.get_one::<String>("pack").map(|name| {
+        Pack::load(&datadir::overlay_dir(&data_root(matches)), name).unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+        })
+    });
+    // The pack's parts of speech needn't be loaded from the usual lists.
+    let pack_pools = pack.as_ref().map(Pack::parts_of_speech).unwrap_or_default();
+    let pools: HashSet<PartOfSpeech> = pools.difference(&pack_pools).copied().collect();
+    let force_download = matches.get_flag("redownload");
+    let needs_wordnet = matches.contains_id("theme")
+        || matches.contains_id("max-senses")
+        || matches.get_flag("concrete")
+        || matches.get_flag("explain")
+        || memorability_synsets(matches, sources);
+    let bundled = (bundled::AVAILABLE
+        && sources.dictionary == Dictionary::WordNet
+        && !force_download
+        && !needs_wordnet
+        && missing_word_lists(data_dir, &PartOfSpeech::ALL.into_iter().collect()).len()
+            == PartOfSpeech::ALL.len())
+    .then(bundled::word_lists)
+    .flatten();
+    let using_bundled = bundled.is_some();
+    let mut word_lists = match bundled {
+        Some(word_lists) => {
+            log::info!(
+                "Using the bundled word lists; run with --redownload to fetch the full lists"
+            );
+            word_lists
+        }
+        None if pools.is_empty() && !needs_wordnet => load_word_lists(data_dir, &pools, false),
+        None => load_or_generate_word_lists(
+            data_dir,
+            sources,
+            generation,
+            &pools,
+            force_download,
+            matches.get_flag("allow-proper-nouns"),
+            matches.get_flag("explain")
+                || matches.get_flag("concrete")
+                || memorability_synsets(matches, sources),
+        ),
+    };
+
+    let loaded = Stage::new(
+        if using_bundled {
+            "bundled word lists"
+        } else {
+            "word lists"
+        },
+        &word_lists,
+    );
+    let pack = pack.map(|pack| {
+        pack.apply(&mut word_lists);
+        Stage::new(format!("pack {}", pack.name), &word_lists)
+    });
+    let overlay = Overlay::load(&datadir::overlay_dir(&data_root(matches))).unwrap_or_else(|e| {
+        clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+    });
+    overlay.apply(&mut word_lists);
+    let overlay = Stage::new("overlay (words add/remove)", &word_lists);
+
+    let mut filters: Vec<Box<dyn WordFilter>> = Vec::new();
+    if let Some(theme) = matches.get_one::<String>("theme") {
+        let theme = Theme::from_name(theme).unwrap();
+        filters.push(Box::new(ThemeFilter::new(theme, &data_dir.join("dict"))));
+    }
+    if matches.get_flag("concrete") {
+        filters.push(Box::new(ConcreteFilter::new(&data_dir.join("dict"))));
+    }
+    let spelling = Spelling::from_name(matches.get_one::<String>("spelling").unwrap()).unwrap();
+    if spelling != Spelling::Either {
+        filters.push(Box::new(SpellingFilter::new(spelling)));
+    }
+    if let Some(ambiguity) = unambiguous {
+        filters.push(Box::new(ambiguity.clone()));
+    }
+    if let Some(common_passwords) = common_passwords {
+        filters.push(Box::new(common_passwords.clone()));
+    }
+    if let Some(layout) = matches.get_one::<String>("easy-to-type") {
+        let layout = Layout::from_name(layout).unwrap();
+        filters.push(Box::new(ErgonomicsFilter::new(layout)));
+    }
+    if let Some(&max_senses) = matches.get_one::<u32>("max-senses") {
+        filters.push(Box::new(SenseFilter::new(
+            max_senses,
+            &data_dir.join("dict"),
+        )));
+    }
+    for program in matches
+        .get_many::<PathBuf>("filter-script")
+        .into_iter()
+        .flatten()
+    {
+        let filter = ScriptFilter::new(program, &word_lists)
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        filters.push(Box::new(filter));
+    }
+    let filters = apply_filters(&mut word_lists, &filters);
+    let pipeline = Pipeline {
+        loaded,
+        pack,
+        overlay,
+        filters,
+    };
+    (word_lists, using_bundled, pipeline)
+}
+
+fn load_or_generate_word_lists(
+    data_dir: &Path,
+    sources: &Sources,
+    params: &cache::GenerationParams,
+    pools: &HashSet<PartOfSpeech>,
+    force_download: bool,
+    allow_proper_nouns: bool,
+    synsets: bool,
+) -> WordLists {
+    if !force_download {
+        if let Some(word_lists) = load_partial_word_lists(data_dir, pools, allow_proper_nouns) {
+            return word_lists;
+        }
+    }
+    let status = cache::check(data_dir, params);
+    if let Err(stale) = &status {
+        log::warn!("Rebuilding word list cache: {}", stale.reason);
+    }
+    let synsets_missing = synsets && !has_synset_data(data_dir);
+    if !word_lists_exist(data_dir) || force_download || synsets_missing || status.is_err() {
+        let redownload = force_download || status.is_err_and(|stale| stale.sources_damaged);
+        rebuild_cache(data_dir, sources, params, redownload, synsets);
+    }
+
+    if let Err(problem) = validate_word_lists(data_dir, pools) {
+        log::warn!("Regenerating word lists from cached sources: {}", problem);
+        rebuild_cache(data_dir, sources, params, false, synsets);
+        if let Err(problem) = validate_word_lists(data_dir, pools) {
+            exit::fail(
+                exit::CACHE_CORRUPTION,
+                format!(
+                    "{}\nThe cached sources look damaged; run again with --redownload to fetch fresh copies.",
+                    problem
+                ),
+            );
+        }
     }
 
-    let wordlists = load_all_word_lists(&data_dir);
-    wordlists
+    load_word_lists(data_dir, pools, allow_proper_nouns)
 }
 
-fn parse_arguments() -> clap::ArgMatches {
-    clap_command::new("PhraseForge")
-        .version("0.1.0")
-        .author("Chris Solomon <chris.m.solomon@gmail.com>")
-        .about("Generates memorable passphrases using WordNet word lists")
-        .arg(
-            Arg::new("count")
-                .short('c')
-                .long("count")
-                .help("Number of passphrases to generate")
-                .value_parser(clap::value_parser!(usize))
-                .default_value("1"),
-        )
-        .arg(
-            Arg::new("min-frequency")
-                .short('f')
-                .long("min-frequency")
-                .help("Minimum word frequency to include")
-                .value_parser(clap::value_parser!(u32))
-                .default_value("10000"),
-        )
-        .arg(
-            Arg::new("redownload")
-                .short('r')
-                .long("redownload")
-                .help("Force re-download of WordNet data")
-                .num_args(0),
-        )
-        .get_matches()
+/// Generates the word lists in `staging` and moves each into place, logging
+/// how each changed when `report` is set.
+fn install_word_lists(
+    data_dir: &Path,
+    sources: &Sources,
+    params: &cache::GenerationParams,
+    staging: &Path,
+    report: bool,
+) {
+    let frequency_list = data_dir.join(sources.frequency_list_file());
+    generate_word_lists(
+        data_dir,
+        &sources.dictionary,
+        &frequency_list,
+        params,
+        staging,
+    );
+    for file in GENERATED_FILES {
+        let installed = data_dir.join(file);
+        if report {
+            log::info!(
+                "{}",
+                update::describe_change(file, &installed, &staging.join(file))
+            );
+        }
+        update::rename(&staging.join(file), &installed).expect("Failed to install word list");
+    }
 }
 
-fn main() {
-    env_logger::init(); // Reads RUST_LOG from the environment
+/// Rebuilds the cache from its sources, downloading any that are missing (or
+/// all of them when `redownload` is set). The manifest is only written once
+/// everything is in place.
+fn rebuild_cache(
+    data_dir: &Path,
+    sources: &Sources,
+    params: &cache::GenerationParams,
+    redownload: bool,
+    synsets: bool,
+) {
+    datadir::ensure_writable(data_dir, "rebuild the word lists");
+    cache::invalidate(data_dir);
+    let staging = staging_dir(data_dir);
+
+    let frequency_list = data_dir.join(sources.frequency_list_file());
+    let dictionary = sources.dictionary;
+    let fetch_wordnet = (dictionary == Dictionary::WordNet || synsets)
+        && (redownload
+            || !data_dir.join("dict").join("index.noun").exists()
+            || (synsets && !has_synset_data(data_dir)));
+    let fetch_scowl =
+        dictionary.uses_scowl() && (redownload || !data_dir.join(scowl::SCOWL_DIR).exists());
+    let moby_pos = data_dir.join(moby::POS_FILE);
+    let mut downloads = Vec::new();
+    if fetch_wordnet {
+        downloads.push(Download {
+            name: "WordNet Dictionary",
+            url: sources.wordnet.clone(),
+            destination: Destination::Unpacked {
+                directory: staging.clone(),
+                keep: wordnet_files_wanted(data_dir, synsets),
+            },
+            sha256: None,
+        });
+    }
+    if fetch_scowl {
+        downloads.push(Download {
+            name: "SCOWL",
+            url: sources.scowl.clone(),
+            destination: Destination::Unpacked {
+                directory: staging.clone(),
+                keep: scowl::word_list_file,
+            },
+            sha256: None,
+        });
+    }
+    if dictionary.uses_moby() && (redownload || !moby_pos.exists()) {
+        downloads.push(Download {
+            name: "Moby POS list",
+            url: sources.moby_pos.clone(),
+            destination: Destination::File(moby_pos),
+            sha256: None,
+        });
+    }
+    if redownload || !frequency_list.exists() {
+        downloads.push(Download {
+            name: "Frequently used Word List",
+            url: sources.frequency_list.clone(),
+            destination: Destination::File(frequency_list),
+            sha256: None,
+        });
+    }
+    if !downloads.is_empty() {
+        let checksums = sources
+            .trusted_checksums()
+            .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+        for download in downloads.iter_mut() {
+            download.sha256 = checksums.as_ref().map(|checksums| {
+                checksums
+                    .expected(&download.url)
+                    .unwrap_or_else(|e| exit::fail(exit::NETWORK, e))
+                    .to_string()
+            });
+        }
+    }
+    download::download_all(&sources.async_client(), downloads)
+        .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+    if fetch_wordnet {
+        install_wordnet_dictionary(data_dir, &staging);
+    }
+    if fetch_scowl {
+        scowl::install(data_dir, &staging);
+    }
+    install_word_lists(data_dir, sources, params, &staging, false);
 
-    let matches = parse_arguments();
-    log::debug!("Command line arguments: {:?}", matches);
+    cache::write_manifest(data_dir, &sources.word_list_sources(), params);
+    fs::remove_dir_all(&staging).expect("Failed to remove staging directory");
+}
 
-    let num_passwords = *matches.get_one::<usize>("count").unwrap();
-    let force_download = matches.get_flag("redownload");
-    let min_frequency: u32 = *matches.get_one::<u32>("min-frequency").unwrap();
+/// `phraseforge words add|remove|list`: edits or shows the word overlay.
+fn manage_words(
+    matches: &clap::ArgMatches,
+    words_matches: &clap::ArgMatches,
+    sources: &Sources,
+    params: &cache::GenerationParams,
+) {
+    let root = datadir::overlay_dir(&data_root(matches));
+    let mut overlay = Overlay::load(&root).unwrap_or_else(|e| {
+        clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+    });
+    let (action, action_matches) = words_matches
+        .subcommand()
+        .expect("A words subcommand is required");
+    let pos = PartOfSpeech::from_name(action_matches.get_one::<String>("pos").unwrap()).unwrap();
+
+    if action == "list" {
+        let data_dir = word_list_dir(matches, params, false);
+        let bundled = (bundled::AVAILABLE
+            && sources.dictionary == Dictionary::WordNet
+            && !matches.get_flag("redownload")
+            && !word_lists_exist(&data_dir))
+        .then(bundled::word_lists)
+        .flatten();
+        let mut word_lists = bundled.unwrap_or_else(|| {
+            load_or_generate_word_lists(
+                &data_dir,
+                sources,
+                params,
+                &HashSet::from([pos]),
+                matches.get_flag("redownload"),
+                matches.get_flag("allow-proper-nouns"),
+                false,
+            )
+        });
+        overlay.apply(&mut word_lists);
+
+        let mut entries: Vec<&WordEntry> = word_lists.entries_of(pos).iter().collect();
+        entries.sort_by(|a, b| {
+            b.frequency
+                .cmp(&a.frequency)
+                .then_with(|| normalize::collate(&a.word, &b.word))
+        });
+        let top = action_matches
+            .get_one::<usize>("top")
+            .copied()
+            .unwrap_or(entries.len());
+        for entry in entries.iter().take(top) {
+            if overlay.is_added(pos, &entry.word) {
+                println!("{}\tadded", entry.word);
+            } else {
+                println!("{}\t{}", entry.word, entry.frequency);
+            }
+        }
+        return;
+    }
+
+    for word in action_matches.get_many::<String>("word").unwrap() {
+        let result = match action {
+            "add" => overlay.add(pos, word),
+            _ => overlay.remove(pos, word),
+        };
+        result.unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+        });
+    }
+    datadir::ensure_writable(&root, "save the word overlay");
+    overlay.save(&root);
+    log::info!("Updated {}", root.join(OVERLAY_FILE).display());
+}
+
+/// `phraseforge pack install|list|remove`: manages the installed word packs.
+fn manage_packs(matches: &clap::ArgMatches, pack_matches: &clap::ArgMatches, sources: &Sources) {
+    let root = datadir::overlay_dir(&data_root(matches));
+    match pack_matches.subcommand() {
+        Some(("install", install_matches)) => {
+            datadir::ensure_writable(&root, "install the pack");
+            let source = install_matches.get_one::<String>("source").unwrap();
+            let manifest = pack::install(source, &root, sources)
+                .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+            println!(
+                "Installed {} {} ({} lists); use it with --pack {}",
+                manifest.name,
+                manifest.version,
+                manifest.files.len(),
+                manifest.name
+            );
+        }
+        Some(("remove", remove_matches)) => {
+            let name = remove_matches.get_one::<String>("name").unwrap();
+            pack::remove(&root, name).unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+            log::info!("Removed pack {}", name);
+        }
+        _ => {
+            for manifest in pack::installed(&root) {
+                match manifest {
+                    Ok(manifest) => println!(
+                        "{:<16} {:<8} {:<40} {}",
+                        manifest.name,
+                        manifest.version,
+                        manifest.files.keys().cloned().collect::<Vec<_>>().join(" "),
+                        manifest.description
+                    ),
+                    Err(e) => log::warn!("{}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Implements `phraseforge update`: fetches whichever sources changed, then
+/// regenerates the word lists in a staging directory and moves each into
+/// place, so an interrupted update never leaves a half-written list.
+fn update_sources(data_dir: &Path, sources: &Sources, params: &cache::GenerationParams) {
+    datadir::ensure_writable(data_dir, "update the sources");
+    let mut validators = update::load_validators(data_dir);
+    cache::invalidate(data_dir);
+    let staging = staging_dir(data_dir);
+
+    let client = sources.client();
+    let checksums = sources
+        .trusted_checksums()
+        .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+    let mut targets = sources.word_list_sources();
+    if sources.dictionary != Dictionary::WordNet && data_dir.join("dict").exists() {
+        targets.push(("WordNet", sources.wordnet.as_str()));
+    }
+    if data_dir.join(diceware::EFF_LARGE_WORDLIST_FILE).exists() {
+        targets.push(("EFF word list", sources.eff_word_list.as_str()));
+    }
+    if data_dir.join(corpus::COMMON_PASSWORDS_FILE).exists() {
+        targets.push(("common password list", sources.common_passwords.as_str()));
+    }
+
+    let mut lists_stale = !word_lists_exist(data_dir);
+    for (name, url) in targets {
+        let fetched = update::fetch_if_changed(&client, url, validators.get(url))
+            .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+        let (body, fresh) = match fetched {
+            update::Fetched::Unchanged => {
+                log::info!("{}: unchanged", name);
+                continue;
+            }
+            update::Fetched::Changed { body, validators } => (body, validators),
+        };
+        if let Some(checksums) = &checksums {
+            checksums
+                .check(url, &body)
+                .unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+        }
+
+        match name {
+            "WordNet" => {
+                download::unpack(&body[..], &staging, wordnet_files_wanted(data_dir, false))
+                    .expect("Failed to extract WordNet");
+                install_wordnet_dictionary(data_dir, &staging);
+                lists_stale = true;
+            }
+            "SCOWL" => {
+                download::unpack(&body[..], &staging, scowl::word_list_file)
+                    .expect("Failed to extract SCOWL");
+                scowl::install(data_dir, &staging);
+                lists_stale = true;
+            }
+            "Moby POS list" => {
+                update::write_atomically(&data_dir.join(moby::POS_FILE), &body);
+                lists_stale = true;
+            }
+            "frequency list" => {
+                update::write_atomically(&data_dir.join(sources.frequency_list_file()), &body);
+                lists_stale = true;
+            }
+            "common password list" => {
+                update::write_atomically(&data_dir.join(corpus::COMMON_PASSWORDS_FILE), &body)
+            }
+            _ => update::write_atomically(&data_dir.join(diceware::EFF_LARGE_WORDLIST_FILE), &body),
+        }
+        log::info!("{}: updated", name);
+        validators.insert(url.to_string(), fresh);
+    }
+
+    if lists_stale {
+        install_word_lists(data_dir, sources, params, &staging, true);
+    } else {
+        log::info!("Word lists are up to date");
+    }
 
-    let data_dir = get_data_dir();
-    let word_lists = load_or_generate_word_lists(&data_dir, force_download);
+    update::save_validators(data_dir, &validators);
+    cache::write_manifest(data_dir, &sources.word_list_sources(), params);
+    fs::remove_dir_all(&staging).expect("Failed to remove staging directory");
+}
 
-    for _ in 0..num_passwords {
-        println!("{}", generate_password(&word_lists, &min_frequency));
+/// Prints, for `--dry-run`, how many words each pool the template draws
+/// from keeps after every stage of the filter pipeline, then the estimate
+/// those pools give.
+fn print_pipeline(pipeline: &Pipeline, word_lists: &WordLists, options: &GenerationOptions) {
+    let pools = template_pools(&options.template);
+    let columns: Vec<usize> = PartOfSpeech::ALL
+        .iter()
+        .enumerate()
+        .filter(|(_, pos)| pools.contains(pos))
+        .map(|(i, _)| i)
+        .collect();
+    let eligible = Stage {
+        name: format!("above --min-frequency {}", options.min_frequency),
+        counts: PartOfSpeech::ALL.map(|pos| {
+            count_eligible(
+                word_lists.entries_of(pos),
+                &options.min_frequency.of(pos),
+                None,
+            )
+        }),
+    };
+    let stages = [&pipeline.loaded]
+        .into_iter()
+        .chain(&pipeline.pack)
+        .chain([&pipeline.overlay])
+        .chain(&pipeline.filters)
+        .chain([&eligible]);
+    let mut header = format!("{:<32}", "stage");
+    for &i in &columns {
+        header.push_str(&format!(" {:>10}", PartOfSpeech::ALL[i].name()));
+    }
+    println!("{}", header);
+    for stage in stages {
+        let mut line = format!("{:<32}", stage.name);
+        for &i in &columns {
+            line.push_str(&format!(" {:>10}", stage.counts[i]));
+        }
+        println!("{}", line);
+    }
+    println!(
+        "estimated {:.1} bits per phrase",
+        entropy::estimate(word_lists, options).bits_per_phrase
+    );
+}
+
+/// Suggests `update` once the word lists are older than `cache_max_age`, or
+/// under `cache_auto_refresh` starts one in the background. Only the user's
+/// own cache is refreshed that way; a system-wide one is left to its owner.
+fn check_word_list_age(
+    matches: &clap::ArgMatches,
+    config: &Config,
+    data_dir: &Path,
+    sources: &Sources,
+) {
+    let Some(refreshed) = update::last_refreshed(data_dir, &sources.frequency_list_file()) else {
+        return;
+    };
+    let age = refreshed.elapsed().unwrap_or_default();
+    if age <= config.cache_max_age() {
+        return;
+    }
+    let days = age.as_secs() / SECONDS_PER_DAY;
+    if config.cache_auto_refresh && sandbox::root().is_none() && data_dir == get_data_dir(matches) {
+        match refresh_in_background(matches) {
+            Ok(()) => log::info!(
+                "The word lists are {} days old; refreshing them in the background",
+                days
+            ),
+            Err(e) => log::warn!("cannot start a background refresh: {}", e),
+        }
+    } else {
+        log::warn!(
+            "The word lists in {} are {} days old; run `phraseforge update` for current frequency data",
+            data_dir.display(),
+            days
+        );
+    }
+}
+
+/// Starts `phraseforge update` detached, with the flags that decide which
+/// cache it updates and where from.
+fn refresh_in_background(matches: &clap::ArgMatches) -> io::Result<()> {
+    let mut command = std::process::Command::new(std::env::current_exe()?);
+    command.args(["update", "--quiet"]);
+    for flag in [
+        "config",
+        "cache-dir",
+        "mirror",
+        "wordnet-url",
+        "scowl-url",
+        "moby-pos-url",
+        "dictionary",
+        "frequency-list-url",
+        "frequency-list",
+        "proxy",
+    ] {
+        if let Some(value) = matches.get_raw(flag).and_then(|mut values| values.next()) {
+            command.arg(format!("--{}", flag)).arg(value);
+        }
+    }
+    if matches.get_flag("assign-part-of-speech") {
+        command.arg("--assign-part-of-speech");
+    }
+    command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+/// For `--pick` and `--reroll`, the last session and the matches of its
+/// arguments followed by this run's, which take precedence; otherwise
+/// `matches` as they are.
+fn resume_session(matches: clap::ArgMatches) -> (clap::ArgMatches, Option<Session>) {
+    if !matches.contains_id("pick") && !matches.contains_id("reroll") {
+        return (matches, None);
+    }
+    let session = Session::load(&data_root(&matches)).unwrap_or_else(|e| {
+        clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+    });
+    let args = std::iter::once("phraseforge".to_string())
+        .chain(default_args(&matches))
+        .chain(session.args.iter().cloned())
+        .chain(std::env::args().skip(1));
+    let matches = cli().args_override_self(true).get_matches_from(args);
+    (matches, Some(session))
+}
+
+/// 1-based indices of the secrets to generate: `--count` of them, or without
+/// end under `--infinite`.
+fn indices(matches: &clap::ArgMatches) -> Box<dyn Iterator<Item = usize>> {
+    if matches.get_flag("infinite") {
+        Box::new(1..)
+    } else {
+        Box::new(1..=requested_count(matches))
+    }
+}
+
+/// How many secrets were asked for: `--series`' size, else `--count`.
+fn requested_count(matches: &clap::ArgMatches) -> usize {
+    matches
+        .get_one::<usize>("series")
+        .or_else(|| matches.get_one::<usize>("count"))
+        .copied()
+        .unwrap()
+}
+
+fn part_of_speech_arg() -> Arg {
+    Arg::new("pos")
+        .long("pos")
+        .help("Part of speech: adjective (adj), noun, verb or adverb (adv)")
+        .value_parser(PossibleValuesParser::new(PartOfSpeech::possible_values()))
+        .required(true)
+}
+
+/// The pool `encode` and `decode` draw their words from.
+fn codec_pos_arg() -> Arg {
+    part_of_speech_arg()
+        .help("Part of speech whose pool is the codebook: adjective (adj), noun, verb or adverb (adv)")
+        .required(false)
+        .default_value("noun")
+}
+
+/// `--secret-fd` and `--secret-env`, for reading `secret` without a
+/// terminal.
+fn secret_source_args(secret: &str) -> [Arg; 2] {
+    [
+        Arg::new("secret-fd")
+            .long("secret-fd")
+            .value_name("FD")
+            .help(format!(
+                "Read the {} from the first line of this open file descriptor instead of prompting",
+                secret
+            ))
+            .value_parser(clap::value_parser!(i32).range(0..))
+            .conflicts_with("secret-env"),
+        Arg::new("secret-env")
+            .long("secret-env")
+            .value_name("VAR")
+            .help(format!(
+                "Read the {} from this environment variable instead of prompting",
+                secret
+            )),
+    ]
+}
+
+/// `[defaults]` from the config file, as arguments to go before the command
+/// line's own. A broken config file is reported once `main` loads it.
+fn default_args(matches: &clap::ArgMatches) -> Vec<String> {
+    Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))
+        .map(|config| config.defaults.args())
+        .unwrap_or_default()
+}
+
+/// `matches` parsed again after the config file's `[defaults]`, which the
+/// command line's own arguments override.
+fn with_defaults(matches: clap::ArgMatches) -> clap::ArgMatches {
+    let defaults = default_args(&matches);
+    if defaults.is_empty() {
+        return matches;
+    }
+    let args = std::iter::once("phraseforge".to_string())
+        .chain(defaults)
+        .chain(std::env::args().skip(1));
+    cli().args_override_self(true).get_matches_from(args)
+}
+
+fn parse_arguments() -> clap::ArgMatches {
+    with_defaults(cli().get_matches())
+}
+
+fn cli() -> clap_command {
+    clap_command::new("PhraseForge")
+        .version("0.1.0")
+        .author("Chris Solomon <chris.m.solomon@gmail.com>")
+        .about("Generates memorable passphrases using WordNet word lists")
+        .after_help(exit::HELP)
+        .arg(
+            Arg::new("count")
+                .short('c')
+                .long("count")
+                .help("Number of passphrases to generate")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .global(true),
+        )
+        .arg(
+            Arg::new("infinite")
+                .long("infinite")
+                .help("Keep generating until the reader closes the pipe, e.g. `phraseforge --infinite --quiet | head -n 100000`; --quiet also skips the slow per-phrase zxcvbn warning")
+                .num_args(0)
+                .conflicts_with_all([
+                    "count",
+                    "keyring",
+                    "qr",
+                    "qr-png",
+                    "output",
+                    "log-history",
+                    "transcript",
+                ])
+                .global(true),
+        )
+        .arg(
+            Arg::new("series")
+                .long("series")
+                .help("Generate this many phrases sharing a template and number but not words, for setting several passwords at once; the shared number counts for no entropy, and each phrase is rated without the words the others took")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with_all(["count", "infinite"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("series-hint")
+                .long("series-hint")
+                .help("Print the structure a --series shares to stderr, as a reminder of what the phrases have in common")
+                .num_args(0)
+                .requires("series")
+                .global(true),
+        )
+        .arg(
+            Arg::new("session")
+                .long("session")
+                .help("Remember this run's flags and phrases, in the clear in a file only you can read, for --pick and --reroll to follow up on")
+                .num_args(0)
+                .conflicts_with_all(["infinite", "hash", "output"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("pick")
+                .long("pick")
+                .value_name("N")
+                .help("Print result N of the last --session run and forget the session")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with_all(["session", "reroll"])
+                .global(true),
+        )
+        .arg(
+            Arg::new("reroll")
+                .long("reroll")
+                .value_name("N")
+                .help("Replace result N of the last --session run with a new phrase drawn with that run's flags")
+                .value_parser(clap::value_parser!(usize))
+                .conflicts_with("session")
+                .global(true),
+        )
+        .arg(
+            Arg::new("slot")
+                .long("slot")
+                .value_name("M")
+                .help("With --reroll, redraw only the phrase's Mth word")
+                .value_parser(clap::value_parser!(usize))
+                .requires("reroll")
+                .global(true),
+        )
+        .arg(
+            Arg::new("labels")
+                .long("labels")
+                .help("Read account names from stdin, one per line, and print 'label<TAB>phrase' for each")
+                .num_args(0)
+                .conflicts_with_all([
+                    "count",
+                    "keyring",
+                    "qr",
+                    "qr-png",
+                    "spell",
+                    "explain",
+                    "typo-report",
+                    "abbrev",
+                    "annotate",
+                    "infinite",
+                    "series",
+                    "session",
+                    "remind-label",
+                ]),
+        )
+        .arg(
+            Arg::new("min-frequency")
+                .short('f')
+                .long("min-frequency")
+                .value_name("FREQ")
+                .help("Minimum word frequency to include, for every part of speech or per part of speech, e.g. adj=5000,noun=20000,adv=2000")
+                .value_parser(MinFrequency::parse)
+                .default_value("10000")
+                .global(true),
+        )
+        .arg(
+            Arg::new("cache-dir")
+                .long("cache-dir")
+                .value_name("DIR")
+                .help("Keep downloaded sources and word lists here instead of the data directory")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("sandbox")
+                .long("sandbox")
+                .value_name("DIR")
+                .help("Write nothing outside DIR, which holds the cache unless --cache-dir names a directory inside it, and never use the network")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("yes")
+                .short('y')
+                .long("yes")
+                .help("Skip the first-run setup and download the word lists right away")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("redownload")
+                .short('r')
+                .long("redownload")
+                .help("Force re-download of WordNet data")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("allow-proper-nouns")
+                .long("allow-proper-nouns")
+                .help("Include names and acronyms in the noun pool")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .help("Restrict nouns to a semantic category")
+                .value_parser(Theme::NAMES)
+                .global(true),
+        )
+        .arg(
+            Arg::new("pack")
+                .long("pack")
+                .value_name("NAME")
+                .help("Draw words from the installed word pack NAME for the parts of speech it has (see `pack list`)")
+                .global(true),
+        )
+        .arg(
+            Arg::new("concrete")
+                .long("concrete")
+                .help("Only use concrete, imageable nouns and adjectives")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("spelling")
+                .long("spelling")
+                .help("Keep only American (us) or British (uk) spellings of words like color/colour")
+                .value_parser(Spelling::NAMES)
+                .default_value("either")
+                .global(true),
+        )
+        .arg(
+            Arg::new("unambiguous")
+                .long("unambiguous")
+                .help("Avoid easily confused glyphs (rn/m, cl/d, 1/l, 0/O) in words and numbers, for phrases read off paper")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("easy-to-type")
+                .long("easy-to-type")
+                .value_name("LAYOUT")
+                .help("Prefer words that are comfortable to type: hands alternate, no same-finger stretches")
+                .value_parser(Layout::NAMES)
+                .num_args(0..=1)
+                .default_missing_value("qwerty")
+                .global(true),
+        )
+        .arg(
+            Arg::new("max-senses")
+                .long("max-senses")
+                .value_name("N")
+                .help("Only use words with at most N meanings in WordNet, across all parts of speech")
+                .value_parser(clap::value_parser!(u32).range(1..))
+                .global(true),
+        )
+        .arg(
+            Arg::new("filter-script")
+                .long("filter-script")
+                .value_name("PROGRAM")
+                .help("Screen words with this program: it reads '<part of speech><TAB><word>' lines on stdin and prints back those to keep; repeat to chain several")
+                .value_parser(clap::value_parser!(PathBuf))
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("alliterate")
+                .long("alliterate")
+                .help("Start every word in a phrase with the same letter")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("acrostic")
+                .long("acrostic")
+                .help("Make the initials of the word slots spell this text")
+                .value_parser(parse_acrostic)
+                .conflicts_with("alliterate"),
+        )
+        .arg(
+            Arg::new("caps-pattern")
+                .long("caps-pattern")
+                .value_name("PATTERN")
+                .help("Capitalize by word and letter: one group of x (lower), X (upper) or ? (random) per word slot, e.g. Xxxx-xxxx-XXXX, the last x or X covering the rest of the word; or random:N to capitalize N random words")
+                .value_parser(caps::CapsPattern::parse)
+                .conflicts_with("sentence"),
+        )
+        .arg(
+            Arg::new("rhyme")
+                .long("rhyme")
+                .help("Make the first two words of each phrase rhyme")
+                .num_args(0)
+                .conflicts_with_all(["alliterate", "acrostic"]),
+        )
+        .arg(
+            Arg::new("min-entropy")
+                .long("min-entropy")
+                .value_name("BITS")
+                .help("Refuse phrases whose estimated or zxcvbn-rated entropy is below BITS, explaining what to relax")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("unique")
+                .long("unique")
+                .help("Never print the same phrase twice in one batch")
+                .num_args(0)
+                .conflicts_with_all(["infinite", "labels"]),
+        )
+        .arg(
+            Arg::new("reject-common")
+                .long("reject-common")
+                .help("Download the 100,000 most common breached passwords; drop them from the pools of one-word templates and warn when a phrase is one")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("hibp")
+                .long("hibp")
+                .help("Reject phrases found in Have I Been Pwned's breach corpus; only 5 hex digits of each phrase's SHA-1 are sent")
+                .num_args(0)
+                .conflicts_with_all(["infinite", "labels"]),
+        )
+        .arg(
+            Arg::new("avoid-file")
+                .long("avoid-file")
+                .value_name("FILE")
+                .help("Regenerate any phrase found in this file of salted hashes of earlier phrases, and add the ones issued to it")
+                .value_parser(clap::value_parser!(PathBuf))
+                .conflicts_with_all(["infinite", "reroll"]),
+        )
+        .arg(
+            Arg::new("allow-repeats")
+                .long("allow-repeats")
+                .help("Allow a word to appear more than once in a phrase, e.g. in different roles")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("sort-by")
+                .long("sort-by")
+                .value_name("ORDER")
+                .help("List a batch most memorable first, by word frequency rank, length and concreteness; every phrase keeps the same entropy")
+                .value_parser(memorability::NAMES)
+                .conflicts_with_all(["infinite", "labels"]),
+        )
+        .arg(
+            Arg::new("distinct-sounds")
+                .long("distinct-sounds")
+                .help("Keep words that sound alike (the same Metaphone code), like bare and bear, out of the same phrase")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("allow-repeats"),
+        )
+        .arg(
+            Arg::new("explain")
+                .long("explain")
+                .help("Print a short WordNet definition of each word under the phrase")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .help("Run the word filters and print how many words each pool keeps after every stage, then exit without generating")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("annotate")
+                .long("annotate")
+                .help("Print each word's frequency rank in its pool and whether it is common, uncommon or rare, to tune --min-frequency by")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("abbrev")
+                .long("abbrev")
+                .help("Print each phrase's initials, e.g. 7bbbb, to keep as a written hint")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("split")
+                .long("split")
+                .value_name("K-of-N")
+                .help("Split each phrase into N word-encoded Shamir shares, any K of which rebuild it with `combine`; the shares are printed even when the phrase is not")
+                .value_parser(shamir::Split::parse)
+                .conflicts_with_all(["hash", "labels", "infinite"]),
+        )
+        .arg(
+            Arg::new("typo-report")
+                .long("typo-report")
+                .help("List words in each phrase that one typo turns into another word of the same pool")
+                .num_args(0),
+        )
+        .arg(
+            Arg::new("spell")
+                .long("spell")
+                .help("Print a NATO phonetic spelling of each phrase")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("hash")
+                .long("hash")
+                .help("Print a hash of each secret on stdout, moving the secret itself to stderr")
+                .value_parser(HashAlgorithm::NAMES)
+                .global(true),
+        )
+        .arg(
+            Arg::new("no-echo")
+                .long("no-echo")
+                .help("Never print generated secrets; hashes are still printed")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("qr")
+                .long("qr")
+                .help("Draw each secret as a QR code in the terminal")
+                .num_args(0)
+                .conflicts_with("no-echo")
+                .global(true),
+        )
+        .arg(
+            Arg::new("qr-png")
+                .long("qr-png")
+                .value_name("FILE")
+                .help("Write the secret as a QR code PNG image")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("FILE")
+                .help("Write secrets to FILE, encrypted with --encrypt-to or --age-recipient")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("encrypt-to")
+                .long("encrypt-to")
+                .value_name("KEYID")
+                .help("GnuPG recipient for --output (repeatable)")
+                .action(ArgAction::Append)
+                .requires("output")
+                .global(true),
+        )
+        .arg(
+            Arg::new("age-recipient")
+                .long("age-recipient")
+                .value_name("RECIPIENT")
+                .help("age recipient for --output (repeatable)")
+                .action(ArgAction::Append)
+                .requires("output")
+                .conflicts_with("encrypt-to")
+                .global(true),
+        )
+        .arg(
+            Arg::new("log-history")
+                .long("log-history")
+                .value_name("RECORD")
+                .help("Append generated secrets to the encrypted history configured in [history]: hashes (default) or phrases")
+                .value_parser(Record::NAMES)
+                .num_args(0..=1)
+                .global(true),
+        )
+        .arg(
+            Arg::new("remind")
+                .long("remind")
+                .value_name("PERIOD")
+                .help("Note the secret's label and a hash of it in the reminders file, due for rotation after this period, e.g. 90d, 12w, 6m or 1y")
+                .value_parser(reminders::parse_period)
+                .conflicts_with("infinite")
+                .global(true),
+        )
+        .arg(
+            Arg::new("remind-label")
+                .long("remind-label")
+                .value_name("NAME")
+                .help("Label for --remind; defaults to the --keyring name")
+                .requires("remind")
+                .global(true),
+        )
+        .arg(
+            Arg::new("remind-file")
+                .long("remind-file")
+                .value_name("FILE")
+                .help("Reminders file for --remind and `reminders` [default: reminders.jsonl in the data directory]")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("keyring")
+                .long("keyring")
+                .value_name("NAME")
+                .help("Also save the secret in the OS keyring under this name")
+                .global(true),
+        )
+        .arg(
+            Arg::new("leet")
+                .long("leet")
+                .help("Substitute digits and symbols for a random subset of letters")
+                .value_parser(LeetLevel::NAMES)
+                .num_args(0..=1)
+                .default_missing_value("light"),
+        )
+        .arg(
+            Arg::new("mutate")
+                .long("mutate")
+                .help("Insert, swap the case of, or substitute this many random characters")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Log each phrase's pools, their filtered sizes and per-slot entropy to stderr")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress status messages; only secrets and errors are printed")
+                .num_args(0)
+                .conflicts_with("verbose")
+                .global(true),
+        )
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Never color output (also honored: the NO_COLOR environment variable)")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("screen-reader")
+                .long("screen-reader")
+                .help("Lay output out for screen readers: each phrase also spoken in words, no color or columns, no redrawn countdowns")
+                .num_args(0)
+                .global(true),
+        )
+        .arg(
+            Arg::new("mirror")
+                .long("mirror")
+                .value_name("URL")
+                .help("Download every source from this mirror, which serves them under their upstream file names")
+                .global(true),
+        )
+        .arg(
+            Arg::new("wordnet-url")
+                .long("wordnet-url")
+                .value_name("URL")
+                .help("Download the WordNet archive from this URL")
+                .global(true),
+        )
+        .arg(
+            Arg::new("dictionary")
+                .long("dictionary")
+                .value_name("NAME")
+                .help("Dictionary to take parts of speech from: wordnet (default), moby, or scowl-<size> for SCOWL's words up to a size tier (10, 20, 35, 40, 50, 55, 60, 70, 80, 95) tagged by Moby")
+                .value_parser(|name: &str| Dictionary::from_name(name).map(|_| name.to_string()))
+                .global(true),
+        )
+        .arg(
+            Arg::new("scowl-url")
+                .long("scowl-url")
+                .value_name("URL")
+                .help("Download the SCOWL archive from this URL")
+                .global(true),
+        )
+        .arg(
+            Arg::new("moby-pos-url")
+                .long("moby-pos-url")
+                .value_name("URL")
+                .help("Download Moby's part-of-speech list from this URL")
+                .global(true),
+        )
+        .arg(
+            Arg::new("frequency-list-url")
+                .long("frequency-list-url")
+                .value_name("URL")
+                .help("Download the word frequency list from this URL")
+                .global(true),
+        )
+        .arg(
+            Arg::new("frequency-list")
+                .long("frequency-list")
+                .value_name("VARIANT")
+                .help("Frequency list to build the word lists from; en_50k is much smaller than en_full")
+                .value_parser(FREQUENCY_LISTS)
+                .global(true),
+        )
+        .arg(
+            Arg::new("assign-part-of-speech")
+                .long("assign-part-of-speech")
+                .help("When building the word lists, keep words like \"light\" only in the part of speech the dictionary sees them used as most")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("proxy")
+                .long("proxy")
+                .value_name("URL")
+                .help("Send downloads through this proxy (HTTPS_PROXY and friends are honored by default)")
+                .global(true),
+        )
+        .arg(
+            Arg::new("trust-key")
+                .long("trust-key")
+                .value_name("FILE")
+                .help("Verify downloads against the mirror's SHA256SUMS, whose detached SHA256SUMS.sig must be signed by this OpenPGP public key")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help("Read settings from this config file instead of the default location")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .arg(
+            Arg::new("policy")
+                .long("policy")
+                .help("Guarantee every phrase meets a password policy: nist (800-63B), ad-complex (Active Directory), pci (PCI DSS) or one named in the config file"),
+        )
+        .arg(
+            Arg::new("template")
+                .short('t')
+                .long("template")
+                .help("Phrase layout using {number}, {adjective}, {noun}, {verb[:form]} and {adverb} slots")
+                .value_parser(Template::parse)
+                .default_value(template::DEFAULT_TEMPLATE),
+        )
+        .arg(
+            Arg::new("sentence")
+                .long("sentence")
+                .help("Generate grammatical sentences, e.g. \"The 42 hungry otters quietly devour pancakes.\"")
+                .num_args(0)
+                .conflicts_with_all(["template", "preset", "separator", "leet", "verb-form"]),
+        )
+        .arg(
+            Arg::new("preset")
+                .long("preset")
+                .value_name("NAME")
+                .help("Use a named template: classic, short, sentence, noun-pair, xkcd4 or one from the config file (see `templates list`)")
+                .conflicts_with("template"),
+        )
+        .arg(
+            Arg::new("separator")
+                .short('s')
+                .long("separator")
+                .help("Text placed between slots instead of '-', any Unicode including emoji (e.g. 🦊)")
+                .value_parser(parse_separator)
+                .conflicts_with("template"),
+        )
+        .arg(
+            Arg::new("verb-form")
+                .long("verb-form")
+                .help("Form used for {verb} slots without an explicit modifier")
+                .value_parser(VerbForm::NAMES)
+                .default_value("base"),
+        )
+        .arg(
+            Arg::new("audit-json")
+                .long("audit-json")
+                .value_name("FILE")
+                .help("Append a JSON line per generated phrase (time, label, template, entropy, checks passed; never the phrase) to this file, for SIEM ingestion")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("transcript")
+                .long("transcript")
+                .help("Write a JSON transcript of the run (configuration, source hashes, entropy, timing) to this file")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true),
+        )
+        .subcommand(
+            clap_command::new("random")
+                .about("Generates classic random-character passwords")
+                .arg(
+                    Arg::new("length")
+                        .short('l')
+                        .long("length")
+                        .help("Number of characters in each password")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20"),
+                )
+                .arg(
+                    Arg::new("charset")
+                        .long("charset")
+                        .help("Character classes to draw from, joined with '+' (lower, upper, alpha, digits, alnum, symbols, hex)")
+                        .value_parser(Charset::parse)
+                        .default_value("alnum+symbols"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("pin")
+                .about("Generates numeric PINs, rejecting trivially weak ones")
+                .arg(
+                    Arg::new("digits")
+                        .short('d')
+                        .long("digits")
+                        .help("Number of digits in each PIN")
+                        .value_parser(clap::value_parser!(u32).range(4..=12))
+                        .default_value("6"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("dice")
+                .about("Maps physical dice rolls read from stdin to EFF Diceware words, one phrase per line")
+                .arg(
+                    Arg::new("separator")
+                        .short('s')
+                        .long("separator")
+                        .help("Text placed between words, any Unicode including emoji")
+                        .value_parser(parse_separator)
+                        .default_value("-"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("derive")
+                .about("Derives a repeatable passphrase for a site from a master secret")
+                .arg(
+                    Arg::new("site")
+                        .long("site")
+                        .help("Site the phrase is for, e.g. github.com (case-insensitive)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("words")
+                        .short('w')
+                        .long("words")
+                        .help("Number of words in the phrase")
+                        .value_parser(clap::value_parser!(u32).range(1..=20))
+                        .default_value("6"),
+                )
+                .arg(
+                    Arg::new("counter")
+                        .long("counter")
+                        .help("Increment to get a new phrase for the same site after a rotation")
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::new("separator")
+                        .short('s')
+                        .long("separator")
+                        .help("Text placed between words, any Unicode including emoji")
+                        .value_parser(parse_separator)
+                        .default_value("-"),
+                )
+                .args(secret_source_args("master secret")),
+        )
+        .subcommand(
+            clap_command::new("update")
+                .about("Re-downloads sources that changed upstream and regenerates the word lists"),
+        )
+        .subcommand(
+            clap_command::new("bootstrap")
+                .about("Builds the word lists offline from pre-fetched sources, reporting each step as JSON; for Dockerfiles and CI")
+                .arg(
+                    Arg::new("from-dir")
+                        .long("from-dir")
+                        .value_name("DIR")
+                        .help("Directory holding the sources under the file names a --mirror serves, optionally with a SHA256SUMS file")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap_command::new("recall")
+                .about("Prints a secret previously saved with --keyring")
+                .arg(
+                    Arg::new("name")
+                        .help("Name the secret was saved under")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            clap_command::new("combine")
+                .about("Rebuilds a phrase split with --split from enough of its shares; prompts for each, or reads one per line from a pipe"),
+        )
+        .subcommand(
+            clap_command::new("words")
+                .about("Adds favorite words to, or removes disliked ones from, the generated lists")
+                .subcommand_required(true)
+                .subcommand(
+                    clap_command::new("add")
+                        .about("Adds words; they are always eligible, whatever --min-frequency says")
+                        .arg(part_of_speech_arg())
+                        .arg(Arg::new("word").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    clap_command::new("remove")
+                        .about("Removes words, now and after any re-download")
+                        .arg(part_of_speech_arg())
+                        .arg(Arg::new("word").required(true).num_args(1..)),
+                )
+                .subcommand(
+                    clap_command::new("list")
+                        .about("Lists a part of speech's words, most frequent first")
+                        .arg(part_of_speech_arg())
+                        .arg(
+                            Arg::new("top")
+                                .long("top")
+                                .value_name("N")
+                                .help("Only list the N most frequent words")
+                                .value_parser(clap::value_parser!(usize)),
+                        ),
+                ),
+        )
+        .subcommand(
+            clap_command::new("pack")
+                .about("Installs, lists or removes word packs, themed or language word lists others curate")
+                .subcommand_required(true)
+                .subcommand(
+                    clap_command::new("install")
+                        .about("Installs a pack from a URL, a directory or a .tar.gz, after checking it against its pack.toml")
+                        .arg(Arg::new("source").value_name("URL|PATH").required(true)),
+                )
+                .subcommand(clap_command::new("list").about("Lists the installed packs"))
+                .subcommand(
+                    clap_command::new("remove")
+                        .about("Removes an installed pack")
+                        .arg(Arg::new("name").value_name("NAME").required(true)),
+                ),
+        )
+        .subcommand(
+            clap_command::new("templates")
+                .about("Shows the named templates available to --preset")
+                .subcommand_required(true)
+                .subcommand(
+                    clap_command::new("list")
+                        .about("Lists the built-in presets and those in the config file's [templates]"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("export")
+                .about("Dumps the exact word pools phrases would be drawn from under the current filters, for review")
+                .arg(
+                    Arg::new("pos")
+                        .long("pos")
+                        .help("Part of speech to export, or all")
+                        .value_parser(PossibleValuesParser::new(
+                            std::iter::once(PossibleValue::new("all"))
+                                .chain(PartOfSpeech::possible_values()),
+                        ))
+                        .default_value("all"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("txt: 'word frequency' lines under a comment per pool; json: one document")
+                        .value_parser(export::Format::NAMES)
+                        .default_value("txt"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("encode")
+                .about("Writes bytes, e.g. a key or API token, as words from one pool under the current filters; decode needs the same settings")
+                .arg(
+                    Arg::new("bytes")
+                        .long("bytes")
+                        .value_name("HEX")
+                        .help("Bytes to encode, in hex; prompted for without echo if omitted, or read from a pipe"),
+                )
+                .arg(codec_pos_arg()),
+        )
+        .subcommand(
+            clap_command::new("decode")
+                .about("Turns words written by encode back into hex bytes")
+                .arg(
+                    Arg::new("words")
+                        .help("Words to decode; prompted for without echo if omitted, or read from a pipe"),
+                )
+                .arg(codec_pos_arg()),
+        )
+        .subcommand(
+            clap_command::new("explore")
+                .about("Tabulates entropy against length for three- and four-word templates, with and without the number, at several --min-frequency cutoffs")
+                .arg(
+                    Arg::new("max-length")
+                        .long("max-length")
+                        .value_name("CHARS")
+                        .help("Mark the rows whose longest phrase fits a site's length limit (and meets --min-entropy, if given)")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            clap_command::new("selftest")
+                .about("Checks offline that a large sample of phrases is drawn uniformly from the filtered pools, printing a pass/fail report")
+                .arg(
+                    Arg::new("samples")
+                        .long("samples")
+                        .value_name("N")
+                        .help("Phrases to generate and check")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("20000"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("similar")
+                .about("Prints variations on a phrase, each swapping a word or two; give --template before the subcommand if it was custom")
+                .arg(
+                    Arg::new("phrase")
+                        .help("Phrase to vary; prompted for without echo if omitted, keeping it out of shell history"),
+                )
+                .arg(
+                    Arg::new("swap")
+                        .long("swap")
+                        .value_name("N")
+                        .help("Number of words each variation replaces")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("practice")
+                .about("Generates a phrase, then has you retype it from memory at growing intervals until it sticks")
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .value_name("N")
+                        .help("Number of recalls")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("4"),
+                )
+                .arg(
+                    Arg::new("delay")
+                        .long("delay")
+                        .value_name("SECONDS")
+                        .help("Wait before the first recall; doubles after each correct one")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("5"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("web")
+                .about("Shows phrases on a page on localhost, with buttons to copy, reroll or pick one; prints the pick")
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .help("Shut down after the first pick")
+                        .num_args(0),
+                )
+                .arg(
+                    Arg::new("timeout")
+                        .long("timeout")
+                        .value_name("SECONDS")
+                        .help("Shut down after this long, picked or not")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("600"),
+                )
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("PORT")
+                        .help("Port on 127.0.0.1 to listen on; any free one by default")
+                        .value_parser(clap::value_parser!(u16))
+                        .default_value("0"),
+                ),
+        )
+        .subcommand(
+            clap_command::new("doctor")
+                .about("Checks the data directory, cache, word lists, config and network, suggesting fixes")
+                .arg(
+                    Arg::new("offline")
+                        .long("offline")
+                        .help("Skip the network reachability checks")
+                        .num_args(0),
+                ),
+        )
+        .subcommand(
+            clap_command::new("check")
+                .about("Rates existing passphrases with zxcvbn; prompts, or reads one per line from a pipe"),
+        )
+        .subcommand(
+            clap_command::new("history")
+                .about("Checks whether a phrase was generated with --log-history, and when")
+                .arg(
+                    Arg::new("identity")
+                        .short('i')
+                        .long("identity")
+                        .value_name("FILE")
+                        .help("age identity file, for a history encrypted to age recipients")
+                        .value_parser(clap::value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            clap_command::new("reminders")
+                .about("Lists the credentials noted with --remind that are due for rotation")
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .help("Also list the credentials not due yet")
+                        .num_args(0),
+                ),
+        )
+        .subcommand(
+            clap_command::new("store")
+                .about("Generates one phrase and stores it in a password manager without printing it")
+                .arg(
+                    Arg::new("backend")
+                        .long("backend")
+                        .help("Password manager to store the phrase in")
+                        .value_parser(Backend::NAMES)
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("entry")
+                        .long("entry")
+                        .help("Entry path, e.g. sites/example")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("database")
+                        .long("database")
+                        .help("KeePassXC database file")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required_if_eq("backend", "keepassxc"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .long("force")
+                        .help("Overwrite the entry if it already exists")
+                        .num_args(0),
+                )
+                .args(secret_source_args("KeePassXC database password")),
+        )
+        .subcommand(
+            clap_command::new("capabilities")
+                .about("Lists the templates, dictionaries, features and cached word lists (with checksums) this binary supports, for provisioning checks")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("txt: one capability per line; json: one document")
+                        .value_parser(export::Format::NAMES)
+                        .default_value("txt"),
+                ),
+        )
+}
+
+/// Under `--sandbox`, refuses anything that would write outside the sandbox
+/// directory, before a phrase is generated.
+fn check_sandbox(matches: &clap::ArgMatches, config: &Config) {
+    let unconfined = [
+        ("--keyring", matches.contains_id("keyring")),
+        ("--filter-script", matches.contains_id("filter-script")),
+        ("store", matches.subcommand_name() == Some("store")),
+        ("web", matches.subcommand_name() == Some("web")),
+    ];
+    if let Some((name, _)) = unconfined.iter().find(|(_, set)| *set) {
+        clap::Error::raw(
+            clap::error::ErrorKind::ArgumentConflict,
+            format!(
+                "{} hands the secret to another program, which --sandbox cannot confine\n",
+                name
+            ),
+        )
+        .exit();
+    }
+
+    let mut writes = vec![("the cache".to_string(), data_root(matches))];
+    for flag in ["output", "qr-png", "transcript", "audit-json", "avoid-file"] {
+        if let Some(path) = matches.get_one::<PathBuf>(flag) {
+            writes.push((format!("--{}", flag), path.clone()));
+        }
+    }
+    if matches.contains_id("log-history") {
+        if let Ok(path) = config.history_path() {
+            writes.push(("the history".to_string(), path));
+        }
+        if let Some(path) = config.avoid_file() {
+            writes.push(("[history] avoid_file".to_string(), path));
+        }
+    }
+    if matches.contains_id("remind") {
+        writes.push(("the reminders file".to_string(), reminders_path(matches)));
+    }
+    for (what, path) in writes {
+        sandbox::check_write(&what, &path).unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+        });
+    }
+}
+
+/// The reminders file: `--remind-file` if given, else the default.
+fn reminders_path(matches: &clap::ArgMatches) -> PathBuf {
+    matches
+        .get_one::<PathBuf>("remind-file")
+        .cloned()
+        .or_else(reminders::default_path)
+        .unwrap_or_else(|| {
+            clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                "no default location for reminders; pass --remind-file\n",
+            )
+            .exit()
+        })
+}
+
+/// Logs which pools a phrase drew from and what each slot contributed,
+/// without the words themselves.
+fn log_phrase_breakdown(
+    index: usize,
+    phrase: &GeneratedPhrase,
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+) {
+    let letter = options
+        .alliterate
+        .then(|| {
+            phrase
+                .words
+                .iter()
+                .find(|(slot, _)| *slot != Slot::Number)
+                .and_then(|(_, word)| normalize::initial(word))
+        })
+        .flatten();
+    let slots = entropy::template_slots(word_lists, options, letter);
+    let breakdown: Vec<String> = slots
+        .iter()
+        .map(|s| format!("{} {} choices ({:.1} bits)", s.slot, s.pool_size, s.bits))
+        .collect();
+    log::debug!("Phrase {}: {}", index, breakdown.join(", "));
+}
+
+/// Looks up `--preset` among the built-in templates, then the config file's.
+fn resolve_preset(name: &str, config: &Config) -> Template {
+    if let Some(template) = Template::preset(name) {
+        return template;
+    }
+    match config.template(name) {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            let known: Vec<&str> = template::PRESETS
+                .iter()
+                .map(|(name, _)| *name)
+                .chain(
+                    config
+                        .templates
+                        .keys()
+                        .map(String::as_str)
+                        .filter(|name| Template::preset(name).is_none()),
+                )
+                .collect();
+            clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                format!(
+                    "unknown preset '{}' (expected one of: {})\n",
+                    name,
+                    known.join(", ")
+                ),
+            )
+            .exit()
+        }
+        Err(e) => clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit(),
+    }
+}
+
+/// Looks up `--policy` among the presets, then the config file's policies.
+fn resolve_policy(name: &str, config: &Config) -> Policy {
+    if let Some(policy) = Policy::preset(name) {
+        return policy;
+    }
+    match config.policy(name) {
+        Ok(Some(policy)) => policy,
+        Ok(None) => {
+            let known: Vec<&str> = Policy::PRESET_NAMES
+                .into_iter()
+                .chain(config.policy.keys().map(String::as_str))
+                .collect();
+            clap::Error::raw(
+                clap::error::ErrorKind::InvalidValue,
+                format!(
+                    "unknown policy '{}' (expected one of: {})\n",
+                    name,
+                    known.join(", ")
+                ),
+            )
+            .exit()
+        }
+        Err(e) => clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit(),
+    }
+}
+
+/// How each generated secret is written out.
+struct OutputOptions {
+    /// False under `--no-echo`: the secret itself is never written anywhere.
+    echo: bool,
+    spell_out: bool,
+    /// Set by `--screen-reader`: each secret is followed by its spoken form.
+    screen_reader: bool,
+    hash: Option<HashAlgorithm>,
+    /// Keyring entry name to save the secret under.
+    keyring: Option<String>,
+    qr: bool,
+    qr_png: Option<PathBuf>,
+    /// Set by `--output`: stdout lines are collected and encrypted instead.
+    encrypted: Option<EncryptedOutput>,
+    /// Set by `--log-history`: every secret is also recorded here.
+    history: Option<HistoryLog>,
+    /// Set by `--remind`: every labeled secret is noted for rotation here.
+    reminders: Option<ReminderLog>,
+    /// Set by `--audit-json` once the run's estimate is known.
+    audit: Option<AuditLog>,
+    /// Buffered so that huge batches aren't a write per line; see `flush`.
+    stdout: BufWriter<io::Stdout>,
+}
+
+/// Exits quietly if stdout was closed, which for `phraseforge | head -1` just
+/// means nobody wants more phrases.
+fn check_stdout(result: io::Result<()>) {
+    if let Err(e) = result {
+        if e.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+        panic!("Failed to write to stdout: {}", e);
+    }
+}
+
+impl OutputOptions {
+    /// Writes one line of what would normally go to stdout.
+    fn emit(&mut self, line: &str) {
+        match &mut self.encrypted {
+            Some(encrypted) => encrypted.push_line(line),
+            None => check_stdout(writeln!(self.stdout, "{}", line)),
+        }
+    }
+
+    /// Pushes out buffered lines, for output a reader is waiting on line by
+    /// line.
+    fn flush(&mut self) {
+        check_stdout(self.stdout.flush());
+    }
+
+    /// Flushes stdout and anything held back for encryption; call once before
+    /// exiting.
+    fn finish(mut self) {
+        self.flush();
+        if let Some(history) = &self.history {
+            history.append().unwrap_or_else(|e| {
+                eprintln!("error: failed to log history: {}", e);
+                std::process::exit(1);
+            });
+            log::info!("Logged to history at {}", history.path.display());
+        }
+        if let Some(audit) = &mut self.audit {
+            audit.flush().unwrap_or_else(|e| {
+                eprintln!("error: failed to write {}: {}", audit.path.display(), e);
+                std::process::exit(1);
+            });
+        }
+        if let Some(reminders) = &self.reminders {
+            reminders.append().unwrap_or_else(|e| {
+                eprintln!("error: failed to note reminders: {}", e);
+                std::process::exit(1);
+            });
+            log::info!("Noted for rotation in {}", reminders.path.display());
+        }
+        if let Some(encrypted) = self.encrypted {
+            encrypted.write().unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            });
+            log::info!("Wrote encrypted output to {}", encrypted.path.display());
+        }
+    }
+}
+
+/// Logs that zxcvbn found a phrase weaker than the combinatorial estimate,
+/// identifying it by `which` (its number or label) rather than its text.
+fn warn_weaker_than_estimate(which: &str, bits: f64, weakness: &str) {
+    log::warn!(
+        "Phrase {} is only {:.1} bits by zxcvbn: {}",
+        which,
+        bits,
+        weakness
+    );
+}
+
+/// Warns when a phrase is itself on the `--reject-common` list, naming it by
+/// `which` rather than printing it.
+fn warn_if_common(which: &str, phrase: &str, common_passwords: Option<&CommonPasswords>) {
+    if common_passwords.is_some_and(|common| common.contains(phrase)) {
+        log::warn!(
+            "Phrase {} is on the common password list; generate another",
+            which
+        );
+    }
+}
+
+/// Reads labels from stdin and prints `label<TAB>phrase` for each as soon as
+/// it is generated, so provisioning scripts can stream through thousands of
+/// accounts. Blank lines are skipped. Returns how many phrases were printed.
+///
+/// With `avoid`, phrases issued before are regenerated, and each phrase is
+/// added to the file as soon as it is printed.
+fn print_labeled_phrases(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    estimated_bits: f64,
+    common_passwords: Option<&CommonPasswords>,
+    mut avoid: Option<&mut AvoidList>,
+    output: &mut OutputOptions,
+) -> usize {
+    let mut count = 0;
+    for line in io::stdin().lock().lines() {
+        let line = line.unwrap_or_else(|e| {
+            eprintln!("error: cannot read labels from stdin: {}", e);
+            std::process::exit(1);
+        });
+        let label = line.trim();
+        if label.is_empty() {
+            continue;
+        }
+        if label.contains('\t') {
+            eprintln!("error: label '{}' contains a tab", label.escape_default());
+            std::process::exit(1);
+        }
+
+        let mut futile = 0;
+        let phrase = loop {
+            let phrase = generate_compliant_password(word_lists, options).text;
+            let Some(avoid) = &mut avoid else {
+                break phrase;
+            };
+            let hash = avoid.hash(&phrase);
+            if avoid.insert(hash) {
+                break phrase;
+            }
+            futile += 1;
+            if futile == MAX_FUTILE_ROUNDS {
+                exit::fail(
+                    exit::UNSATISFIABLE,
+                    format!(
+                        "every phrase generated for '{}' had been issued before. Lower --min-frequency or use a longer template.",
+                        label
+                    ),
+                );
+            }
+        };
+        if log::log_enabled!(log::Level::Warn) {
+            if let (bits, Some(weakness)) = strength::weaker(estimated_bits, &phrase) {
+                warn_weaker_than_estimate(label, bits, &weakness);
+            }
+        }
+        warn_if_common(label, &phrase, common_passwords);
+        if let Some(history) = &mut output.history {
+            history.push(Some(label), &phrase);
+        }
+        if let Some(reminders) = &mut output.reminders {
+            reminders.push(Some(label), &phrase);
+        }
+        if let Some(audit) = &mut output.audit {
+            audit.push(Some(label)).unwrap_or_else(|e| {
+                eprintln!("error: failed to write {}: {}", audit.path.display(), e);
+                std::process::exit(1);
+            });
+        }
+        match output.hash {
+            Some(algorithm) => {
+                let hash = algorithm.hash(&phrase).unwrap_or_else(|e| {
+                    clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e))
+                        .exit()
+                });
+                if output.echo {
+                    eprintln!("{}\t{}", label, phrase);
+                }
+                output.emit(&format!("{}\t{}", label, hash));
+            }
+            None if output.echo => output.emit(&format!("{}\t{}", label, phrase)),
+            None => {}
+        }
+        output.flush();
+        if let Some(avoid) = &mut avoid {
+            avoid
+                .save()
+                .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        }
+        count += 1;
+    }
+    count
+}
+
+/// `given` if set, else what is typed without echo at `prompt`, else all of
+/// stdin.
+fn argument_or_input(given: Option<&String>, prompt: &str) -> String {
+    if let Some(given) = given {
+        return given.clone();
+    }
+    let read = if io::stdin().is_terminal() {
+        secret::read(&secret::Source::Prompt, prompt)
+    } else {
+        io::read_to_string(io::stdin()).map_err(|e| format!("cannot read stdin: {}", e))
+    };
+    read.unwrap_or_else(|e| exit::fail(exit::FAILURE, e))
+}
+
+/// Reads shares for `combine` until there are enough to rebuild the phrase:
+/// prompted for without echo at a terminal, else one per line from stdin.
+fn combine_shares() -> Result<String, String> {
+    let mut shares: Vec<shamir::Share> = Vec::new();
+    if io::stdin().is_terminal() {
+        loop {
+            let prompt = match shares.first() {
+                Some(first) => format!("Share {} of {}: ", shares.len() + 1, first.threshold),
+                None => "Share 1: ".to_string(),
+            };
+            let text = secret::read(&secret::Source::Prompt, &prompt)?;
+            match shamir::decode(&text) {
+                Ok(share) => shares.push(share),
+                Err(e) => {
+                    eprintln!("{}; enter that share again", e);
+                    continue;
+                }
+            }
+            if shares.len() >= shares[0].threshold as usize {
+                break;
+            }
+        }
+    } else {
+        for (number, line) in io::stdin().lock().lines().enumerate() {
+            let line = line.map_err(|e| format!("cannot read shares from stdin: {}", e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let share = shamir::decode(&line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            shares.push(share);
+        }
+    }
+    String::from_utf8(shamir::combine(&shares)?).map_err(|_| {
+        "the shares rebuild something that is not text; they may come from different splits"
+            .to_string()
+    })
+}
+
+/// Prints one generated secret along with any requested annotations. When
+/// hashing, only the hash goes to stdout so it can be piped into another tool;
+/// the secret is still shown on stderr for the person running the command.
+fn print_secret(text: &str, output: &mut OutputOptions) {
+    print_secret_as(text, text, output);
+}
+
+/// Like `print_secret`, but echoes `display` (e.g. a colored rendering)
+/// instead of the bare secret.
+fn print_secret_as(text: &str, display: &str, output: &mut OutputOptions) {
+    if let Some(history) = &mut output.history {
+        history.push(None, text);
+    }
+    if let Some(reminders) = &mut output.reminders {
+        reminders.push(None, text);
+    }
+    if let Some(audit) = &mut output.audit {
+        let label = output
+            .reminders
+            .as_ref()
+            .and_then(|reminders| reminders.label.as_deref())
+            .or(output.keyring.as_deref());
+        audit.push(label).unwrap_or_else(|e| {
+            eprintln!("error: failed to write {}: {}", audit.path.display(), e);
+            std::process::exit(1);
+        });
+    }
+    if let Some(label) = &output.keyring {
+        keychain::save(label, text).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        });
+    }
+
+    let mut lines = Vec::new();
+    if output.echo {
+        lines.push(display.to_string());
+    }
+    if output.echo && output.screen_reader {
+        lines.push(format!("    {}", speech::spoken(text)));
+    }
+    if output.echo && output.spell_out {
+        lines.push(format!("    {}", spell::spell(text)));
+    }
+    if output.echo && output.qr {
+        lines.push(qr::render_terminal(text));
+    }
+    // Saving to a file the user named is not echoing, so `--no-echo` allows it.
+    if let Some(path) = &output.qr_png {
+        qr::save_png(text, path);
+    }
+
+    match output.hash {
+        Some(algorithm) => {
+            let hash = algorithm.hash(text).unwrap_or_else(|e| {
+                clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+            });
+            for line in lines {
+                eprintln!("{}", line);
+            }
+            output.emit(&hash);
+        }
+        None => {
+            for line in lines {
+                output.emit(&line);
+            }
+        }
+    }
+}
+
+fn main() {
+    let started = Instant::now();
+    let (matches, session) = resume_session(parse_arguments());
+    // Status messages go to stderr so stdout carries only secrets. RUST_LOG,
+    // when set, overrides the level chosen by --verbose or --quiet.
+    let level = if matches.get_flag("verbose") {
+        "debug"
+    } else if matches.get_flag("quiet") {
+        "error"
+    } else {
+        "info"
+    };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level))
+        .format_timestamp(None)
+        .format_target(false)
+        .init();
+    log::trace!("Command line arguments: {:?}", matches);
+    if let Some(dir) = matches.get_one::<PathBuf>("sandbox") {
+        sandbox::enter(dir).unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+    }
+
+    // Before the config file is loaded, so a broken one is diagnosed rather
+    // than fatal.
+    if let Some(("doctor", doctor_matches)) = matches.subcommand() {
+        let healthy = doctor::run(
+            &matches,
+            &data_root(&matches),
+            !doctor_matches.get_flag("offline") && sandbox::root().is_none(),
+        );
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+
+    let num_passwords = requested_count(&matches);
+    let config = Config::load(matches.get_one::<PathBuf>("config").map(PathBuf::as_path))
+        .unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+        });
+    let sources = Sources::new(&matches, &config.sources).unwrap_or_else(|e| {
+        clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+    });
+    // Before anything is downloaded, and picking up the defaults it saves.
+    let (matches, first_run) = if setup::is_first_run(&matches) {
+        let choice = setup::run(&sources).unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        (with_defaults(matches), Some(choice))
+    } else {
+        (matches, None)
+    };
+    if let Some(setup::Choice::Later) = first_run {
+        eprintln!("Run phraseforge again to download the word lists, or import them with `phraseforge bootstrap --from-dir DIR`.");
+        return;
+    }
+    if sandbox::root().is_some() {
+        check_sandbox(&matches, &config);
+    }
+    let generation = generation_params(&sources, matches.get_flag("assign-part-of-speech"));
+    if let Some(setup::Choice::Import(seed)) = &first_run {
+        let report = bootstrap::run(seed, &get_data_dir(&matches), &sources, &generation);
+        for diagnostic in report
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| !diagnostic.ok)
+        {
+            eprintln!("{}: {}", diagnostic.check, diagnostic.detail);
+        }
+        if !report.ok {
+            exit::fail(
+                exit::FAILURE,
+                format!(
+                    "cannot import the word lists from {}; fix the above and run `phraseforge bootstrap --from-dir {}`",
+                    seed.display(),
+                    seed.display()
+                ),
+            );
+        }
+    }
+    if sources.dictionary != Dictionary::WordNet {
+        let synset_flags = [
+            ("--theme", matches.contains_id("theme")),
+            ("--max-senses", matches.contains_id("max-senses")),
+            ("--concrete", matches.get_flag("concrete")),
+            ("--explain", matches.get_flag("explain")),
+        ];
+        if let Some((flag, _)) = synset_flags.iter().find(|(_, set)| *set) {
+            clap::Error::raw(
+                clap::error::ErrorKind::ArgumentConflict,
+                format!(
+                    "{} reads WordNet's synsets; it needs --dictionary wordnet, not {}\n",
+                    flag,
+                    sources.dictionary.name()
+                ),
+            )
+            .exit();
+        }
+    }
+    let store_target = matches
+        .subcommand_matches("store")
+        .map(|store_matches| StoreTarget {
+            backend: Backend::from_name(store_matches.get_one::<String>("backend").unwrap())
+                .unwrap(),
+            entry: store_matches.get_one::<String>("entry").unwrap().clone(),
+            database: store_matches.get_one::<PathBuf>("database").cloned(),
+            force: store_matches.get_flag("force"),
+            unlock: secret::Source::from_matches(store_matches),
+        });
+    let encrypted = matches.get_one::<PathBuf>("output").map(|path| {
+        let recipients = if let Some(keys) = matches.get_many::<String>("encrypt-to") {
+            Recipients::Gpg(keys.cloned().collect())
+        } else if let Some(keys) = matches.get_many::<String>("age-recipient") {
+            Recipients::Age(keys.cloned().collect())
+        } else {
+            clap::Error::raw(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--output needs --encrypt-to or --age-recipient; secrets are never written in cleartext\n",
+            )
+            .exit()
+        };
+        EncryptedOutput::new(path.clone(), recipients)
+    });
+    let history = matches.contains_id("log-history").then(|| {
+        let mut history = config.history().unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+        });
+        if let Some(record) = matches.get_one::<String>("log-history") {
+            history.record = Record::from_name(record).unwrap();
+        }
+        history.kind = matches.subcommand_name().unwrap_or("phrase").to_string();
+        history
+    });
+    let reminders = matches.get_one::<u64>("remind").map(|&period_days| {
+        let label = matches
+            .get_one::<String>("remind-label")
+            .or(matches.get_one::<String>("keyring"))
+            .cloned();
+        if label.is_none() && !matches.get_flag("labels") {
+            clap::Error::raw(
+                clap::error::ErrorKind::MissingRequiredArgument,
+                "--remind needs a label: pass --remind-label or --keyring, or use --labels\n",
+            )
+            .exit();
+        }
+        if label.is_some() && num_passwords != 1 {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                "--remind notes a single secret per label; drop --count, or use --labels\n",
+            )
+            .exit();
+        }
+        ReminderLog::new(reminders_path(&matches), period_days, label)
+    });
+    let mut output = OutputOptions {
+        echo: !matches.get_flag("no-echo") && store_target.is_none(),
+        spell_out: matches.get_flag("spell"),
+        screen_reader: matches.get_flag("screen-reader"),
+        hash: matches
+            .get_one::<String>("hash")
+            .and_then(|name| HashAlgorithm::from_name(name)),
+        keyring: matches.get_one::<String>("keyring").cloned(),
+        qr: matches.get_flag("qr"),
+        qr_png: matches.get_one::<PathBuf>("qr-png").cloned(),
+        encrypted,
+        history,
+        reminders,
+        audit: None,
+        stdout: BufWriter::new(io::stdout()),
+    };
+    let transcript_path = matches.get_one::<PathBuf>("transcript");
+
+    for (flag, set) in [
+        ("--keyring", output.keyring.is_some()),
+        ("--qr-png", output.qr_png.is_some()),
+    ] {
+        if set && num_passwords != 1 {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("{} saves a single secret; drop --count\n", flag),
+            )
+            .exit();
+        }
+    }
+
+    if let (Some(session), Some(&number)) = (&session, matches.get_one::<usize>("pick")) {
+        let index = session.index(number).unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+        });
+        print_secret(&session.phrases[index], &mut output);
+        output.finish();
+        Session::forget(&data_root(&matches));
+        return;
+    }
+
+    if let Some(("update", _)) = matches.subcommand() {
+        update_sources(&get_data_dir(&matches), &sources, &generation);
+        return;
+    }
+
+    if let Some(("bootstrap", bootstrap_matches)) = matches.subcommand() {
+        let seed = bootstrap_matches.get_one::<PathBuf>("from-dir").unwrap();
+        let report = bootstrap::run(seed, &get_data_dir(&matches), &sources, &generation);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Report serializes")
+        );
+        if !report.ok {
+            std::process::exit(exit::FAILURE);
+        }
+        return;
+    }
+
+    if let Some(("words", words_matches)) = matches.subcommand() {
+        manage_words(&matches, words_matches, &sources, &generation);
+        return;
+    }
+
+    if let Some(("pack", pack_matches)) = matches.subcommand() {
+        manage_packs(&matches, pack_matches, &sources);
+        return;
+    }
+
+    if let Some(("templates", _)) = matches.subcommand() {
+        for (name, text) in template::PRESETS {
+            println!("{:<12} {}", name, text);
+        }
+        for (name, text) in &config.templates {
+            let note = if Template::preset(name).is_some() {
+                "  (config file, hidden by the built-in preset)"
+            } else {
+                "  (config file)"
+            };
+            println!("{:<12} {}{}", name, text, note);
+        }
+        return;
+    }
+
+    if let Some(("capabilities", capabilities_matches)) = matches.subcommand() {
+        let format =
+            export::Format::from_name(capabilities_matches.get_one::<String>("format").unwrap())
+                .unwrap();
+        let subcommands = cli()
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        capabilities::Capabilities::new(
+            subcommands,
+            &config.templates,
+            &word_list_dir(&matches, &generation, false),
+        )
+        .write(format)
+        .unwrap_or_else(|e| {
+            eprintln!("error: failed to write capabilities: {}", e);
+            std::process::exit(1);
+        });
+        return;
+    }
+
+    if let Some(("history", history_matches)) = matches.subcommand() {
+        let path = config.history_path().unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+        });
+        let secret = secret::read(&secret::Source::Prompt, "Phrase to look up: ")
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        let identity = history_matches.get_one::<PathBuf>("identity");
+        let (found, checked) = history::search(&path, identity.map(PathBuf::as_path), &secret)
+            .unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            });
+        if found.is_empty() {
+            println!(
+                "Not generated by phraseforge ({} logged secrets checked)",
+                checked
+            );
+            std::process::exit(1);
+        }
+        for entry in found {
+            let label = entry
+                .label
+                .map(|label| format!(" for {}", label))
+                .unwrap_or_default();
+            println!(
+                "Generated at unix time {} by phraseforge {} ({}{})",
+                entry.created_at_unix, entry.phraseforge_version, entry.kind, label
+            );
+        }
+        return;
+    }
+
+    if let Some(("reminders", reminders_matches)) = matches.subcommand() {
+        let path = reminders_path(&matches);
+        let entries = reminders::load(&path).unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        let shown: Vec<&reminders::Entry> = entries
+            .iter()
+            .filter(|entry| reminders_matches.get_flag("all") || reminders::is_due(entry))
+            .collect();
+        if shown.is_empty() {
+            println!(
+                "Nothing due for rotation ({} credentials noted in {})",
+                entries.len(),
+                path.display()
+            );
+            return;
+        }
+        let width = shown
+            .iter()
+            .map(|entry| entry.label.len())
+            .max()
+            .unwrap_or(0);
+        for entry in shown {
+            println!("{}", reminders::describe(entry, width));
+        }
+        return;
+    }
+
+    if let Some(("check", _)) = matches.subcommand() {
+        let secrets: Vec<String> = if io::stdin().is_terminal() {
+            vec![secret::read(&secret::Source::Prompt, "Phrase to check: ")
+                .unwrap_or_else(|e| exit::fail(exit::FAILURE, e))]
+        } else {
+            io::stdin()
+                .lock()
+                .lines()
+                .map(|line| {
+                    line.unwrap_or_else(|e| {
+                        eprintln!("error: cannot read phrases from stdin: {}", e);
+                        std::process::exit(1);
+                    })
+                })
+                .filter(|line| !line.is_empty())
+                .collect()
+        };
+        // Inputs are identified by position only, never echoed.
+        for (index, secret) in secrets.iter().enumerate() {
+            let score = strength::score(secret);
+            let prefix = if secrets.len() > 1 {
+                format!("{}: ", index + 1)
+            } else {
+                String::new()
+            };
+            println!(
+                "{}{:.1} bits, zxcvbn score {}/4",
+                prefix, score.bits, score.score
+            );
+            if let Some(warning) = score.warning {
+                println!("    warning: {}", warning);
+            }
+            for suggestion in score.suggestions {
+                println!("    suggestion: {}", suggestion);
+            }
+        }
+        return;
+    }
+
+    if let Some(("recall", recall_matches)) = matches.subcommand() {
+        let name = recall_matches.get_one::<String>("name").unwrap();
+        let secret = keychain::recall(name).unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        });
+        // Nothing new was generated, so there is nothing to log.
+        let mut output = OutputOptions {
+            keyring: None,
+            history: None,
+            reminders: None,
+            ..output
+        };
+        print_secret(&secret, &mut output);
+        output.finish();
+        return;
+    }
+
+    if let Some(("combine", _)) = matches.subcommand() {
+        let phrase = combine_shares().unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        // Nothing new was generated, so there is nothing to log.
+        let mut output = OutputOptions {
+            history: None,
+            reminders: None,
+            ..output
+        };
+        print_secret(&phrase, &mut output);
+        output.finish();
+        return;
+    }
+
+    if let Some(("random", random_matches)) = matches.subcommand() {
+        let length = *random_matches.get_one::<usize>("length").unwrap();
+        let charset = random_matches.get_one::<Charset>("charset").unwrap();
+        let loaded = Instant::now();
+
+        let mut rng = rand::rng();
+        let mut count = 0;
+        for _ in indices(&matches) {
+            print_secret(&random::generate(length, charset, &mut rng), &mut output);
+            count += 1;
+        }
+
+        if let Some(transcript_path) = transcript_path {
+            let mut transcript = Transcript::new(
+                &matches,
+                Vec::new(),
+                random::entropy(length, charset),
+                count,
+            );
+            transcript.record_timing(started, loaded);
+            transcript.save(transcript_path);
+        }
+        output.finish();
+        return;
+    }
+
+    if let Some(("pin", pin_matches)) = matches.subcommand() {
+        let digits = *pin_matches.get_one::<u32>("digits").unwrap();
+        let loaded = Instant::now();
+
+        let mut rng = rand::rng();
+        let mut count = 0;
+        for _ in indices(&matches) {
+            print_secret(&pin::generate(digits, &mut rng), &mut output);
+            count += 1;
+        }
+
+        if let Some(transcript_path) = transcript_path {
+            let mut transcript = Transcript::new(&matches, Vec::new(), pin::entropy(digits), count);
+            transcript.record_timing(started, loaded);
+            transcript.save(transcript_path);
+        }
+        output.finish();
+        return;
+    }
+
+    if let Some(("derive", derive_matches)) = matches.subcommand() {
+        let site = derive_matches.get_one::<String>("site").unwrap();
+        let words = *derive_matches.get_one::<u32>("words").unwrap() as usize;
+        let counter = *derive_matches.get_one::<u32>("counter").unwrap();
+        let separator = derive_matches.get_one::<String>("separator").unwrap();
+        if derive::normalize_site(site).is_empty() {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                "--site must not be empty\n",
+            )
+            .exit();
+        }
+
+        let data_dir = get_data_dir(&matches);
+        let word_list_path = data_dir.join(diceware::EFF_LARGE_WORDLIST_FILE);
+        let word_list = diceware::load_or_download_eff_word_list(
+            &data_dir,
+            &sources,
+            matches.get_flag("redownload"),
+        );
+        let secret = secret::read(
+            &secret::Source::from_matches(derive_matches),
+            "Master secret: ",
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        });
+        if secret.is_empty() {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                "master secret must not be empty\n",
+            )
+            .exit();
+        }
+        let loaded = Instant::now();
+
+        let phrase: Vec<&str> = derive::word_indices(secret.as_bytes(), site, counter, words)
+            .into_iter()
+            .map(|index| {
+                word_list
+                    .get(&diceware::roll_for_index(index))
+                    .expect("EFF word list is incomplete")
+                    .as_str()
+            })
+            .collect();
+        print_secret(&phrase.join(separator), &mut output);
+
+        if let Some(transcript_path) = transcript_path {
+            // The phrase is only as strong as the master secret; these bits
+            // are an upper bound.
+            let mut transcript = Transcript::new(
+                &matches,
+                vec![transcript::file_record(
+                    "eff-large-wordlist",
+                    &word_list_path,
+                )],
+                diceware::entropy(words),
+                1,
+            );
+            transcript.determinism.deterministic = true;
+            transcript.record_timing(started, loaded);
+            transcript.save(transcript_path);
+        }
+        output.finish();
+        return;
+    }
+
+    if let Some(("dice", dice_matches)) = matches.subcommand() {
+        let separator = dice_matches.get_one::<String>("separator").unwrap();
+        let data_dir = get_data_dir(&matches);
+        let word_list_path = data_dir.join(diceware::EFF_LARGE_WORDLIST_FILE);
+        let words = diceware::load_or_download_eff_word_list(
+            &data_dir,
+            &sources,
+            matches.get_flag("redownload"),
+        );
+        let loaded = Instant::now();
+
+        let mut phrase_count = 0;
+        let mut fewest_words = usize::MAX;
+        for line in io::stdin().lock().lines() {
+            let line = line.expect("Failed to read dice rolls from stdin");
+            if line.trim().is_empty() {
+                continue;
+            }
+            let rolls = diceware::parse_rolls(&line).unwrap_or_else(|e| {
+                clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+            });
+            let phrase: Vec<&str> = rolls
+                .iter()
+                .map(|group| {
+                    words
+                        .get(group)
+                        .expect("EFF word list is incomplete")
+                        .as_str()
+                })
+                .collect();
+            print_secret(&phrase.join(separator), &mut output);
+            phrase_count += 1;
+            fewest_words = fewest_words.min(phrase.len());
+        }
+
+        if let Some(transcript_path) = transcript_path {
+            let mut transcript = Transcript::new(
+                &matches,
+                vec![transcript::file_record(
+                    "eff-large-wordlist",
+                    &word_list_path,
+                )],
+                diceware::entropy(if phrase_count > 0 { fewest_words } else { 0 }),
+                phrase_count,
+            );
+            transcript.record_timing(started, loaded);
+            transcript.save(transcript_path);
+        }
+        output.finish();
+        return;
+    }
+
+    let options = GenerationOptions {
+        template: {
+            let template = if matches.get_flag("sentence") {
+                Template::parse(sentence::TEMPLATE).expect("Sentence template is valid")
+            } else if let Some(name) = matches.get_one::<String>("preset") {
+                resolve_preset(name, &config)
+            } else {
+                matches.get_one::<Template>("template").unwrap().clone()
+            };
+            match matches.get_one::<String>("separator") {
+                Some(separator) => template.with_separator(separator),
+                None => template,
+            }
+        },
+        verb_form: VerbForm::from_name(matches.get_one::<String>("verb-form").unwrap()).unwrap(),
+        min_frequency: matches
+            .get_one::<MinFrequency>("min-frequency")
+            .unwrap()
+            .clone(),
+        alliterate: matches.get_flag("alliterate"),
+        acrostic: matches.get_one::<Vec<char>>("acrostic").cloned(),
+        rhyme: matches.get_flag("rhyme"),
+        leet: matches
+            .get_one::<String>("leet")
+            .and_then(|level| LeetLevel::from_name(level)),
+        caps: matches
+            .get_one::<caps::CapsPattern>("caps-pattern")
+            .cloned(),
+        mutations: *matches.get_one::<usize>("mutate").unwrap(),
+        allow_repeats: matches.get_flag("allow-repeats"),
+        distinct_sounds: matches.get_flag("distinct-sounds"),
+        unambiguous: matches.get_flag("unambiguous").then(|| {
+            config.ambiguity().unwrap_or_else(|e| {
+                clap::Error::raw(clap::error::ErrorKind::InvalidValue, format!("{}\n", e)).exit()
+            })
+        }),
+        policy: matches
+            .get_one::<String>("policy")
+            .map(|name| resolve_policy(name, &config)),
+        sentence: matches.get_flag("sentence"),
+        min_entropy: matches
+            .get_one::<u32>("min-entropy")
+            .map(|&bits| bits as f64),
+        number: None,
+        series_taken: 0,
+    };
+    if options.template.has_number() && number_choices(&options).is_empty() {
+        exit::fail(
+            exit::UNSATISFIABLE,
+            "every number from 1 to 998 contains a sequence [unambiguous] lists; shorten the list or drop {number} from the template",
+        );
+    }
+    // Drawn once, so every phrase of the series repeats it. The words are
+    // never repeated, so the last phrase drawn has what the others left.
+    let series = matches.get_one::<usize>("series").copied();
+    let options = GenerationOptions {
+        number: series.and_then(|_| number_choices(&options).choose(&mut rand::rng()).copied()),
+        series_taken: series.map_or(0, |size| {
+            let word_slots = options
+                .template
+                .slots()
+                .filter(|&slot| slot != Slot::Number)
+                .count();
+            size.saturating_sub(1) * word_slots
+        }),
+        ..options
+    };
+
+    let data_dir = word_list_dir(
+        &matches,
+        &generation,
+        matches.get_flag("explain")
+            || matches.get_flag("concrete")
+            || memorability_synsets(&matches, &sources),
+    );
+    let common_passwords = matches.get_flag("reject-common").then(|| {
+        CommonPasswords::load_or_download(
+            &get_data_dir(&matches),
+            &sources,
+            matches.get_flag("redownload"),
+        )
+    });
+    // Among other words a listed one is harmless; alone it is the phrase.
+    let common_in_pools = common_passwords.as_ref().filter(|_| {
+        options
+            .template
+            .slots()
+            .filter(|&slot| slot != Slot::Number)
+            .count()
+            <= 1
+    });
+    if let Some(("export", export_matches)) = matches.subcommand() {
+        let pools: HashSet<PartOfSpeech> =
+            match export_matches.get_one::<String>("pos").unwrap().as_str() {
+                "all" => PartOfSpeech::ALL.into_iter().collect(),
+                name => HashSet::from([PartOfSpeech::from_name(name).unwrap()]),
+            };
+        let (word_lists, _, pipeline) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &pools,
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        let format =
+            export::Format::from_name(export_matches.get_one::<String>("format").unwrap()).unwrap();
+        let exported = export::Export::new(
+            &word_lists,
+            &pools,
+            &options.min_frequency,
+            pipeline.names(),
+        );
+        exported.write(format).unwrap_or_else(|e| {
+            if e.kind() == io::ErrorKind::BrokenPipe {
+                std::process::exit(0);
+            }
+            eprintln!("error: failed to write export: {}", e);
+            std::process::exit(1);
+        });
+        return;
+    }
+
+    if let Some((name @ ("encode" | "decode"), codec_matches)) = matches.subcommand() {
+        let pos = PartOfSpeech::from_name(codec_matches.get_one::<String>("pos").unwrap()).unwrap();
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &HashSet::from([pos]),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        let codebook =
+            codec::Codebook::new(word_lists.entries_of(pos), &options.min_frequency.of(pos))
+                .unwrap_or_else(|e| exit::fail(exit::UNSATISFIABLE, e));
+        log::debug!(
+            "Codebook: the {} most frequent eligible {}s, {} bits per word",
+            1u64 << codebook.bits_per_word(),
+            pos.name(),
+            codebook.bits_per_word()
+        );
+        let text = if name == "encode" {
+            let input = argument_or_input(codec_matches.get_one::<String>("bytes"), "Hex bytes: ");
+            let bytes = codec::parse_hex(&input).unwrap_or_else(|e| {
+                clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+            });
+            codebook.encode(&bytes).join(" ")
+        } else {
+            let input = argument_or_input(codec_matches.get_one::<String>("words"), "Words: ");
+            let bytes = codebook
+                .decode(input.split_whitespace())
+                .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+            codec::to_hex(&bytes)
+        };
+        // Nothing new was generated, so there is nothing to log.
+        let mut output = OutputOptions {
+            history: None,
+            reminders: None,
+            ..output
+        };
+        print_secret(&text, &mut output);
+        output.finish();
+        return;
+    }
+
+    if let Some(("explore", explore_matches)) = matches.subcommand() {
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &PartOfSpeech::ALL.into_iter().collect(),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        let rows = explore::rows(
+            &word_lists,
+            &options,
+            matches.get_one::<String>("separator").map(String::as_str),
+        );
+        explore::print(
+            &rows,
+            explore_matches.get_one::<usize>("max-length").copied(),
+            options.min_entropy,
+        );
+        return;
+    }
+
+    if let Some(("selftest", selftest_matches)) = matches.subcommand() {
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &template_pools(&options.template),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        let samples = *selftest_matches.get_one::<usize>("samples").unwrap();
+        let checks = selftest::run(&word_lists, &options, samples)
+            .unwrap_or_else(|e| exit::fail(exit::UNSATISFIABLE, e));
+        for check in &checks {
+            let verdict = if check.passed { "PASS" } else { "FAIL" };
+            println!("{}  {}: {}", verdict, check.name, check.detail);
+        }
+        if checks.iter().any(|check| !check.passed) {
+            std::process::exit(exit::FAILURE);
+        }
+        return;
+    }
+
+    if let Some(("similar", similar_matches)) = matches.subcommand() {
+        let phrase = match similar_matches.get_one::<String>("phrase") {
+            Some(phrase) => phrase.clone(),
+            None => secret::read(&secret::Source::Prompt, "Phrase to vary: ")
+                .unwrap_or_else(|e| exit::fail(exit::FAILURE, e)),
+        };
+        let pieces = similar::Pieces::parse(&options.template, &phrase).unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+        });
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &template_pools(&options.template),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        // One variation is rarely what's wanted here.
+        let count = match matches.value_source("count") {
+            Some(ValueSource::DefaultValue) => 5,
+            _ => num_passwords,
+        };
+        let swaps = *similar_matches.get_one::<usize>("swap").unwrap();
+        let mut rng = rand::rng();
+        let mut seen = HashSet::from([phrase]);
+        // Small pools can run out of distinct variations; stop rather than spin.
+        for _ in 0..count * 20 {
+            if seen.len() > count {
+                break;
+            }
+            let variation = pieces.vary(&word_lists, &options, swaps, &mut rng);
+            if seen.insert(variation.clone()) {
+                print_secret(&variation, &mut output);
+            }
+        }
+        output.finish();
+        return;
+    }
+
+    if let Some(("practice", practice_matches)) = matches.subcommand() {
+        if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                "practice is interactive; run it in a terminal\n",
+            )
+            .exit();
+        }
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &template_pools(&options.template),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        let phrase = loop {
+            let phrase = generate_compliant_password(&word_lists, &options).text;
+            if matches.get_flag("screen-reader") {
+                eprint!(
+                    "\n    {}\n    {}\n\nPractice this phrase? [Y/n] ",
+                    phrase,
+                    speech::spoken(&phrase)
+                );
+            } else {
+                eprint!("\n    {}\n\nPractice this phrase? [Y/n] ", phrase);
+            }
+            let mut answer = String::new();
+            io::stdin()
+                .read_line(&mut answer)
+                .expect("Failed to read answer");
+            if !answer.trim().eq_ignore_ascii_case("n") {
+                break phrase;
+            }
+        };
+        let rounds = *practice_matches.get_one::<usize>("rounds").unwrap();
+        let delay = Duration::from_secs(*practice_matches.get_one::<u64>("delay").unwrap());
+        let correct = practice::run(&phrase, rounds, delay, matches.get_flag("screen-reader"));
+        eprintln!("Recalled {} of {} times.", correct, rounds);
+        if correct < rounds {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("web", web_matches)) = matches.subcommand() {
+        let (word_lists, _, _) = prepare_word_lists(
+            &matches,
+            &data_dir,
+            &sources,
+            &generation,
+            &template_pools(&options.template),
+            options.unambiguous.as_ref(),
+            common_in_pools,
+        );
+        // A handful to choose among, unless --count says otherwise.
+        let count = match matches.value_source("count") {
+            Some(ValueSource::DefaultValue) => 3,
+            _ => num_passwords,
+        };
+        let once = web_matches.get_flag("once");
+        let timeout = Duration::from_secs(*web_matches.get_one::<u64>("timeout").unwrap());
+        let server = web::Server::bind(*web_matches.get_one::<u16>("port").unwrap())
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, format!("cannot listen: {}", e)));
+        let url = server
+            .url()
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, format!("cannot listen: {}", e)));
+        eprintln!(
+            "Open {} in a browser on this computer; it stops in {} seconds.",
+            url,
+            timeout.as_secs()
+        );
+        let picks = server
+            .run(
+                count,
+                once,
+                timeout,
+                || generate_compliant_password(&word_lists, &options).text,
+                |phrase| print_secret(phrase, &mut output),
+            )
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, format!("web page failed: {}", e)));
+        output.finish();
+        if picks == 0 {
+            eprintln!("No phrase was picked.");
+            std::process::exit(exit::FAILURE);
+        }
+        return;
+    }
+
+    let (word_lists, using_bundled, pipeline) = prepare_word_lists(
+        &matches,
+        &data_dir,
+        &sources,
+        &generation,
+        &template_pools(&options.template),
+        options.unambiguous.as_ref(),
+        common_in_pools,
+    );
+    if !using_bundled {
+        check_word_list_age(&matches, &config, &data_dir, &sources);
+    }
+    if matches.get_flag("dry-run") {
+        print_pipeline(&pipeline, &word_lists, &options);
+        return;
+    }
+    let filter_names = pipeline.names();
+    for slot in options.template.slots() {
+        if let Some(entries) = word_lists.pool(slot) {
+            log::debug!(
+                "{} pool: {} words after filters, {} above frequency {}",
+                slot.name(),
+                entries.len(),
+                count_eligible(entries, &options.min_frequency.of_slot(slot), None),
+                options.min_frequency.of_slot(slot)
+            );
+        }
+    }
+
+    if options.rhyme && rhyme_slots(&options).is_none() {
+        clap::Error::raw(
+            clap::error::ErrorKind::ValueValidation,
+            "--rhyme needs a template with at least two word slots\n",
+        )
+        .exit();
+    }
+
+    if let Some(pattern) = &options.caps {
+        let word_slots = options
+            .template
+            .slots()
+            .filter(|&slot| slot != Slot::Number)
+            .count();
+        if let Err(problem) = pattern.check(word_slots) {
+            clap::Error::raw(
+                clap::error::ErrorKind::ValueValidation,
+                format!("{}\n", problem),
+            )
+            .exit();
+        }
+    }
+
+    let problems = acrostic_problems(&word_lists, &options);
+    if !problems.is_empty() {
+        exit::fail(
+            exit::UNSATISFIABLE,
+            format!("cannot build acrostic:\n  {}", problems.join("\n  ")),
+        );
+    }
+    // Definitions would reveal the words, so they follow the secret's echo.
+    let glossary = (matches.get_flag("explain") && output.echo)
+        .then(|| Glossary::new(&data_dir.join("dict"), &options.template));
+    // Like definitions, near-miss words would reveal the phrase.
+    let typo_report = matches.get_flag("typo-report") && output.echo;
+    let abbrev = matches.get_flag("abbrev") && output.echo;
+    let annotate = matches.get_flag("annotate") && output.echo;
+    let split = matches.get_one::<shamir::Split>("split").copied();
+    let loaded = Instant::now();
+
+    if store_target.is_some() && matches.get_flag("labels") {
+        clap::Error::raw(
+            clap::error::ErrorKind::ArgumentConflict,
+            "store saves a single phrase; drop --labels\n",
+        )
+        .exit();
+    }
+    if store_target.is_some() && (num_passwords != 1 || matches.get_flag("infinite")) {
+        clap::Error::raw(
+            clap::error::ErrorKind::ValueValidation,
+            "store saves a single phrase; drop --count or --infinite\n",
+        )
+        .exit();
+    }
+
+    let estimate = entropy::estimate(&word_lists, &options);
+    log::debug!(
+        "Estimated min-entropy: {:.1} bits per phrase",
+        estimate.bits_per_phrase
+    );
+    if let Some(&min_entropy) = matches.get_one::<u32>("min-entropy") {
+        if estimate.bits_per_phrase < min_entropy as f64 {
+            let mut message = format!(
+                "phrases would have {:.1} bits of entropy, below --min-entropy {}\n",
+                estimate.bits_per_phrase, min_entropy
+            );
+            let relaxations = entropy::relaxations(&word_lists, &options);
+            if !relaxations.is_empty() {
+                message.push_str("  relax a constraint to gain entropy:\n");
+                for (change, bits) in relaxations {
+                    message.push_str(&format!("    {}: +{:.1} bits\n", change, bits));
+                }
+            }
+            if !filter_names.is_empty() {
+                message.push_str(&format!(
+                    "  these filters also shrink the word pools: {}\n",
+                    filter_names.join(", ")
+                ));
+            }
+            exit::fail(exit::UNSATISFIABLE, message.trim_end());
+        }
+    }
+
+    // `[history] avoid_file` keeps one beside the history `--log-history`
+    // appends to.
+    let mut avoid = matches
+        .get_one::<PathBuf>("avoid-file")
+        .cloned()
+        .or_else(|| {
+            config
+                .avoid_file()
+                .filter(|_| matches.contains_id("log-history"))
+        })
+        .map(|path| AvoidList::load(path).unwrap_or_else(|e| exit::fail(exit::FAILURE, e)));
+
+    if let Some(path) = matches.get_one::<PathBuf>("audit-json") {
+        let policy = matches.get_one::<String>("policy").cloned();
+        let mut checks_passed = Vec::new();
+        if let Some(policy) = &policy {
+            checks_passed.push(format!("policy {}", policy));
+        }
+        if let Some(min_entropy) = matches.get_one::<u32>("min-entropy") {
+            checks_passed.push(format!("min-entropy {}", min_entropy));
+        }
+        if common_in_pools.is_some() {
+            checks_passed.push("reject-common".to_string());
+        }
+        if matches.get_flag("hibp") {
+            checks_passed.push("hibp".to_string());
+        }
+        if matches.get_flag("unique") {
+            checks_passed.push("unique".to_string());
+        }
+        if avoid.is_some() {
+            checks_passed.push("avoid-file".to_string());
+        }
+        let audit = AuditLog::open(
+            path.clone(),
+            options.template.to_string(),
+            estimate.bits_per_phrase,
+            policy,
+            checks_passed,
+        )
+        .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        output.audit = Some(audit);
+    }
+
+    if matches.get_flag("series-hint") {
+        let number = match options.number {
+            Some(number) if options.template.has_number() => format!(", number {}", number),
+            _ => String::new(),
+        };
+        eprintln!(
+            "Series hint: {}{}; each phrase is {:.1} bits with what they share left out",
+            options.template, number, estimate.bits_per_phrase
+        );
+    }
+
+    if let Some(mut session) = session {
+        let number = *matches.get_one::<usize>("reroll").unwrap();
+        let index = session.index(number).unwrap_or_else(|e| {
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e)).exit()
+        });
+        let phrase = match matches.get_one::<usize>("slot") {
+            Some(&word) => similar::Pieces::parse(&options.template, &session.phrases[index])
+                .and_then(|pieces| pieces.reroll(word, &word_lists, &options, &mut rand::rng()))
+                .unwrap_or_else(|e| {
+                    clap::Error::raw(clap::error::ErrorKind::ValueValidation, format!("{}\n", e))
+                        .exit()
+                }),
+            None => generate_compliant_password(&word_lists, &options).text,
+        };
+        let display = if io::stdout().is_terminal() {
+            format!("{}. {}", number, phrase)
+        } else {
+            phrase.clone()
+        };
+        print_secret_as(&phrase, &display, &mut output);
+        session.phrases[index] = phrase;
+        session.save(&data_root(&matches)).unwrap_or_else(|e| {
+            exit::fail(exit::FAILURE, format!("cannot save the session: {}", e))
+        });
+        output.finish();
+        return;
+    }
+
+    let phrase_count = if matches.get_flag("labels") {
+        print_labeled_phrases(
+            &word_lists,
+            &options,
+            estimate.bits_per_phrase,
+            common_passwords.as_ref(),
+            avoid.as_mut(),
+            &mut output,
+        )
+    } else {
+        // Interactive batches get aligned, colored phrases with a dim entropy
+        // note; anything piped or redirected stays plain.
+        let interactive = output.echo
+            && output.hash.is_none()
+            && output.encrypted.is_none()
+            && !matches.get_flag("infinite")
+            && io::stdout().is_terminal();
+        let use_color =
+            interactive && !output.screen_reader && color::enabled(matches.get_flag("no-color"));
+        let hibp = matches.get_flag("hibp");
+        let generated: Box<dyn Iterator<Item = GeneratedPhrase>> = if matches.get_flag("unique")
+            || hibp
+            || avoid.is_some()
+            || series.is_some()
+        {
+            Box::new(
+                generate_validated_batch(
+                    &word_lists,
+                    &options,
+                    num_passwords,
+                    matches.get_flag("unique"),
+                    series.is_some(),
+                    hibp.then(|| hibp::Checker::new(sources.async_client(), &sources.hibp_range)),
+                    avoid.as_mut(),
+                )
+                .into_iter(),
+            )
+        } else {
+            Box::new(indices(&matches).map(|_| generate_compliant_password(&word_lists, &options)))
+        };
+        let generated = generated.enumerate().map(|(index, phrase)| {
+            if log::log_enabled!(log::Level::Debug) {
+                log_phrase_breakdown(index + 1, &phrase, &word_lists, &options);
+            }
+            phrase
+        });
+        let scorer = matches.contains_id("sort-by").then(|| {
+            let concrete = memorability_synsets(&matches, &sources)
+                .then(|| ConcreteFilter::new(&data_dir.join("dict")));
+            memorability::Scorer::new(&word_lists, &options, concrete)
+        });
+        // Aligning and sorting need every phrase up front; anything else
+        // streams, so huge counts run in constant memory.
+        let collect = interactive || scorer.is_some();
+        let (phrases, width): (Box<dyn Iterator<Item = GeneratedPhrase>>, usize) = if collect {
+            let mut phrases: Vec<GeneratedPhrase> = generated.collect();
+            if let Some(scorer) = &scorer {
+                phrases = scorer.sort(phrases);
+            }
+            let width = phrases
+                .iter()
+                .map(|phrase| phrase.text.width())
+                .max()
+                .unwrap_or(0);
+            (Box::new(phrases.into_iter()), width)
+        } else {
+            (Box::new(generated), 0)
+        };
+
+        // Numbered, so --pick and --reroll can name a result.
+        let numbered = interactive && (num_passwords > 1 || matches.get_flag("session"));
+        let number_width = num_passwords.to_string().len();
+        let mut remembered = Vec::new();
+        let mut count = 0;
+        for (index, phrase) in phrases.enumerate() {
+            count += 1;
+            // zxcvbn dominates the cost of a phrase, so skip it when nobody
+            // would see the result.
+            let (bits, weakness) = if interactive || log::log_enabled!(log::Level::Warn) {
+                strength::weaker(estimate.bits_per_phrase, &phrase.text)
+            } else {
+                (estimate.bits_per_phrase, None)
+            };
+            if let Some(weakness) = &weakness {
+                warn_weaker_than_estimate(&(index + 1).to_string(), bits, weakness);
+            }
+            warn_if_common(
+                &(index + 1).to_string(),
+                &phrase.text,
+                common_passwords.as_ref(),
+            );
+            let mut display = phrase.text.clone();
+            if use_color
+                && phrase
+                    .pieces
+                    .iter()
+                    .map(|(_, text)| text.as_str())
+                    .collect::<String>()
+                    == phrase.text
+            {
+                display = color::paint(&phrase.pieces);
+            }
+            if interactive && output.screen_reader {
+                display.push_str(&speech::note(bits, weakness.as_deref()));
+            } else if interactive {
+                // Display width, since emoji take two terminal columns.
+                let padding = " ".repeat(width - phrase.text.width());
+                let note = match &weakness {
+                    Some(weakness) => format!("  {:.1} bits (zxcvbn: {})", bits, weakness),
+                    None => format!("  {:.1} bits", bits),
+                };
+                let note = if use_color { color::dim(&note) } else { note };
+                display = format!("{}{}{}", display, padding, note);
+            }
+            if numbered {
+                display = format!("{:>width$}. {}", index + 1, display, width = number_width);
+            }
+            print_secret_as(&phrase.text, &display, &mut output);
+            if matches.get_flag("session") {
+                remembered.push(phrase.text.clone());
+            }
+            if let Some(target) = &store_target {
+                target.store(&phrase.text).unwrap_or_else(|e| {
+                    eprintln!("error: {}", e);
+                    std::process::exit(1);
+                });
+                log::info!(
+                    "Stored phrase in {} as {}",
+                    target.backend.name(),
+                    target.entry
+                );
+            }
+            if let Some(glossary) = &glossary {
+                for (slot, word) in &phrase.words {
+                    output.emit(&format!("    {}", glossary.describe(*slot, word)));
+                }
+            }
+            if annotate {
+                for (slot, word) in &phrase.words {
+                    let entries = word_lists.pool(*slot).expect("Word slots have a pool");
+                    let min_frequency = options.min_frequency.of_slot(*slot);
+                    output.emit(&format!(
+                        "    {}",
+                        annotate::describe(*slot, word, entries, &min_frequency)
+                    ));
+                }
+            }
+            if abbrev {
+                output.emit(&format!(
+                    "    hint: {}",
+                    abbrev::initials(&phrase.pieces, &phrase.text)
+                ));
+            }
+            if let Some(split) = split {
+                let shares = shamir::split(phrase.text.as_bytes(), split, &mut rand::rng());
+                for (index, share) in shares.iter().enumerate() {
+                    output.emit(&format!(
+                        "    share {}/{}: {}",
+                        index + 1,
+                        split.shares,
+                        share
+                    ));
+                }
+            }
+            if typo_report {
+                let words: Vec<(Slot, &str, Vec<String>)> = phrase
+                    .words
+                    .iter()
+                    .filter_map(|(slot, word)| {
+                        let pool = word_lists.pool(*slot)?;
+                        let neighbors =
+                            typo::neighbors(word, pool, &options.min_frequency.of_slot(*slot));
+                        Some((*slot, word.as_str(), neighbors))
+                    })
+                    .collect();
+                for line in typo::report(&words) {
+                    output.emit(&format!("    {}", line));
+                }
+            }
+        }
+        if matches.get_flag("session") {
+            Session::new(std::env::args(), remembered)
+                .save(&data_root(&matches))
+                .unwrap_or_else(|e| {
+                    exit::fail(exit::FAILURE, format!("cannot save the session: {}", e))
+                });
+        }
+        count
+    };
+    if let Some(avoid) = &mut avoid {
+        avoid
+            .save()
+            .unwrap_or_else(|e| exit::fail(exit::FAILURE, e));
+        log::info!("Added the phrases issued to {}", avoid.path.display());
+    }
+
+    if let Some(transcript_path) = transcript_path {
+        let mut transcript = Transcript::new(
+            &matches,
+            if using_bundled {
+                vec![bundled::source_record()]
+            } else {
+                transcript::source_records(&data_dir, &sources)
+            },
+            estimate,
+            phrase_count,
+        );
+        transcript.record_timing(started, loaded);
+        transcript.save(transcript_path);
     }
+    output.finish();
 }
@@ -0,0 +1,182 @@
+//! `--sort-by memorability`: orders a batch so the phrases most likely to
+//! stick come first.
+//!
+//! The score is a heuristic from 0 to 100, averaged over a phrase's words:
+//! how common each word is within its eligible pool (its frequency rank),
+//! how short it is, and, with the WordNet dictionary, whether it is concrete
+//! in the sense of `--concrete`. Every candidate was drawn the same way, so
+//! each carries the same entropy; only the order changes. Picking the top
+//! one of N by a known score does cost up to log2(N) bits, though, so for
+//! the full estimate pick among them by taste rather than always the first.
+
+use crate::concrete::ConcreteFilter;
+use crate::filter::WordFilter;
+use crate::generate::{eligible, GeneratedPhrase, GenerationOptions};
+use crate::template::Slot;
+use crate::{normalize, PartOfSpeech, WordLists};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+pub const NAMES: [&str; 1] = ["memorability"];
+
+/// Letters up to which a word counts as short, and from which it counts as
+/// long; lengths between score in proportion.
+const SHORT: usize = 4;
+const LONG: usize = 12;
+
+pub struct Scorer {
+    /// Each word's standing in its pool, 1 for the most common.
+    ranks: HashMap<PartOfSpeech, HashMap<String, f64>>,
+    concrete: Option<ConcreteFilter>,
+}
+
+impl Scorer {
+    pub fn new(
+        word_lists: &WordLists,
+        options: &GenerationOptions,
+        concrete: Option<ConcreteFilter>,
+    ) -> Self {
+        let mut ranks = HashMap::new();
+        for slot in options.template.slots() {
+            let (Some(pos), Some(entries)) = (slot.part_of_speech(), word_lists.pool(slot)) else {
+                continue;
+            };
+            let min_frequency = options.min_frequency.of_slot(slot);
+            let mut pool: Vec<_> = eligible(entries, &min_frequency, None).collect();
+            pool.sort_by_key(|entry| Reverse(entry.frequency));
+            let last = pool.len().saturating_sub(1).max(1) as f64;
+            ranks.entry(pos).or_insert_with(|| {
+                pool.iter()
+                    .enumerate()
+                    .map(|(rank, entry)| (entry.word.clone(), 1.0 - rank as f64 / last))
+                    .collect()
+            });
+        }
+        Scorer { ranks, concrete }
+    }
+
+    fn word(&self, slot: Slot, word: &str) -> f64 {
+        let Some(pos) = slot.part_of_speech() else {
+            return 0.0;
+        };
+        let frequency = self
+            .ranks
+            .get(&pos)
+            .and_then(|ranks| ranks.get(word))
+            .copied()
+            .unwrap_or(0.0);
+        let letters = normalize::letters(word).clamp(SHORT, LONG);
+        let length = (LONG - letters) as f64 / (LONG - SHORT) as f64;
+        match &self.concrete {
+            Some(filter) => {
+                let concrete = match pos {
+                    PartOfSpeech::Noun | PartOfSpeech::Adjective => {
+                        f64::from(u8::from(filter.keep(pos, word)))
+                    }
+                    // Verbs and adverbs are never concrete; leave them be.
+                    PartOfSpeech::Verb | PartOfSpeech::Adverb => 0.5,
+                };
+                0.5 * frequency + 0.25 * length + 0.25 * concrete
+            }
+            None => 0.6 * frequency + 0.4 * length,
+        }
+    }
+
+    /// The phrase's score, from 0 to 100.
+    pub fn score(&self, phrase: &GeneratedPhrase) -> f64 {
+        if phrase.words.is_empty() {
+            return 0.0;
+        }
+        let total: f64 = phrase
+            .words
+            .iter()
+            .map(|(slot, word)| self.word(*slot, word))
+            .sum();
+        100.0 * total / phrase.words.len() as f64
+    }
+
+    /// `phrases`, most memorable first, ties in the order they came.
+    pub fn sort(&self, phrases: Vec<GeneratedPhrase>) -> Vec<GeneratedPhrase> {
+        let mut scored: Vec<(f64, GeneratedPhrase)> = phrases
+            .into_iter()
+            .map(|phrase| (self.score(&phrase), phrase))
+            .collect();
+        // By position in the batch, not by text: secrets stay out of logs.
+        for (index, (score, _)) in scored.iter().enumerate() {
+            log::debug!("Phrase {}: memorability {:.0}", index + 1, score);
+        }
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().map(|(_, phrase)| phrase).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::MinFrequency;
+    use crate::inflect::VerbForm;
+    use crate::template::Template;
+    use crate::{WordEntry, WordType};
+
+    fn entries(words: &[(&str, u32)]) -> Vec<WordEntry> {
+        words
+            .iter()
+            .map(|&(word, frequency)| WordEntry {
+                word: word.to_string(),
+                frequency,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn common_short_words_come_first() {
+        let word_lists = WordLists {
+            adjectives: WordType::Adjective(entries(&[("red", 9000), ("incandescent", 20)])),
+            nouns: WordType::Noun(entries(&[("cat", 8000), ("paraphernalia", 30)])),
+            verbs: WordType::Verb(Vec::new()),
+            adverbs: WordType::Adverb(Vec::new()),
+        };
+        let options = GenerationOptions {
+            template: Template::parse("{adjective}-{noun}").unwrap(),
+            verb_form: VerbForm::Base,
+            min_frequency: MinFrequency::uniform(0),
+            alliterate: false,
+            acrostic: None,
+            rhyme: false,
+            leet: None,
+            caps: None,
+            mutations: 0,
+            policy: None,
+            allow_repeats: false,
+            distinct_sounds: false,
+            unambiguous: None,
+            sentence: false,
+            min_entropy: None,
+            number: None,
+            series_taken: 0,
+        };
+        let scorer = Scorer::new(&word_lists, &options, None);
+        let phrase = |adjective: &str, noun: &str| GeneratedPhrase {
+            text: format!("{}-{}", adjective, noun),
+            words: vec![
+                (Slot::Adjective, adjective.to_string()),
+                (Slot::Noun, noun.to_string()),
+            ],
+            pieces: Vec::new(),
+        };
+        let sorted: Vec<String> = scorer
+            .sort(vec![
+                phrase("incandescent", "paraphernalia"),
+                phrase("red", "paraphernalia"),
+                phrase("red", "cat"),
+            ])
+            .into_iter()
+            .map(|phrase| phrase.text)
+            .collect();
+        assert_eq!(
+            sorted,
+            ["red-cat", "red-paraphernalia", "incandescent-paraphernalia"]
+        );
+        assert_eq!(scorer.score(&phrase("red", "cat")), 100.0);
+    }
+}
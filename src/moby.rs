@@ -0,0 +1,108 @@
+//! Parsing Moby's part-of-speech list, `mobypos.txt`, for `--dictionary
+//! moby` and to tag SCOWL's words.
+//!
+//! Each entry is a word, a `×` and a string of part-of-speech codes, e.g.
+//! `dimple×Nt` for a noun that is also a transitive verb. The file predates
+//! UTF-8: the `×` is a single Latin-1 byte and lines may end in a bare
+//! carriage return, so it is read as bytes. Codes are weighted by the order
+//! Moby lists them in, for `--assign-part-of-speech`.
+
+use crate::dictionary::{pool_index, Pools};
+use crate::{normalize, PartOfSpeech};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+pub const MOBY_POS_URL: &str = "https://www.gutenberg.org/files/3203/files/mobypos.txt";
+pub const POS_FILE: &str = "mobypos.txt";
+
+/// The `×` between a word and its codes, in Latin-1 and in UTF-8.
+const SEPARATORS: [&[u8]; 2] = [b"\xd7", "\u{d7}".as_bytes()];
+
+/// The part of speech a Moby code stands for. Plurals (`p`) and noun
+/// phrases (`h`) are left out, as are the closed classes.
+fn part_of_speech(code: char) -> Option<PartOfSpeech> {
+    match code {
+        'N' => Some(PartOfSpeech::Noun),
+        'V' | 't' | 'i' => Some(PartOfSpeech::Verb),
+        'A' => Some(PartOfSpeech::Adjective),
+        'v' => Some(PartOfSpeech::Adverb),
+        _ => None,
+    }
+}
+
+/// Splits an entry into its word and codes.
+fn split_entry(line: &[u8]) -> Option<(String, &[u8])> {
+    SEPARATORS.iter().find_map(|separator| {
+        let at = line
+            .windows(separator.len())
+            .position(|window| window == *separator)?;
+        let word = std::str::from_utf8(&line[..at]).ok()?;
+        Some((word.trim().to_string(), &line[at + separator.len()..]))
+    })
+}
+
+/// The words in `pos_file` that `keep` accepts once lowercased, by part of
+/// speech.
+pub fn pools(pos_file: &Path, keep: impl Fn(&str) -> bool) -> Pools {
+    let text = fs::read(pos_file).expect("Failed to read the Moby part-of-speech list");
+    parse(&text, keep)
+}
+
+fn parse(text: &[u8], keep: impl Fn(&str) -> bool) -> Pools {
+    let mut words = vec![HashMap::new(); PartOfSpeech::ALL.len()];
+    // word -> whether it was ever listed starting with a lowercase letter
+    let mut seen_lowercase: HashMap<String, bool> = HashMap::new();
+
+    for line in text.split(|&byte| byte == b'\n' || byte == b'\r') {
+        let Some((word, codes)) = split_entry(line) else {
+            continue;
+        };
+        let lowercased = normalize::word(&word);
+        if !keep(&lowercased) {
+            continue;
+        }
+        let codes: Vec<PartOfSpeech> = codes
+            .iter()
+            .filter_map(|&code| part_of_speech(code as char))
+            .collect();
+        for (rank, &pos) in codes.iter().enumerate() {
+            let weight = (codes.len() - rank) as u32;
+            let pool: &mut HashMap<String, u32> = &mut words[pool_index(pos)];
+            let best = pool.entry(lowercased.clone()).or_insert(0);
+            *best = (*best).max(weight);
+        }
+        if codes.contains(&PartOfSpeech::Noun) {
+            let lowercase = word.chars().next().is_some_and(|c| c.is_lowercase());
+            *seen_lowercase.entry(lowercased).or_insert(false) |= lowercase;
+        }
+    }
+
+    let proper_nouns: HashSet<String> = seen_lowercase
+        .into_iter()
+        .filter(|(_, lowercase)| !lowercase)
+        .map(|(word, _)| word)
+        .collect();
+    Pools {
+        words,
+        proper_nouns,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_latin1_entries_with_carriage_returns() {
+        let text = b"dimple\xd7Nt\rquickly\xd7v\rAaron\xd7N\rcolour\xc3\x97NV\rdoghouse\xd7p\r";
+        let pools = parse(text, |_| true);
+        let pool = |pos| &pools.words[pool_index(pos)];
+        assert_eq!(pool(PartOfSpeech::Noun)["dimple"], 2);
+        assert_eq!(pool(PartOfSpeech::Verb)["dimple"], 1);
+        assert!(pool(PartOfSpeech::Adverb).contains_key("quickly"));
+        assert!(pool(PartOfSpeech::Verb).contains_key("colour"));
+        assert!(!pool(PartOfSpeech::Noun).contains_key("doghouse"));
+        assert_eq!(pools.proper_nouns, HashSet::from(["aaron".to_string()]));
+    }
+}
@@ -0,0 +1,58 @@
+//! Random character perturbation of finished phrases.
+//!
+//! Each mutation picks a distinct position in the phrase and either inserts a
+//! random character before it, swaps its case, or replaces it with a random
+//! character. Only the choice of positions is credited to the entropy
+//! estimate; the operations and characters are a bonus.
+
+use rand::seq::index;
+use rand::Rng;
+use unicode_segmentation::UnicodeSegmentation;
+
+const MUTATION_CHARSET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*-_=+?";
+
+fn random_char(rng: &mut impl Rng) -> char {
+    MUTATION_CHARSET[rng.random_range(0..MUTATION_CHARSET.len())] as char
+}
+
+fn swap_case(c: char) -> char {
+    if c.is_lowercase() {
+        c.to_ascii_uppercase()
+    } else {
+        c.to_ascii_lowercase()
+    }
+}
+
+/// Applies `count` mutations at distinct random positions of `text`.
+pub fn apply(text: &str, count: usize, rng: &mut impl Rng) -> String {
+    // Positions are graphemes, so an emoji is never split into stray code points.
+    let mut graphemes: Vec<String> = text.graphemes(true).map(str::to_string).collect();
+    let mut positions = index::sample(rng, graphemes.len(), count.min(graphemes.len())).into_vec();
+    // Work from the end so insertions don't shift positions still to come.
+    positions.sort_unstable_by(|a, b| b.cmp(a));
+
+    for position in positions {
+        let letter = graphemes[position]
+            .chars()
+            .next()
+            .filter(|c| c.is_ascii_alphabetic() && graphemes[position].len() == 1);
+        match (rng.random_range(0..3), letter) {
+            (0, _) => graphemes.insert(position, random_char(rng).to_string()),
+            (1, Some(c)) => graphemes[position] = swap_case(c).to_string(),
+            _ => graphemes[position] = random_char(rng).to_string(),
+        }
+    }
+    graphemes.concat()
+}
+
+/// Bits contributed by choosing `count` distinct positions out of
+/// `min_length`: log2 of the binomial coefficient.
+pub fn position_bits(min_length: usize, count: usize) -> f64 {
+    if count > min_length {
+        return 0.0;
+    }
+    (0..count)
+        .map(|i| ((min_length - i) as f64 / (i + 1) as f64).log2())
+        .sum()
+}
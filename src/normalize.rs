@@ -0,0 +1,76 @@
+//! One spelling per word, whatever language or source it came from.
+//!
+//! Words are stored lowercased and in Unicode Normalization Form C, so "café"
+//! typed with a combining accent and "café" read from a Latin-1 list are the
+//! same word when pools are filtered, deduplicated and joined. Comparisons
+//! that are about letters rather than spellings, alliteration, acrostics and
+//! sorting, look through diacritics: "élan" starts with an e and sorts among
+//! the e words, not after z.
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// `text` lowercased and composed (NFC), as word lists store it.
+pub fn word(text: &str) -> String {
+    text.nfc().collect::<String>().to_lowercase()
+}
+
+/// Whether `text` is made of letters alone, allowing the combining accents
+/// some letters have no composed form without.
+pub fn is_word(text: &str) -> bool {
+    text.chars().next().is_some_and(char::is_alphabetic)
+        && text
+            .chars()
+            .all(|c| c.is_alphabetic() || is_combining_mark(c))
+}
+
+/// Letters in `text`, a character count rather than a byte count.
+pub fn letters(text: &str) -> usize {
+    text.nfc().filter(|&c| !is_combining_mark(c)).count()
+}
+
+/// `text` lowercased with its diacritics removed: "Ärger" becomes "arger".
+/// Letters that don't decompose, like ß or ø, are kept as they are.
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// The letter `text` starts with, lowercased and without its diacritics.
+pub fn initial(text: &str) -> Option<char> {
+    let first = text.chars().next()?;
+    if first.is_ascii() {
+        return Some(first.to_ascii_lowercase());
+    }
+    fold(&first.to_string()).chars().next()
+}
+
+/// Orders words as a dictionary would: by their letters first, so accented
+/// words sit among their unaccented neighbors, then by their exact spelling
+/// to keep the order total.
+pub fn collate(a: &str, b: &str) -> std::cmp::Ordering {
+    fold(a).cmp(&fold(b)).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accented_words_are_one_spelling_and_sort_by_their_letters() {
+        let decomposed = "cafe\u{301}";
+        assert_eq!(word(decomposed), "café");
+        assert_eq!(word("STRAßE"), "straße");
+        assert!(is_word(decomposed) && is_word("straße"));
+        assert!(!is_word("\u{301}a") && !is_word("don't"));
+        assert_eq!(letters(decomposed), 4);
+        assert_eq!(initial("élan"), Some('e'));
+        assert_eq!(initial("Ørsted"), Some('ø'));
+
+        let mut words = vec!["zebra", "élan", "eagle", "ember"];
+        words.sort_by(|a, b| collate(a, b));
+        assert_eq!(words, ["eagle", "élan", "ember", "zebra"]);
+    }
+}
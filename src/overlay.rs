@@ -0,0 +1,141 @@
+//! The user's word overlay (`phraseforge words`).
+//!
+//! Words added or removed by hand are kept in `words.toml` in the data
+//! directory, outside the versioned cache, so they survive re-downloads and
+//! cache upgrades. The overlay is applied as the lists are loaded, before any
+//! filter: removed words are dropped, and added words are always eligible
+//! whatever `--min-frequency` says.
+//!
+//! ```toml
+//! [add]
+//! noun = ["otter"]
+//!
+//! [remove]
+//! adjective = ["moist"]
+//! ```
+
+use crate::update::write_atomically;
+use crate::{PartOfSpeech, WordEntry, WordLists};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+pub const OVERLAY_FILE: &str = "words.toml";
+
+/// Part of speech name to words.
+type Words = BTreeMap<String, BTreeSet<String>>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overlay {
+    #[serde(default, skip_serializing_if = "Words::is_empty")]
+    add: Words,
+    #[serde(default, skip_serializing_if = "Words::is_empty")]
+    remove: Words,
+}
+
+/// `word` as word lists store it, or why it can't go in one.
+fn normalize(word: &str) -> Result<String, String> {
+    if !crate::normalize::is_word(word) {
+        return Err(format!("'{}' is not a single word of letters", word));
+    }
+    Ok(crate::normalize::word(word))
+}
+
+impl Overlay {
+    /// Reads the overlay in `dir`; empty if there isn't one yet.
+    pub fn load(dir: &Path) -> Result<Overlay, String> {
+        let path = dir.join(OVERLAY_FILE);
+        if !path.exists() {
+            return Ok(Overlay::default());
+        }
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let overlay: Overlay = toml::from_str(&text)
+            .map_err(|e| format!("invalid word overlay {}: {}", path.display(), e))?;
+        for name in overlay.add.keys().chain(overlay.remove.keys()) {
+            if PartOfSpeech::from_name(name).is_none() {
+                return Err(format!(
+                    "invalid word overlay {}: unknown part of speech '{}'",
+                    path.display(),
+                    name
+                ));
+            }
+        }
+        Ok(overlay)
+    }
+
+    pub fn save(&self, dir: &Path) {
+        let text = toml::to_string(self).expect("Overlay serializes");
+        write_atomically(&dir.join(OVERLAY_FILE), text.as_bytes());
+    }
+
+    /// Adds `word` to the `pos` pool, undoing any earlier removal.
+    pub fn add(&mut self, pos: PartOfSpeech, word: &str) -> Result<(), String> {
+        let word = normalize(word)?;
+        if let Some(removed) = self.remove.get_mut(pos.name()) {
+            removed.remove(&word);
+        }
+        self.remove.retain(|_, words| !words.is_empty());
+        self.add
+            .entry(pos.name().to_string())
+            .or_default()
+            .insert(word);
+        Ok(())
+    }
+
+    /// Drops `word` from the `pos` pool, undoing any earlier addition.
+    pub fn remove(&mut self, pos: PartOfSpeech, word: &str) -> Result<(), String> {
+        let word = normalize(word)?;
+        if let Some(added) = self.add.get_mut(pos.name()) {
+            added.remove(&word);
+        }
+        self.add.retain(|_, words| !words.is_empty());
+        self.remove
+            .entry(pos.name().to_string())
+            .or_default()
+            .insert(word);
+        Ok(())
+    }
+
+    pub fn is_added(&self, pos: PartOfSpeech, word: &str) -> bool {
+        self.add
+            .get(pos.name())
+            .is_some_and(|words| words.contains(word))
+    }
+
+    pub fn apply(&self, word_lists: &mut WordLists) {
+        for pool in word_lists.pools_mut() {
+            let pos = pool.part_of_speech();
+            let entries = pool.entries_mut();
+            if let Some(removed) = self.remove.get(pos.name()) {
+                entries.retain(|entry| !removed.contains(&entry.word));
+            }
+            for word in self.add.get(pos.name()).into_iter().flatten() {
+                match entries.iter_mut().find(|entry| entry.word == *word) {
+                    Some(entry) => entry.frequency = u32::MAX,
+                    None => entries.push(WordEntry {
+                        word: word.clone(),
+                        frequency: u32::MAX,
+                    }),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove_undo_each_other() {
+        let mut overlay = Overlay::default();
+        overlay.remove(PartOfSpeech::Noun, "Otter").unwrap();
+        overlay.add(PartOfSpeech::Noun, "otter").unwrap();
+        assert!(overlay.is_added(PartOfSpeech::Noun, "otter"));
+        assert!(overlay.remove.is_empty());
+        assert!(overlay.add(PartOfSpeech::Noun, "sea otter").is_err());
+    }
+}
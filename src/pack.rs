@@ -0,0 +1,323 @@
+//! Word packs (`phraseforge pack`, `--pack NAME`).
+//!
+//! A pack is a directory, or a gzipped tarball of one, holding word lists in
+//! the generated lists' format and names (`nouns.txt` and so on, one `word
+//! frequency` pair per line) and a `pack.toml` naming the pack and listing
+//! each file's SHA-256:
+//!
+//! ```toml
+//! name = "space"
+//! version = "1.0"
+//! description = "Stars, planets and the craft that visit them"
+//!
+//! [files]
+//! "nouns.txt" = "5e0b..."
+//! ```
+//!
+//! `pack install` checks every file against its checksum and the word list
+//! format before the pack goes in `packs/NAME` in the data directory,
+//! outside the versioned cache. `--pack NAME` then stands the pack's lists
+//! in for the parts of speech it has; the others come from the usual lists,
+//! and the overlay and filters apply on top as ever. The checksums catch a
+//! damaged download, not a malicious author: install packs you trust.
+
+use crate::sources::Sources;
+use crate::transcript::sha256_file;
+use crate::{download, load_word_list, staging_dir, update, validate_word_list};
+use crate::{PartOfSpeech, WordEntry, WordLists};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "pack.toml";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// Word list file name to SHA-256.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Where installed packs live, under the data directory `root`.
+pub fn packs_dir(root: &Path) -> PathBuf {
+    root.join("packs")
+}
+
+/// Pack names become directory names, so they keep to a safe alphabet.
+fn check_name(name: &str) -> Result<(), String> {
+    let safe = !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if safe {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid pack name '{}': use lowercase letters, digits, '-' and '_'",
+            name
+        ))
+    }
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, String> {
+    let path = dir.join(MANIFEST_FILE);
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let manifest: Manifest =
+        toml::from_str(&text).map_err(|e| format!("invalid {}: {}", path.display(), e))?;
+    check_name(&manifest.name)?;
+    Ok(manifest)
+}
+
+/// The part of speech whose list a pack file holds, by the file's name.
+fn file_part_of_speech(file: &str) -> Option<PartOfSpeech> {
+    PartOfSpeech::ALL
+        .into_iter()
+        .find(|pos| pos.word_list_file() == file)
+}
+
+/// Checks the pack in `dir`: a valid manifest, and every file it lists a
+/// word list with the listed checksum.
+pub fn verify(dir: &Path) -> Result<Manifest, String> {
+    let manifest = read_manifest(dir)?;
+    if manifest.files.is_empty() {
+        return Err(format!("pack '{}' lists no word lists", manifest.name));
+    }
+    for (file, expected) in &manifest.files {
+        if file_part_of_speech(file).is_none() {
+            return Err(format!(
+                "pack '{}' lists {}, which is not a word list name; expected {}",
+                manifest.name,
+                file,
+                PartOfSpeech::ALL.map(|pos| pos.word_list_file()).join(", ")
+            ));
+        }
+        let path = dir.join(file);
+        let actual =
+            sha256_file(&path).ok_or_else(|| format!("pack '{}' lacks {}", manifest.name, file))?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            return Err(format!(
+                "pack '{}': {} has SHA-256 {}, but pack.toml lists {}",
+                manifest.name, file, actual, expected
+            ));
+        }
+        validate_word_list(&path, 1).map_err(|e| format!("pack '{}': {}", manifest.name, e))?;
+    }
+    Ok(manifest)
+}
+
+/// The directory in an unpacked archive holding the manifest: the top
+/// level, or the one directory the archive was made from.
+fn unpacked_root(staging: &Path) -> Option<PathBuf> {
+    if staging.join(MANIFEST_FILE).exists() {
+        return Some(staging.to_path_buf());
+    }
+    let mut entries = fs::read_dir(staging).ok()?.flatten();
+    let only = entries.next()?.path();
+    (entries.next().is_none() && only.join(MANIFEST_FILE).exists()).then_some(only)
+}
+
+/// Installs the pack at `source`, a URL or a local directory or tarball,
+/// under `root`, replacing any installed pack of the same name.
+pub fn install(source: &str, root: &Path, sources: &Sources) -> Result<Manifest, String> {
+    let packs = packs_dir(root);
+    let staging = staging_dir(&packs);
+    let unpack = |archive: &mut dyn std::io::Read| {
+        download::unpack(archive, &staging, |_| true)
+            .map_err(|e| format!("cannot unpack {}: {}", source, e))
+    };
+    let dir = if source.starts_with("https://") || source.starts_with("http://") {
+        log::info!("Downloading {}...", source);
+        let bytes = sources
+            .get(source)
+            .and_then(|response| response.bytes())
+            .map_err(|e| format!("cannot download {}: {}", source, e))?;
+        unpack(&mut Cursor::new(bytes))?;
+        unpacked_root(&staging)
+    } else if Path::new(source).is_dir() {
+        let manifest = read_manifest(Path::new(source))?;
+        for file in manifest
+            .files
+            .keys()
+            .map(String::as_str)
+            .chain([MANIFEST_FILE])
+        {
+            if file_part_of_speech(file).is_some() || file == MANIFEST_FILE {
+                fs::copy(Path::new(source).join(file), staging.join(file))
+                    .map_err(|e| format!("cannot copy {} from {}: {}", file, source, e))?;
+            }
+        }
+        Some(staging.clone())
+    } else {
+        let mut archive =
+            File::open(source).map_err(|e| format!("cannot open {}: {}", source, e))?;
+        unpack(&mut archive)?;
+        unpacked_root(&staging)
+    }
+    .ok_or_else(|| format!("{} has no {}", source, MANIFEST_FILE))?;
+
+    let manifest = verify(&dir)?;
+    let installed = packs.join(&manifest.name);
+    if installed.exists() {
+        fs::remove_dir_all(&installed)
+            .map_err(|e| format!("cannot replace {}: {}", installed.display(), e))?;
+    }
+    update::rename(&dir, &installed)
+        .map_err(|e| format!("cannot install {}: {}", installed.display(), e))?;
+    let _ = fs::remove_dir_all(&staging);
+    Ok(manifest)
+}
+
+/// The installed packs, by name.
+pub fn installed(root: &Path) -> Vec<Result<Manifest, String>> {
+    let Ok(entries) = fs::read_dir(packs_dir(root)) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            !path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .starts_with('.')
+        })
+        .collect();
+    dirs.sort();
+    dirs.iter().map(|dir| read_manifest(dir)).collect()
+}
+
+pub fn remove(root: &Path, name: &str) -> Result<(), String> {
+    check_name(name)?;
+    let dir = packs_dir(root).join(name);
+    if !dir.exists() {
+        return Err(format!("no pack named '{}' is installed", name));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| format!("cannot remove {}: {}", dir.display(), e))
+}
+
+/// An installed pack's word lists.
+#[derive(Debug)]
+pub struct Pack {
+    pub name: String,
+    lists: Vec<(PartOfSpeech, Vec<WordEntry>)>,
+}
+
+impl Pack {
+    /// Loads the pack `name`, checking it again in case it changed since it
+    /// was installed.
+    pub fn load(root: &Path, name: &str) -> Result<Pack, String> {
+        check_name(name)?;
+        let dir = packs_dir(root).join(name);
+        if !dir.exists() {
+            return Err(format!(
+                "no pack named '{}' is installed; see `phraseforge pack list`",
+                name
+            ));
+        }
+        let manifest = verify(&dir)?;
+        let lists = manifest
+            .files
+            .keys()
+            .filter_map(|file| Some((file_part_of_speech(file)?, load_word_list(&dir.join(file)))))
+            .collect();
+        Ok(Pack {
+            name: manifest.name,
+            lists,
+        })
+    }
+
+    /// The parts of speech the pack has lists for.
+    pub fn parts_of_speech(&self) -> HashSet<PartOfSpeech> {
+        self.lists.iter().map(|(pos, _)| *pos).collect()
+    }
+
+    /// Replaces the pools the pack has lists for with them.
+    pub fn apply(&self, word_lists: &mut WordLists) {
+        for pool in word_lists.pools_mut() {
+            if let Some((_, entries)) = self
+                .lists
+                .iter()
+                .find(|(pos, _)| *pos == pool.part_of_speech())
+            {
+                *pool.entries_mut() = entries.to_vec();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provenance::sha256_hex;
+    use crate::WordType;
+
+    #[test]
+    fn installs_checks_and_applies_a_pack() {
+        let dir = std::env::temp_dir().join(format!("phraseforge-pack-{}", std::process::id()));
+        let source = dir.join("space");
+        let root = dir.join("data");
+        fs::create_dir_all(&source).unwrap();
+        let nouns = "comet 900\nnebula 400\n";
+        fs::write(source.join("nouns.txt"), nouns).unwrap();
+        let manifest = format!(
+            "name = \"space\"\nversion = \"1.0\"\n\n[files]\n\"nouns.txt\" = \"{}\"\n",
+            sha256_hex(nouns.as_bytes())
+        );
+        fs::write(source.join(MANIFEST_FILE), manifest).unwrap();
+        let sources = Sources::new(
+            &crate::cli().get_matches_from(["phraseforge"]),
+            &Default::default(),
+        )
+        .unwrap();
+
+        let installed_pack = install(source.to_str().unwrap(), &root, &sources).unwrap();
+        assert_eq!(installed_pack.name, "space");
+        let names: Vec<String> = installed(&root)
+            .into_iter()
+            .map(|manifest| manifest.unwrap().name)
+            .collect();
+        assert_eq!(names, ["space"]);
+
+        let pack = Pack::load(&root, "space").unwrap();
+        assert_eq!(pack.parts_of_speech(), HashSet::from([PartOfSpeech::Noun]));
+        let mut word_lists = WordLists {
+            adjectives: WordType::Adjective(vec![WordEntry {
+                word: "bright".to_string(),
+                frequency: 500,
+            }]),
+            nouns: WordType::Noun(vec![WordEntry {
+                word: "otter".to_string(),
+                frequency: 700,
+            }]),
+            verbs: WordType::Verb(Vec::new()),
+            adverbs: WordType::Adverb(Vec::new()),
+        };
+        pack.apply(&mut word_lists);
+        let words = |pos| -> Vec<&str> {
+            word_lists
+                .entries_of(pos)
+                .iter()
+                .map(|entry| entry.word.as_str())
+                .collect()
+        };
+        assert_eq!(words(PartOfSpeech::Noun), ["comet", "nebula"]);
+        assert_eq!(words(PartOfSpeech::Adjective), ["bright"]);
+
+        fs::write(packs_dir(&root).join("space/nouns.txt"), "comet 900\n").unwrap();
+        assert!(Pack::load(&root, "space").unwrap_err().contains("SHA-256"));
+        assert!(remove(&root, "../data").is_err());
+        remove(&root, "space").unwrap();
+        assert!(installed(&root).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+//! Metaphone codes, for `--distinct-sounds`.
+//!
+//! Lawrence Philips' original Metaphone reduces a word to the consonant
+//! sounds it is spoken with, so words that sound alike share a code: "bare"
+//! and "bear" are both `BR`, "knight" and "night" both `NT`. Two words of a
+//! phrase with the same code are the pair a listener mishears or a memory
+//! swaps, so `--distinct-sounds` keeps them apart. Letters outside a-z,
+//! once diacritics are removed, are ignored.
+
+use crate::normalize;
+use std::collections::HashMap;
+
+/// Beginnings whose first letter is silent, as in "knight" and "wrist".
+const SILENT_FIRST: [&[u8]; 5] = [b"ae", b"gn", b"kn", b"pn", b"wr"];
+
+fn is_vowel(c: Option<&u8>) -> bool {
+    matches!(c, Some(b'a' | b'e' | b'i' | b'o' | b'u'))
+}
+
+/// The Metaphone code of `word`, in capitals, with `0` for "th".
+pub fn metaphone(word: &str) -> String {
+    let folded = normalize::fold(word);
+    let mut letters: Vec<u8> = folded.bytes().filter(u8::is_ascii_lowercase).collect();
+    if SILENT_FIRST
+        .iter()
+        .any(|beginning| letters.starts_with(beginning))
+    {
+        letters.remove(0);
+    } else if letters.starts_with(b"x") {
+        letters[0] = b's';
+    } else if letters.starts_with(b"wh") {
+        letters.remove(1);
+    }
+
+    let mut code = String::new();
+    for (i, &c) in letters.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|j| letters[j]);
+        let next = letters.get(i + 1);
+        let after = letters.get(i + 2);
+        // Doubled letters sound once, except "cc" as in "accent".
+        if prev == Some(c) && c != b'c' {
+            continue;
+        }
+        match c {
+            b'a' | b'e' | b'i' | b'o' | b'u' => {
+                if i == 0 {
+                    code.push(c.to_ascii_uppercase() as char);
+                }
+            }
+            // "dumb", "climb"
+            b'b' if prev == Some(b'm') && next.is_none() => {}
+            b'c' => {
+                if (next == Some(&b'i') && after == Some(&b'a')) || next == Some(&b'h') {
+                    // "sch" is hard, as in "school".
+                    code.push(if prev == Some(b's') { 'K' } else { 'X' });
+                } else if matches!(next, Some(b'i' | b'e' | b'y')) {
+                    // Silent in "science", "scene".
+                    if prev != Some(b's') {
+                        code.push('S');
+                    }
+                } else {
+                    code.push('K');
+                }
+            }
+            b'd' => {
+                if next == Some(&b'g') && matches!(after, Some(b'e' | b'i' | b'y')) {
+                    code.push('J');
+                } else {
+                    code.push('T');
+                }
+            }
+            b'g' => {
+                if next == Some(&b'h') && !(after.is_none() || is_vowel(after)) {
+                    // "night", "light"
+                } else if next == Some(&b'n') && (after.is_none() || &letters[i + 1..] == b"ned") {
+                    // "sign", "signed"
+                } else if prev == Some(b'd') && matches!(next, Some(b'e' | b'i' | b'y')) {
+                    // Heard in the "d" of "badge".
+                } else if matches!(next, Some(b'i' | b'e' | b'y')) && prev != Some(b'g') {
+                    code.push('J');
+                } else {
+                    code.push('K');
+                }
+            }
+            b'h' => {
+                let after_modifier = matches!(prev, Some(b'c' | b's' | b'p' | b't' | b'g'));
+                if !after_modifier && is_vowel(next) {
+                    code.push('H');
+                }
+            }
+            b'k' if prev == Some(b'c') => {}
+            b'p' => code.push(if next == Some(&b'h') { 'F' } else { 'P' }),
+            b'q' => code.push('K'),
+            b's' => {
+                let sh = next == Some(&b'h')
+                    || (next == Some(&b'i') && matches!(after, Some(b'o' | b'a')));
+                code.push(if sh { 'X' } else { 'S' });
+            }
+            b't' => {
+                if next == Some(&b'i') && matches!(after, Some(b'o' | b'a')) {
+                    code.push('X');
+                } else if next == Some(&b'h') {
+                    code.push('0');
+                } else if !(next == Some(&b'c') && after == Some(&b'h')) {
+                    code.push('T');
+                }
+            }
+            b'v' => code.push('F'),
+            b'w' | b'y' => {
+                if is_vowel(next) {
+                    code.push(c.to_ascii_uppercase() as char);
+                }
+            }
+            b'x' => code.push_str("KS"),
+            b'z' => code.push('S'),
+            _ => code.push(c.to_ascii_uppercase() as char),
+        }
+    }
+    code
+}
+
+/// How many of `words` share the most common code, which is how many words
+/// one earlier word of a phrase can rule out under `--distinct-sounds`.
+pub fn largest_group<'a>(words: impl Iterator<Item = &'a str>) -> usize {
+    let mut groups: HashMap<String, usize> = HashMap::new();
+    for word in words {
+        *groups.entry(metaphone(word)).or_default() += 1;
+    }
+    groups.into_values().max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_that_sound_alike_share_a_code() {
+        for (a, b) in [
+            ("bare", "bear"),
+            ("knight", "night"),
+            ("phone", "fone"),
+            ("write", "right"),
+            ("plane", "plain"),
+            ("sign", "sine"),
+        ] {
+            assert_eq!(metaphone(a), metaphone(b), "{} and {}", a, b);
+        }
+        assert_eq!(metaphone("thumb"), "0M");
+        assert_eq!(metaphone("school"), "SKL");
+        assert_eq!(metaphone("café"), "KF");
+        assert_eq!(metaphone("badger"), "BJR");
+        assert_ne!(metaphone("otter"), metaphone("badger"));
+        assert_eq!(largest_group(["bare", "bear", "otter"].into_iter()), 2);
+    }
+}
@@ -0,0 +1,111 @@
+//! Numeric PINs (`phraseforge pin`).
+//!
+//! PINs are drawn uniformly and redrawn if they look like something a person
+//! would choose: a repeated digit or block, a run of consecutive digits, or a
+//! year.
+
+use crate::entropy::{slot_entropy, Entropy};
+use rand::Rng;
+
+/// Largest PIN length whose valid PINs are counted exactly; longer lengths
+/// use a conservative bound.
+const EXACT_COUNT_MAX_DIGITS: u32 = 6;
+
+fn is_year(digits: &[u8]) -> bool {
+    let year = digits.iter().fold(0u32, |acc, d| acc * 10 + u32::from(*d));
+    (1900..=2099).contains(&year)
+}
+
+/// Why a PIN was rejected, or `None` if it is acceptable.
+pub fn weakness(pin: &str) -> Option<&'static str> {
+    let digits: Vec<u8> = pin.bytes().map(|b| b - b'0').collect();
+    let n = digits.len();
+
+    if digits.windows(3).any(|w| w[0] == w[1] && w[1] == w[2]) {
+        return Some("run of three identical digits");
+    }
+    if (1..n).any(|period| {
+        n.is_multiple_of(period) && (period..n).all(|i| digits[i] == digits[i - period])
+    }) {
+        return Some("repeated block");
+    }
+    let steps: Vec<i8> = digits.windows(2).map(|w| w[1] as i8 - w[0] as i8).collect();
+    if steps.iter().all(|&s| s == 1) || steps.iter().all(|&s| s == -1) {
+        return Some("sequence");
+    }
+    if (n == 4 && is_year(&digits))
+        || (n >= 6 && (is_year(&digits[..4]) || is_year(&digits[n - 4..])))
+    {
+        return Some("year");
+    }
+    None
+}
+
+pub fn generate(digits: u32, rng: &mut impl Rng) -> String {
+    loop {
+        let pin: String = (0..digits)
+            .map(|_| char::from(b'0' + rng.random_range(0..10u8)))
+            .collect();
+        match weakness(&pin) {
+            None => return pin,
+            Some(reason) => log::debug!("Rejected weak PIN ({})", reason),
+        }
+    }
+}
+
+/// Upper bound on the number of weak PINs of a given length, used when
+/// counting them exactly would be too slow.
+fn weak_upper_bound(digits: u32) -> f64 {
+    let d = digits as i32;
+    let runs = f64::from(digits - 2) * 10f64.powi(d - 2);
+    let blocks: f64 = (1..d).filter(|p| d % p == 0).map(|p| 10f64.powi(p)).sum();
+    let sequences = 14.0;
+    let years = 2.0 * 200.0 * 10f64.powi(d - 4);
+    runs + blocks + sequences + years
+}
+
+/// Number of PINs that pass the weakness checks; exact for short PINs and a
+/// lower bound otherwise.
+pub fn valid_count(digits: u32) -> f64 {
+    if digits <= EXACT_COUNT_MAX_DIGITS {
+        let width = digits as usize;
+        (0..10u64.pow(digits))
+            .filter(|n| weakness(&format!("{:0width$}", n, width = width)).is_none())
+            .count() as f64
+    } else {
+        10f64.powi(digits as i32) - weak_upper_bound(digits)
+    }
+}
+
+/// Each PIN is uniform over the PINs that pass the checks.
+pub fn entropy(digits: u32) -> Entropy {
+    let valid = valid_count(digits);
+    let mut slot = slot_entropy("pin", valid as usize);
+    slot.bits = valid.log2();
+    Entropy {
+        bits_per_phrase: slot.bits,
+        slots: vec![slot],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_weak_pins() {
+        assert_eq!(weakness("0000"), Some("run of three identical digits"));
+        assert_eq!(weakness("1212"), Some("repeated block"));
+        assert_eq!(weakness("123123"), Some("repeated block"));
+        assert_eq!(weakness("3456"), Some("sequence"));
+        assert_eq!(weakness("9876"), Some("sequence"));
+        assert_eq!(weakness("1987"), Some("year"));
+        assert_eq!(weakness("041985"), Some("year"));
+    }
+
+    #[test]
+    fn accepts_ordinary_pins() {
+        assert_eq!(weakness("4719"), None);
+        assert_eq!(weakness("830472"), None);
+    }
+}
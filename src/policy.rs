@@ -0,0 +1,325 @@
+//! Password policies (`--policy`).
+//!
+//! Every emitted phrase satisfies the selected policy. Missing character
+//! classes are added by upper-casing a letter or inserting a random character,
+//! which never removes entropy; phrases that still fail (too short, a banned
+//! substring, too many repeated characters) are regenerated.
+//!
+//! Besides the presets, named policies can be declared in the config file:
+//!
+//! ```toml
+//! [policy.corp]
+//! min_length = 16
+//! required_classes = ["upper", "digit"]
+//! banned_substrings = ["acme", "password"]
+//! max_consecutive_identical = 2
+//! ```
+
+use rand::Rng;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+    /// Any letter, either case; only used as a requirement.
+    Letter,
+}
+
+const SYMBOLS: &[u8] = b"!@#$%^&*-_=+?";
+
+impl CharClass {
+    /// In the order they are added when a policy needs more classes.
+    pub const ALL: [CharClass; 4] = [
+        CharClass::Upper,
+        CharClass::Digit,
+        CharClass::Symbol,
+        CharClass::Lower,
+    ];
+
+    pub const NAMES: [&'static str; 5] = ["lower", "upper", "digit", "symbol", "letter"];
+
+    pub fn from_name(name: &str) -> Option<CharClass> {
+        match name {
+            "lower" => Some(CharClass::Lower),
+            "upper" => Some(CharClass::Upper),
+            "digit" => Some(CharClass::Digit),
+            "symbol" => Some(CharClass::Symbol),
+            "letter" => Some(CharClass::Letter),
+            _ => None,
+        }
+    }
+
+    pub fn of(c: char) -> CharClass {
+        if c.is_lowercase() {
+            CharClass::Lower
+        } else if c.is_uppercase() {
+            CharClass::Upper
+        } else if c.is_ascii_digit() {
+            CharClass::Digit
+        } else {
+            CharClass::Symbol
+        }
+    }
+
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Letter => c.is_alphabetic(),
+            class => CharClass::of(c) == *class,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CharClass::Letter => "letter",
+            CharClass::Lower => "lowercase letter",
+            CharClass::Upper => "uppercase letter",
+            CharClass::Digit => "digit",
+            CharClass::Symbol => "symbol",
+        }
+    }
+
+    fn random_char(&self, rng: &mut impl Rng) -> char {
+        match self {
+            CharClass::Lower | CharClass::Letter => rng.random_range(b'a'..=b'z') as char,
+            CharClass::Upper => rng.random_range(b'A'..=b'Z') as char,
+            CharClass::Digit => rng.random_range(b'0'..=b'9') as char,
+            CharClass::Symbol => SYMBOLS[rng.random_range(0..SYMBOLS.len())] as char,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub min_length: usize,
+    /// Classes that must all appear.
+    pub required_classes: Vec<CharClass>,
+    /// How many distinct classes must appear, e.g. three of four for Active
+    /// Directory.
+    pub min_classes: usize,
+    /// Matched case-insensitively.
+    pub banned_substrings: Vec<String>,
+    /// Longest allowed run of one repeated character.
+    pub max_consecutive_identical: Option<usize>,
+    /// Phrases generated before giving up on the policy.
+    pub max_attempts: usize,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            min_length: 0,
+            required_classes: Vec::new(),
+            min_classes: 0,
+            banned_substrings: Vec::new(),
+            max_consecutive_identical: None,
+            max_attempts: 1000,
+        }
+    }
+}
+
+/// One way a phrase failed a policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// The policy setting that was violated, e.g. `min_length`.
+    pub rule: &'static str,
+    pub detail: String,
+}
+
+fn longest_run(text: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous = None;
+    for grapheme in text.graphemes(true) {
+        run = if Some(grapheme) == previous {
+            run + 1
+        } else {
+            1
+        };
+        longest = longest.max(run);
+        previous = Some(grapheme);
+    }
+    longest
+}
+
+impl Policy {
+    pub const PRESET_NAMES: [&'static str; 3] = ["nist", "ad-complex", "pci"];
+
+    pub fn preset(name: &str) -> Option<Policy> {
+        match name {
+            // NIST SP 800-63B: length only, composition rules discouraged.
+            "nist" => Some(Policy {
+                min_length: 8,
+                ..Policy::default()
+            }),
+            // Active Directory "passwords must meet complexity requirements".
+            "ad-complex" => Some(Policy {
+                min_length: 8,
+                min_classes: 3,
+                ..Policy::default()
+            }),
+            // PCI DSS 4.0 requirement 8.3.6.
+            "pci" => Some(Policy {
+                min_length: 12,
+                required_classes: vec![CharClass::Letter, CharClass::Digit],
+                ..Policy::default()
+            }),
+            _ => None,
+        }
+    }
+
+    fn classes_in(text: &str) -> Vec<CharClass> {
+        let mut classes: Vec<CharClass> = Vec::new();
+        for class in text.chars().map(CharClass::of) {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        classes
+    }
+
+    /// Why `text` fails the policy; empty when it complies.
+    pub fn problems(&self, text: &str) -> Vec<Violation> {
+        let mut problems = Vec::new();
+        let mut violate = |rule, detail| problems.push(Violation { rule, detail });
+
+        // Graphemes, so an emoji separator counts as the one character a
+        // user sees rather than the several code points it may encode to.
+        let length = text.graphemes(true).count();
+        if length < self.min_length {
+            violate(
+                "min_length",
+                format!(
+                    "{} characters, at least {} required",
+                    length, self.min_length
+                ),
+            );
+        }
+        for class in &self.required_classes {
+            if !text.chars().any(|c| class.matches(c)) {
+                violate("required_classes", format!("no {}", class.name()));
+            }
+        }
+        let present = Self::classes_in(text).len();
+        if present < self.min_classes {
+            violate(
+                "min_classes",
+                format!(
+                    "{} character classes, at least {} required",
+                    present, self.min_classes
+                ),
+            );
+        }
+        let lowercase = text.to_lowercase();
+        for banned in &self.banned_substrings {
+            if lowercase.contains(&banned.to_lowercase()) {
+                violate("banned_substrings", format!("contains '{}'", banned));
+            }
+        }
+        if let Some(max) = self.max_consecutive_identical {
+            let run = longest_run(text);
+            if run > max {
+                violate(
+                    "max_consecutive_identical",
+                    format!(
+                        "{} identical characters in a row, at most {} allowed",
+                        run, max
+                    ),
+                );
+            }
+        }
+        problems
+    }
+
+    fn add_class(graphemes: &mut Vec<String>, class: CharClass, rng: &mut impl Rng) {
+        // Capitalizing an existing letter keeps the phrase readable.
+        if class == CharClass::Upper {
+            let letters: Vec<usize> = (0..graphemes.len())
+                .filter(|&i| graphemes[i].chars().all(|c| c.is_ascii_lowercase()))
+                .collect();
+            if !letters.is_empty() {
+                let i = letters[rng.random_range(0..letters.len())];
+                graphemes[i].make_ascii_uppercase();
+                return;
+            }
+        }
+        // Insert between graphemes so a multi-code-point emoji stays whole.
+        let position = rng.random_range(0..=graphemes.len());
+        graphemes.insert(position, class.random_char(rng).to_string());
+    }
+
+    /// Adds whatever character classes `text` is missing. The result may
+    /// still be too short, in which case the caller should regenerate.
+    pub fn repair(&self, text: &str, rng: &mut impl Rng) -> String {
+        let mut graphemes: Vec<String> = text.graphemes(true).map(str::to_string).collect();
+        for class in &self.required_classes {
+            if !graphemes.concat().chars().any(|c| class.matches(c)) {
+                Self::add_class(&mut graphemes, *class, rng);
+            }
+        }
+        for class in CharClass::ALL {
+            let present = graphemes.concat();
+            if Self::classes_in(&present).len() >= self.min_classes {
+                break;
+            }
+            if !present.chars().any(|c| class.matches(c)) {
+                Self::add_class(&mut graphemes, class, rng);
+            }
+        }
+        graphemes.concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_satisfies_class_requirements() {
+        let mut rng = rand::rng();
+        let ad = Policy::preset("ad-complex").unwrap();
+        assert!(!ad.problems("quiet-otters").is_empty());
+        assert!(ad.problems(&ad.repair("quiet-otters", &mut rng)).is_empty());
+
+        let pci = Policy::preset("pci").unwrap();
+        assert!(pci
+            .problems(&pci.repair("quiet-otters", &mut rng))
+            .is_empty());
+        assert!(!pci.problems(&pci.repair("otters", &mut rng)).is_empty());
+    }
+
+    #[test]
+    fn reports_custom_rules() {
+        let policy = Policy {
+            banned_substrings: vec!["Otter".to_string()],
+            max_consecutive_identical: Some(1),
+            ..Policy::default()
+        };
+        let rules: Vec<&str> = policy
+            .problems("quiet-otters")
+            .iter()
+            .map(|v| v.rule)
+            .collect();
+        assert_eq!(
+            rules,
+            vec!["banned_substrings", "max_consecutive_identical"]
+        );
+    }
+
+    #[test]
+    fn counts_emoji_as_one_character() {
+        let policy = Policy {
+            min_length: 5,
+            ..Policy::default()
+        };
+        // The family emoji is seven code points joined into one grapheme.
+        assert!(!policy.problems("ab👨‍👩‍👧‍👦c").is_empty());
+        assert!(policy.problems("ab👨‍👩‍👧‍👦cd").is_empty());
+
+        let mut rng = rand::rng();
+        let ad = Policy::preset("ad-complex").unwrap();
+        assert!(ad.repair("quiet🦊otters", &mut rng).contains('🦊'));
+    }
+}
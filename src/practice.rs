@@ -0,0 +1,136 @@
+//! `phraseforge practice`: rehearse a new phrase until it sticks.
+//!
+//! After the phrase is shown and accepted, it is hidden and asked for from
+//! memory several times, with the wait between recalls doubling after each
+//! success (a simple form of spaced repetition). A miss points out which words
+//! were wrong and shows the phrase again, then repeats the same wait. The
+//! phrase is kept only in memory and never written anywhere; answers are
+//! typed without echo. Under `--screen-reader` the phrase is also spoken in
+//! words and the wait is announced once instead of counted down in place.
+
+use crate::color;
+use crate::speech;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Moves the cursor home and clears the terminal, including scrollback.
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J\x1b[3J";
+
+/// The words of a phrase, ignoring separators, so that a mistake can be
+/// reported by position without repeating the phrase.
+fn words(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Describes how `attempt` differs from `phrase`, or `None` if it doesn't.
+fn mistakes(phrase: &str, attempt: &str) -> Option<String> {
+    if phrase == attempt {
+        return None;
+    }
+    let expected = words(phrase);
+    let given = words(attempt);
+    if expected.len() != given.len() {
+        return Some(format!(
+            "{} words instead of {}",
+            given.len(),
+            expected.len()
+        ));
+    }
+    let wrong: Vec<String> = expected
+        .iter()
+        .zip(&given)
+        .enumerate()
+        .filter(|(_, (expected, given))| expected != given)
+        .map(|(i, _)| (i + 1).to_string())
+        .collect();
+    Some(if wrong.is_empty() {
+        "the words are right but the separators are not".to_string()
+    } else {
+        let noun = if wrong.len() == 1 { "word" } else { "words" };
+        format!("{} {} of {} wrong", noun, wrong.join(", "), expected.len())
+    })
+}
+
+fn wait(delay: Duration, screen_reader: bool) {
+    if screen_reader {
+        eprintln!("Next recall in {} seconds.", delay.as_secs());
+        thread::sleep(delay);
+        return;
+    }
+    for remaining in (1..=delay.as_secs()).rev() {
+        eprint!("\rNext recall in {}s... ", remaining);
+        let _ = io::stderr().flush();
+        thread::sleep(Duration::from_secs(1));
+    }
+    eprint!("\r{:24}\r", "");
+}
+
+fn hide() {
+    let _ = rpassword::prompt_password("Memorize it, then press Enter to hide it ");
+    if color::escape_codes_work() {
+        eprint!("{}", CLEAR_SCREEN);
+    } else {
+        // An old Windows console can't be cleared; scroll the phrase away.
+        eprint!("{}", "\n".repeat(100));
+    }
+}
+
+fn show_then_hide(phrase: &str, screen_reader: bool) {
+    if screen_reader {
+        eprintln!("\n    {}\n    {}\n", phrase, speech::spoken(phrase));
+    } else {
+        eprintln!("\n    {}\n", phrase);
+    }
+    hide();
+}
+
+/// Asks for `phrase`, which is on screen, from memory `rounds` times,
+/// starting with `first_delay` between recalls. Returns how many recalls were
+/// correct.
+pub fn run(phrase: &str, rounds: usize, first_delay: Duration, screen_reader: bool) -> usize {
+    hide();
+    let mut delay = first_delay;
+    let mut correct = 0;
+    for round in 1..=rounds {
+        wait(delay, screen_reader);
+        let attempt = rpassword::prompt_password(format!("Recall {}/{}: ", round, rounds))
+            .expect("Failed to read phrase");
+        match mistakes(phrase, &attempt) {
+            None => {
+                correct += 1;
+                delay *= 2;
+                eprintln!("Correct.");
+            }
+            Some(mistakes) => {
+                eprintln!("Not quite: {}. Here it is again:", mistakes);
+                show_then_hide(phrase, screen_reader);
+            }
+        }
+    }
+    correct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_wrong_words_by_position() {
+        let phrase = "7-brave-badgers-bounce-briskly";
+        assert_eq!(mistakes(phrase, phrase), None);
+        assert_eq!(
+            mistakes(phrase, "7 brave badger bounce briskly").unwrap(),
+            "word 3 of 5 wrong"
+        );
+        assert_eq!(
+            mistakes(phrase, "7-brave-badgers-bounce").unwrap(),
+            "4 words instead of 5"
+        );
+        assert!(mistakes(phrase, "7_brave_badgers_bounce_briskly")
+            .unwrap()
+            .contains("separators"));
+    }
+}
@@ -0,0 +1,295 @@
+//! Authenticated provenance for downloaded sources (`--trust-key`).
+//!
+//! None of the upstreams sign their files, so verification goes through a
+//! signed checksum manifest instead: `SHA256SUMS`, in `sha256sum` format,
+//! listing each source by the file name it is served under, with a detached
+//! signature `SHA256SUMS.sig` (binary or armored) beside it. A mirror serves
+//! both at its root; otherwise `[sources] signed_manifest_url` names the
+//! manifest. The signature is checked by `gpg` in a throwaway home directory
+//! holding only the trusted key, so nothing else in the user's keyring can
+//! vouch for it: the key given with `--trust-key` or `[sources] trust_key`,
+//! else the one a package bundled at build time (`PHRASEFORGE_TRUST_KEY`).
+//! Every download is then hashed as it arrives and rejected unless the
+//! manifest lists it with that hash.
+
+use crate::codec::to_hex;
+use rand::Rng;
+use reqwest::blocking::Client;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A public key embedded by the build, used when none is configured.
+#[cfg(bundled_trust_key)]
+pub const BUNDLED_KEY: Option<&[u8]> =
+    Some(include_bytes!(concat!(env!("OUT_DIR"), "/trust-key.asc")));
+#[cfg(not(bundled_trust_key))]
+pub const BUNDLED_KEY: Option<&[u8]> = None;
+
+pub const MANIFEST_FILE: &str = "SHA256SUMS";
+
+#[derive(Debug, Clone)]
+pub enum TrustKey {
+    File(PathBuf),
+    Bundled(&'static [u8]),
+}
+
+impl TrustKey {
+    pub fn describe(&self) -> String {
+        match self {
+            TrustKey::File(path) => path.display().to_string(),
+            TrustKey::Bundled(_) => "bundled key".to_string(),
+        }
+    }
+}
+
+/// The name a URL serves its file under, which the manifest lists it by.
+pub fn file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Checksums from a manifest whose signature has been verified.
+#[derive(Debug)]
+pub struct TrustedChecksums {
+    manifest_url: String,
+    sha256: HashMap<String, String>,
+}
+
+fn parse(text: &str) -> Result<HashMap<String, String>, String> {
+    let mut sha256 = HashMap::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let hash_and_name = line
+            .split_once(char::is_whitespace)
+            .filter(|(hash, _)| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()));
+        let Some((hash, name)) = hash_and_name else {
+            return Err(format!("line {} is not `<sha256>  <file>`", number + 1));
+        };
+        // `sha256sum` marks binary-mode entries with a leading `*`.
+        let name = name.trim_start().trim_start_matches('*');
+        sha256.insert(name.to_string(), hash.to_ascii_lowercase());
+    }
+    Ok(sha256)
+}
+
+/// Creates a new directory for gpg's home that only this user can enter,
+/// under a random name so nobody can plant a keyring there beforehand.
+/// Fails rather than reuse a directory that already exists.
+fn private_home() -> Result<PathBuf, String> {
+    let mut name = [0u8; 16];
+    rand::rng().fill(&mut name[..]);
+    let home = std::env::temp_dir().join(format!("phraseforge-gpg-{}", to_hex(&name)));
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(&home)
+        .map_err(|e| format!("cannot create {}: {}", home.display(), e))?;
+    #[cfg(unix)]
+    {
+        // The umask can only narrow the mode; check nothing widened it.
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&home)
+            .map_err(|e| format!("cannot inspect {}: {}", home.display(), e))?
+            .permissions()
+            .mode();
+        if mode & 0o077 != 0 {
+            let _ = fs::remove_dir_all(&home);
+            return Err(format!(
+                "{} is accessible to other users (mode {:o})",
+                home.display(),
+                mode & 0o777
+            ));
+        }
+    }
+    Ok(home)
+}
+
+/// Checks `signature` over `manifest` against `key` alone.
+fn verify_signature(key: &TrustKey, manifest: &[u8], signature: &[u8]) -> Result<(), String> {
+    let home = private_home()?;
+    let result = verify_in(&home, key, manifest, signature);
+    let _ = fs::remove_dir_all(&home);
+    result
+}
+
+/// `verify_signature` with `home`, a fresh private directory, as gpg's home
+/// directory.
+fn verify_in(home: &Path, key: &TrustKey, manifest: &[u8], signature: &[u8]) -> Result<(), String> {
+    let key_path = match key {
+        TrustKey::File(path) => path.clone(),
+        TrustKey::Bundled(bytes) => {
+            let path = home.join("trusted.key");
+            fs::write(&path, bytes).map_err(|e| format!("cannot write the bundled key: {}", e))?;
+            path
+        }
+    };
+    fs::write(home.join(MANIFEST_FILE), manifest)
+        .and_then(|()| fs::write(home.join("SHA256SUMS.sig"), signature))
+        .map_err(|e| format!("cannot stage the manifest: {}", e))?;
+
+    let gpg = |args: &[&std::ffi::OsStr]| {
+        Command::new("gpg")
+            .arg("--batch")
+            .arg("--homedir")
+            .arg(home)
+            .args(args)
+            .output()
+            .map_err(|e| format!("failed to run gpg: {}", e))
+    };
+    let imported = gpg(&["--import".as_ref(), key_path.as_os_str()])?;
+    if !imported.status.success() {
+        return Err(format!(
+            "gpg cannot import {}: {}",
+            key.describe(),
+            String::from_utf8_lossy(&imported.stderr).trim()
+        ));
+    }
+    let verified = gpg(&[
+        "--status-fd".as_ref(),
+        "1".as_ref(),
+        "--verify".as_ref(),
+        home.join("SHA256SUMS.sig").as_os_str(),
+        home.join(MANIFEST_FILE).as_os_str(),
+    ])?;
+    let valid = String::from_utf8_lossy(&verified.stdout)
+        .lines()
+        .any(|line| line.starts_with("[GNUPG:] VALIDSIG "));
+    if !verified.status.success() || !valid {
+        return Err(format!(
+            "the signature does not verify with {}: {}",
+            key.describe(),
+            String::from_utf8_lossy(&verified.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+impl TrustedChecksums {
+    /// Downloads the manifest at `manifest_url` and its `.sig`, and verifies
+    /// the signature with `key`.
+    pub fn fetch(client: &Client, manifest_url: &str, key: &TrustKey) -> Result<Self, String> {
+        let get = |url: &str| {
+            client
+                .get(url)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .and_then(|response| response.bytes())
+                .map_err(|e| format!("cannot download {}: {}", url, e))
+        };
+        let manifest = get(manifest_url)?;
+        let signature = get(&format!("{}.sig", manifest_url))?;
+        verify_signature(key, &manifest, &signature)
+            .map_err(|e| format!("{}: {}", manifest_url, e))?;
+        let sha256 = parse(&String::from_utf8_lossy(&manifest))
+            .map_err(|e| format!("{}: {}", manifest_url, e))?;
+        log::debug!(
+            "Verified {} ({} files) with {}",
+            manifest_url,
+            sha256.len(),
+            key.describe()
+        );
+        Ok(TrustedChecksums {
+            manifest_url: manifest_url.to_string(),
+            sha256,
+        })
+    }
+
+    /// The SHA-256 the manifest lists for the file at `url`.
+    pub fn expected(&self, url: &str) -> Result<&str, String> {
+        self.sha256
+            .get(file_name(url))
+            .map(String::as_str)
+            .ok_or_else(|| {
+                format!(
+                    "{} is not listed in the signed manifest {}",
+                    file_name(url),
+                    self.manifest_url
+                )
+            })
+    }
+
+    /// Fails unless `bytes` downloaded from `url` are what the manifest lists.
+    pub fn check(&self, url: &str, bytes: &[u8]) -> Result<(), String> {
+        check_sha256(url, self.expected(url)?, &sha256_hex(bytes))
+    }
+}
+
+/// Fails unless a download's `actual` SHA-256 is the `expected` one.
+pub fn check_sha256(url: &str, expected: &str, actual: &str) -> Result<(), String> {
+    if actual != expected {
+        return Err(format!(
+            "{} has SHA-256 {}, but the signed manifest lists {}; it may have been tampered with",
+            url, actual, expected
+        ));
+    }
+    Ok(())
+}
+
+/// A `--trust-key` file, checked up front so a typo fails before anything
+/// is downloaded.
+pub fn key_file(path: &Path) -> Result<TrustKey, String> {
+    if !path.is_file() {
+        return Err(format!("trust key {} does not exist", path.display()));
+    }
+    Ok(TrustKey::File(path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looks_sources_up_by_the_file_name_they_are_served_under() {
+        let hash = sha256_hex(b"otter 12\n");
+        let checksums = TrustedChecksums {
+            manifest_url: "https://mirror.example/SHA256SUMS".to_string(),
+            sha256: parse(&format!(
+                "{}  en_full.txt\n{} *WNdb-3.0.tar.gz\n",
+                hash,
+                "0".repeat(64)
+            ))
+            .unwrap(),
+        };
+        assert!(checksums
+            .check("https://mirror.example/en_full.txt", b"otter 12\n")
+            .is_ok());
+        assert!(checksums
+            .check("https://mirror.example/en_full.txt", b"otter 13\n")
+            .is_err());
+        assert!(checksums
+            .expected("https://mirror.example/WNdb-3.0.tar.gz?x=1")
+            .is_ok());
+        assert!(checksums
+            .expected("https://mirror.example/mobypos.txt")
+            .is_err());
+        assert!(parse("not a checksum line").is_err());
+    }
+
+    #[test]
+    fn gpg_homes_are_fresh_and_private() {
+        let first = private_home().unwrap();
+        let second = private_home().unwrap();
+        assert_ne!(first, second);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&first).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        fs::remove_dir(&first).unwrap();
+        fs::remove_dir(&second).unwrap();
+    }
+}
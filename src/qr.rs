@@ -0,0 +1,32 @@
+//! QR codes of generated secrets, for moving a new Wi-Fi or device
+//! passphrase to a phone without typing it.
+
+use image::Luma;
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use std::path::Path;
+
+fn encode(text: &str) -> QrCode {
+    QrCode::new(text.as_bytes()).expect("Secrets are short enough for a QR code")
+}
+
+/// The code drawn with half-block characters, two modules per line.
+pub fn render_terminal(text: &str) -> String {
+    // Inverted like `qrencode -t UTF8`: terminals are usually light text on a
+    // dark background, and scanners need the dark modules to be dark.
+    encode(text)
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build()
+}
+
+pub fn save_png(text: &str, path: &Path) {
+    encode(text)
+        .render::<Luma<u8>>()
+        .min_dimensions(256, 256)
+        .build()
+        .save(path)
+        .expect("Failed to write QR code image");
+}
@@ -0,0 +1,72 @@
+//! Classic random-character passwords (`phraseforge random`).
+
+use crate::entropy::{slot_entropy, Entropy};
+use rand::Rng;
+
+const LOWER: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPER: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+pub const CHARSET_NAMES: [&str; 7] = [
+    "lower", "upper", "alpha", "digits", "alnum", "symbols", "hex",
+];
+
+/// The characters a random password may contain, in a fixed order.
+#[derive(Debug, Clone)]
+pub struct Charset {
+    chars: Vec<char>,
+}
+
+impl Charset {
+    /// Parses a `+`-separated union of named classes, e.g. `alnum+symbols`.
+    pub fn parse(spec: &str) -> Result<Charset, String> {
+        let mut chars: Vec<char> = Vec::new();
+        for name in spec.split('+') {
+            let class = match name {
+                "lower" => LOWER.to_string(),
+                "upper" => UPPER.to_string(),
+                "alpha" => format!("{}{}", LOWER, UPPER),
+                "digits" => DIGITS.to_string(),
+                "alnum" => format!("{}{}{}", LOWER, UPPER, DIGITS),
+                "symbols" => SYMBOLS.to_string(),
+                "hex" => "0123456789abcdef".to_string(),
+                _ => {
+                    return Err(format!(
+                        "unknown character class '{}' (expected one of: {})",
+                        name,
+                        CHARSET_NAMES.join(", ")
+                    ))
+                }
+            };
+            for c in class.chars() {
+                if !chars.contains(&c) {
+                    chars.push(c);
+                }
+            }
+        }
+        Ok(Charset { chars })
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+}
+
+pub fn generate(length: usize, charset: &Charset, rng: &mut impl Rng) -> String {
+    (0..length)
+        .map(|_| charset.chars[rng.random_range(0..charset.len())])
+        .collect()
+}
+
+/// Every character is an independent uniform draw from the charset.
+pub fn entropy(length: usize, charset: &Charset) -> Entropy {
+    let slots: Vec<_> = (1..=length)
+        .map(|i| slot_entropy(&format!("char-{}", i), charset.len()))
+        .collect();
+    let bits_per_phrase = slots.iter().map(|s| s.bits).sum();
+    Entropy {
+        slots,
+        bits_per_phrase,
+    }
+}
@@ -0,0 +1,226 @@
+//! Rotation reminders (`--remind 90d`, `phraseforge reminders`).
+//!
+//! Each labeled secret generated with `--remind` appends one JSON line to the
+//! reminders file: the label, when it was generated, when it is due for
+//! rotation and a salted Argon2 hash of it, so the file never holds a secret
+//! and can live alongside a team's other notes. A later entry for the same
+//! label replaces the earlier ones, so rotating a credential with `--remind`
+//! again resets its due date.
+
+use crate::config::SECONDS_PER_DAY;
+use crate::hash::HashAlgorithm;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub label: String,
+    pub created_at_unix: u64,
+    pub due_at_unix: u64,
+    /// Argon2 PHC string of the secret, to tell which secret a reminder is
+    /// about without recording it.
+    pub argon2: String,
+}
+
+pub fn default_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "tynsol", "phraseforge")
+        .map(|dirs| dirs.data_local_dir().join("reminders.jsonl"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Parses a rotation period such as `90d`, `12w`, `6m` or `1y` into days; a
+/// month counts as 30 days and a year as 365.
+pub fn parse_period(text: &str) -> Result<u64, String> {
+    let error = || format!("'{}' is not a period like 90d, 12w, 6m or 1y", text);
+    let split = text.len() - text.chars().last().map_or(0, char::len_utf8);
+    let (count, unit) = text.split_at(split);
+    let count: u64 = count.parse().map_err(|_| error())?;
+    let days = match unit {
+        "d" => count,
+        "w" => count * 7,
+        "m" => count * 30,
+        "y" => count * 365,
+        _ => return Err(error()),
+    };
+    if days == 0 {
+        return Err(format!("'{}' would be due immediately", text));
+    }
+    Ok(days)
+}
+
+/// `YYYY-MM-DD` for a Unix time, in UTC.
+pub fn date(unix: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm, for days since 1970-01-01.
+    let days = (unix / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Reminders collected during one run, appended together by `append`.
+pub struct ReminderLog {
+    pub path: PathBuf,
+    pub period_days: u64,
+    /// The label for secrets not generated under `--labels`.
+    pub label: Option<String>,
+    entries: Vec<Entry>,
+}
+
+impl ReminderLog {
+    pub fn new(path: PathBuf, period_days: u64, label: Option<String>) -> Self {
+        ReminderLog {
+            path,
+            period_days,
+            label,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Records `secret` under `label`, or under this log's own label.
+    pub fn push(&mut self, label: Option<&str>, secret: &str) {
+        let Some(label) = label.or(self.label.as_deref()) else {
+            return;
+        };
+        let created_at_unix = now();
+        self.entries.push(Entry {
+            label: label.to_string(),
+            created_at_unix,
+            due_at_unix: created_at_unix + self.period_days * SECONDS_PER_DAY,
+            argon2: HashAlgorithm::Argon2
+                .hash(secret)
+                .expect("Argon2 hashes any secret"),
+        });
+    }
+
+    pub fn append(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for entry in &self.entries {
+            lines.push_str(&serde_json::to_string(entry).expect("Entry serializes"));
+            lines.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| format!("cannot append to {}: {}", self.path.display(), e))
+    }
+}
+
+/// The latest entry for each label in the reminders file at `path`, soonest
+/// due first. A missing file has no reminders.
+pub fn load(path: &Path) -> Result<Vec<Entry>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    latest(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn latest(text: &str) -> Result<Vec<Entry>, String> {
+    let mut by_label: BTreeMap<String, Entry> = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Entry =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+        by_label.insert(entry.label.clone(), entry);
+    }
+    let mut entries: Vec<Entry> = by_label.into_values().collect();
+    entries.sort_by_key(|entry| entry.due_at_unix);
+    Ok(entries)
+}
+
+/// A line such as `github  due 2026-01-14 (12 days overdue), generated
+/// 2025-10-16`, the label padded to `width`.
+pub fn describe(entry: &Entry, width: usize) -> String {
+    let now = now();
+    let status = if entry.due_at_unix <= now {
+        format!(
+            "{} days overdue",
+            (now - entry.due_at_unix) / SECONDS_PER_DAY
+        )
+    } else {
+        format!(
+            "in {} days",
+            (entry.due_at_unix - now).div_ceil(SECONDS_PER_DAY)
+        )
+    };
+    format!(
+        "{:<width$}  due {} ({}), generated {}",
+        entry.label,
+        date(entry.due_at_unix),
+        status,
+        date(entry.created_at_unix),
+        width = width
+    )
+}
+
+/// Whether `entry` is due for rotation now.
+pub fn is_due(entry: &Entry) -> bool {
+    entry.due_at_unix <= now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_periods_and_formats_dates() {
+        assert_eq!(parse_period("90d"), Ok(90));
+        assert_eq!(parse_period("2w"), Ok(14));
+        assert_eq!(parse_period("1y"), Ok(365));
+        assert!(parse_period("90").is_err() && parse_period("0d").is_err());
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(1_790_000_000), "2026-09-21");
+    }
+
+    #[test]
+    fn a_later_entry_replaces_a_labels_earlier_one() {
+        let line = |label: &str, due: u64| {
+            format!(
+                "{{\"label\":\"{}\",\"created_at_unix\":0,\"due_at_unix\":{},\"argon2\":\"x\"}}\n",
+                label, due
+            )
+        };
+        let text = line("github", 100) + &line("email", 200) + &line("github", 300);
+        let entries = latest(&text).unwrap();
+        let due: Vec<(&str, u64)> = entries
+            .iter()
+            .map(|entry| (entry.label.as_str(), entry.due_at_unix))
+            .collect();
+        assert_eq!(due, [("email", 200), ("github", 300)]);
+    }
+}
@@ -0,0 +1,182 @@
+//! Rhyme lookup and rhyming pair selection.
+//!
+//! Pronunciations come from a bundled subset of the CMU Pronouncing
+//! Dictionary. Two words rhyme when everything from their last stressed vowel
+//! onwards sounds the same. Words missing from the subset fall back to a
+//! spelling-based approximation, which handles the regular cases
+//! ("stop"/"drop") but not the irregular ones the subset exists for
+//! ("love"/"move").
+
+use crate::WordEntry;
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const CMUDICT_SUBSET: &str = include_str!("../data/cmudict-subset.txt");
+
+fn pronunciations() -> &'static HashMap<&'static str, Vec<&'static str>> {
+    static PRONUNCIATIONS: OnceLock<HashMap<&'static str, Vec<&'static str>>> = OnceLock::new();
+    PRONUNCIATIONS.get_or_init(|| {
+        CMUDICT_SUBSET
+            .lines()
+            .filter(|line| !line.starts_with(";;;"))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                Some((fields.next()?, fields.collect()))
+            })
+            .collect()
+    })
+}
+
+/// Phonemes from the last primary-stressed vowel (or, failing that, the last
+/// stressed vowel) to the end, with stress markers removed.
+fn phonetic_rhyme_key(phones: &[&str]) -> Option<String> {
+    let stressed = |marker: char| phones.iter().rposition(|p| p.ends_with(marker));
+    let start = stressed('1').or_else(|| stressed('2'))?;
+    let key: Vec<&str> = phones[start..]
+        .iter()
+        .map(|p| p.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .collect();
+    Some(key.join(" "))
+}
+
+fn is_vowel_letter(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// Endings that are rarely stressed, so rhyming on them alone ("bitter" and
+/// "water") sounds wrong; the rhyme has to reach back a syllable.
+const UNSTRESSED_ENDINGS: &[&str] = &[
+    "al", "an", "ar", "ed", "el", "en", "er", "es", "est", "ing", "le", "ly", "on", "or", "y",
+];
+
+/// Start of the vowel group ending at or before `end`, if there is one.
+fn vowel_group_start(chars: &[char], end: usize) -> Option<usize> {
+    let last_vowel = chars[..end].iter().rposition(|&c| is_vowel_letter(c))?;
+    Some(
+        chars[..=last_vowel]
+            .iter()
+            .rposition(|&c| !is_vowel_letter(c))
+            .map_or(0, |i| i + 1),
+    )
+}
+
+/// The last stressed-looking vowel group and everything after it, ignoring a
+/// silent final "e". Positions count characters, not bytes, since words can
+/// carry accents ("señor").
+fn spelling_rhyme_key(word: &str) -> Option<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let from = |start: usize| chars[start..].iter().collect::<String>();
+    let mut end = chars.len();
+    // "cake" rhymes on "ake", not on the silent "e"
+    if end >= 3 && chars[end - 1] == 'e' && !is_vowel_letter(chars[end - 2]) {
+        end -= 1;
+    }
+    let mut start = vowel_group_start(&chars, end)?;
+    if start > 0 && UNSTRESSED_ENDINGS.contains(&from(start - 1).as_str()) {
+        start = vowel_group_start(&chars, start - 1)?;
+    } else if UNSTRESSED_ENDINGS.contains(&from(start).as_str()) {
+        start = vowel_group_start(&chars, start)?;
+    }
+    (start > 0 && chars.len() - start >= 2).then(|| format!("~{}", from(start)))
+}
+
+/// A key that is equal for two words exactly when they rhyme.
+pub fn rhyme_key(word: &str) -> Option<String> {
+    match pronunciations().get(word) {
+        Some(phones) => phonetic_rhyme_key(phones),
+        None => spelling_rhyme_key(word),
+    }
+}
+
+/// Words from two pools grouped by what they rhyme with.
+pub struct RhymeGroups<'a> {
+    groups: Vec<(Vec<&'a str>, Vec<&'a str>)>,
+    pair_count: usize,
+}
+
+fn pairs_in_group(first: &[&str], second: &[&str]) -> usize {
+    let shared = first.iter().filter(|w| second.contains(w)).count();
+    first.len() * second.len() - shared
+}
+
+impl<'a> RhymeGroups<'a> {
+    pub fn new(
+        first: impl Iterator<Item = &'a WordEntry>,
+        second: impl Iterator<Item = &'a WordEntry>,
+    ) -> Self {
+        let mut by_key: HashMap<String, (Vec<&'a str>, Vec<&'a str>)> = HashMap::new();
+        for entry in first {
+            if let Some(key) = rhyme_key(&entry.word) {
+                by_key.entry(key).or_default().0.push(&entry.word);
+            }
+        }
+        for entry in second {
+            if let Some(key) = rhyme_key(&entry.word) {
+                by_key.entry(key).or_default().1.push(&entry.word);
+            }
+        }
+
+        let groups: Vec<(Vec<&str>, Vec<&str>)> = by_key
+            .into_values()
+            .filter(|(first, second)| pairs_in_group(first, second) > 0)
+            .collect();
+        let pair_count = groups.iter().map(|(a, b)| pairs_in_group(a, b)).sum();
+        RhymeGroups { groups, pair_count }
+    }
+
+    /// Number of distinct rhyming pairs; each is equally likely to be chosen.
+    pub fn pair_count(&self) -> usize {
+        self.pair_count
+    }
+
+    pub fn choose(&self, rng: &mut impl Rng) -> Option<(&'a str, &'a str)> {
+        if self.pair_count == 0 {
+            return None;
+        }
+        // Rejecting identical words keeps every remaining pair equally likely.
+        let total: usize = self.groups.iter().map(|(a, b)| a.len() * b.len()).sum();
+        loop {
+            let mut index = rng.random_range(0..total);
+            for (first, second) in &self.groups {
+                let size = first.len() * second.len();
+                if index < size {
+                    let pair = (first[index / second.len()], second[index % second.len()]);
+                    if pair.0 != pair.1 {
+                        return Some(pair);
+                    }
+                    break;
+                }
+                index -= size;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rhymes_irregular_spellings_from_dictionary() {
+        assert_eq!(rhyme_key("bear"), rhyme_key("chair"));
+        assert_eq!(rhyme_key("weight"), rhyme_key("straight"));
+        assert_ne!(rhyme_key("love"), rhyme_key("move"));
+    }
+
+    #[test]
+    fn falls_back_to_spelling() {
+        assert_eq!(rhyme_key("stop"), rhyme_key("drop"));
+        assert_eq!(rhyme_key("blade"), rhyme_key("spade"));
+        assert_eq!(rhyme_key("slowly"), rhyme_key("lowly"));
+        assert_ne!(rhyme_key("bitter"), rhyme_key("water"));
+    }
+
+    #[test]
+    fn spells_accented_words_by_character() {
+        assert_eq!(rhyme_key("señor"), Some("~eñor".to_string()));
+        assert_eq!(rhyme_key("señor"), rhyme_key("meñor"));
+        assert_eq!(rhyme_key("crêpe"), None);
+        assert_eq!(rhyme_key("façade"), rhyme_key("arcade"));
+    }
+}
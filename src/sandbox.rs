@@ -0,0 +1,116 @@
+//! `--sandbox DIR`: a run that writes nothing outside `DIR` and never touches
+//! the network, for credential-issuing services confined by seccomp or
+//! AppArmor profiles that would otherwise kill the process mid-run.
+//!
+//! The cache lives in `DIR` unless `--cache-dir` names a directory inside
+//! it, and every file the run would write (`--output`, `--transcript`, the
+//! history and so on) is checked against `DIR` before anything is generated,
+//! symlinks resolved. Every HTTP client is built through
+//! [`crate::sources::Sources`], which refuses to build one, so no flag or
+//! subcommand can reach the network by a path this module doesn't know
+//! about. Word lists must already be installed, e.g. with `bootstrap` into
+//! `DIR`, or read from a system-wide cache. Features that hand secrets to
+//! other programs (`--keyring`, `store`, `--filter-script`, and `web`, which
+//! serves them to whichever browser connects) are refused outright, since
+//! what those programs write is out of reach.
+
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Confines the rest of the run to `dir`, creating it if needed.
+pub fn enter(dir: &Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("cannot create {}: {}", dir.display(), e))?;
+    let root = dir
+        .canonicalize()
+        .map_err(|e| format!("cannot resolve {}: {}", dir.display(), e))?;
+    log::debug!("Sandboxed to {}", root.display());
+    ROOT.get_or_init(|| root);
+    Ok(())
+}
+
+/// The directory the run is confined to, under `--sandbox`.
+pub fn root() -> Option<&'static Path> {
+    ROOT.get().map(PathBuf::as_path)
+}
+
+/// `path` made absolute with its existing ancestors' symlinks resolved, or
+/// `None` if what remains climbs out with `..`.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let absolute = env::current_dir().ok()?.join(path);
+    let mut existing = absolute.as_path();
+    let mut rest = Vec::new();
+    let base = loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            break resolved;
+        }
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    };
+    // The part that doesn't exist yet has no symlinks, but may hold `..`.
+    let suffix: PathBuf = rest.into_iter().rev().collect();
+    if suffix
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(base.join(suffix))
+}
+
+fn within(root: &Path, path: &Path) -> bool {
+    resolve(path).is_some_and(|resolved| resolved.starts_with(root))
+}
+
+/// Fails if the run is sandboxed and `path`, written for `what`, is outside
+/// the sandbox directory.
+pub fn check_write(what: &str, path: &Path) -> Result<(), String> {
+    match root() {
+        Some(root) if !within(root, path) => Err(format!(
+            "{} {} is outside the --sandbox directory {}",
+            what,
+            path.display(),
+            root.display()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if the run is sandboxed, which allows no network access at all.
+pub fn check_network() -> Result<(), String> {
+    match root() {
+        Some(root) => Err(format!(
+            "--sandbox allows no network access, and this run needs it; install the word lists in {} first, e.g. with `phraseforge --cache-dir {} bootstrap --from-dir DIR`",
+            root.display(),
+            root.display()
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_writes_inside_the_root() {
+        let dir = env::temp_dir().join(format!("phraseforge-sandbox-{}", std::process::id()));
+        let root = dir.join("root");
+        fs::create_dir_all(root.join("cache")).unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert!(within(&root, &root.join("cache")));
+        assert!(within(&root, &root.join("new/dir/audit.jsonl")));
+        assert!(!within(&root, &root.join("new/../../escape")));
+        assert!(!within(&root, &dir.join("outside.jsonl")));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&dir, root.join("link")).unwrap();
+            assert!(!within(&root, &root.join("link/outside.jsonl")));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,81 @@
+//! Reading SCOWL's word lists for `--dictionary scowl-<size>`.
+//!
+//! The archive's `final` directory holds one file per category and size
+//! tier, e.g. `english-words.35`; a size takes in every tier up to it. Only
+//! the `english-words` (spelled the same everywhere) and `american-words`
+//! files are kept, matching WordNet's spellings; `--spelling` filters from
+//! there. The files are Latin-1, and hold possessives and other forms with
+//! punctuation, which the dictionary's word check drops.
+
+use crate::dictionary::SCOWL_SIZES;
+use crate::{normalize, update};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const SCOWL_URL: &str = "https://downloads.sourceforge.net/wordlist/scowl-2020.12.07.tar.gz";
+pub const SCOWL_ARCHIVE: &str = "scowl-2020.12.07.tar.gz";
+/// Where the kept files are installed in the cache.
+pub const SCOWL_DIR: &str = "scowl";
+const CATEGORIES: [&str; 2] = ["english-words", "american-words"];
+
+/// Whether `path` in the SCOWL archive is one of the word lists kept.
+pub fn word_list_file(path: &Path) -> bool {
+    let in_final = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == "final");
+    let name = path.file_name().and_then(|name| name.to_str());
+    in_final
+        && name.is_some_and(|name| {
+            name.split_once('.').is_some_and(|(category, size)| {
+                CATEGORIES.contains(&category) && size.parse::<u32>().is_ok()
+            })
+        })
+}
+
+/// Swaps the `final` directory unpacked into `staging`, under the
+/// archive's versioned top-level directory or not, in for the installed
+/// lists.
+pub fn install(data_dir: &Path, staging: &Path) {
+    let unpacked = fs::read_dir(staging)
+        .expect("Failed to read staging directory")
+        .flatten()
+        .map(|entry| entry.path().join("final"))
+        .chain([staging.join("final")])
+        .find(|dir| dir.is_dir())
+        .expect("The SCOWL archive has no final directory");
+    let installed = data_dir.join(SCOWL_DIR);
+    if installed.exists() {
+        update::rename(&installed, &staging.join("scowl.old"))
+            .expect("Failed to move old SCOWL lists aside");
+    }
+    update::rename(&unpacked, &installed).expect("Failed to install SCOWL");
+}
+
+/// The words in every tier up to `size`, normalized as word lists store
+/// them.
+pub fn words(scowl_dir: &Path, size: u32) -> HashSet<String> {
+    let files: Vec<PathBuf> = SCOWL_SIZES
+        .iter()
+        .filter(|&&tier| tier <= size)
+        .flat_map(|tier| {
+            CATEGORIES
+                .iter()
+                .map(move |category| scowl_dir.join(format!("{}.{}", category, tier)))
+        })
+        .collect();
+    let mut words = HashSet::new();
+    for file in files.iter().filter(|file| file.exists()) {
+        let bytes = fs::read(file).expect("Failed to read SCOWL word list");
+        // Latin-1 maps byte for byte onto the first 256 code points.
+        let text: String = bytes.iter().map(|&byte| byte as char).collect();
+        words.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|word| word.chars().next().is_some_and(char::is_lowercase))
+                .map(normalize::word),
+        );
+    }
+    words
+}
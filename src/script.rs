@@ -0,0 +1,83 @@
+//! `--filter-script`: screening words with an external program, for lists a
+//! deployment can't or won't add to PhraseForge itself (brand names,
+//! internal jargon, compliance lists).
+//!
+//! The program is run once, before any phrase is generated, with every word
+//! of every loaded pool on stdin as `<part of speech><TAB><word>` lines, the
+//! part of speech one of adjective, noun, verb or adverb. It prints back the
+//! lines it accepts, unchanged and in any order; a word it leaves out is
+//! dropped. A program that exits unsuccessfully stops the run rather than
+//! silently keeping or dropping everything.
+
+use crate::filter::WordFilter;
+use crate::store::run_capturing;
+use crate::{PartOfSpeech, WordLists};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub struct ScriptFilter {
+    program: PathBuf,
+    kept: HashMap<PartOfSpeech, HashSet<String>>,
+}
+
+impl ScriptFilter {
+    /// Runs `program` over the words in `word_lists`.
+    pub fn new(program: &Path, word_lists: &WordLists) -> Result<ScriptFilter, String> {
+        let mut input = String::new();
+        for pos in PartOfSpeech::ALL {
+            for entry in word_lists.entries_of(pos) {
+                input.push_str(&format!("{}\t{}\n", pos.name(), entry.word));
+            }
+        }
+        let output = run_capturing(Command::new(program), &input)?;
+        let output = String::from_utf8(output)
+            .map_err(|_| format!("{} printed something that is not UTF-8", program.display()))?;
+        let kept =
+            parse_kept(&output).map_err(|e| format!("{} printed {}", program.display(), e))?;
+        Ok(ScriptFilter {
+            program: program.to_path_buf(),
+            kept,
+        })
+    }
+}
+
+/// The `<part of speech><TAB><word>` lines of `output`.
+fn parse_kept(output: &str) -> Result<HashMap<PartOfSpeech, HashSet<String>>, String> {
+    let mut kept: HashMap<PartOfSpeech, HashSet<String>> = HashMap::new();
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let (pos, word) = line
+            .split_once('\t')
+            .and_then(|(pos, word)| Some((PartOfSpeech::from_name(pos)?, word)))
+            .ok_or_else(|| format!("'{}', not a <part of speech><TAB><word> line", line))?;
+        kept.entry(pos).or_default().insert(word.to_string());
+    }
+    Ok(kept)
+}
+
+impl WordFilter for ScriptFilter {
+    fn name(&self) -> String {
+        format!("filter-script {}", self.program.display())
+    }
+
+    fn keep(&self, pos: PartOfSpeech, word: &str) -> bool {
+        self.kept
+            .get(&pos)
+            .is_some_and(|words| words.contains(word))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_the_accepted_lines() {
+        let kept = parse_kept("noun\totter\n\nadj\tbrave\r\n").unwrap();
+        assert!(kept[&PartOfSpeech::Noun].contains("otter"));
+        assert!(kept[&PartOfSpeech::Adjective].contains("brave"));
+        assert_eq!(kept.len(), 2);
+        assert!(parse_kept("otter\n").is_err());
+        assert!(parse_kept("pronoun\tthey\n").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Reading secrets (the `derive` master secret, a KeePassXC unlock password)
+//! so they never show on screen, in shell history or in the process's
+//! arguments.
+//!
+//! At a terminal the secret is typed without echo. Without one (scripts,
+//! cron, CI) it comes from a file descriptor the caller opened, e.g.
+//! `--secret-fd 3 3< <(pass show master)`, or from the environment variable
+//! named by `--secret-env`, which is cleared once read so no password
+//! manager or other child process inherits it. On Unix, Ctrl-C or a
+//! termination signal while typing puts the terminal back the way it was
+//! before exiting; the prompt alone would leave echo switched off.
+
+use clap::ArgMatches;
+use std::env;
+use std::io;
+
+pub enum Source {
+    Prompt,
+    Fd(i32),
+    Env(String),
+}
+
+impl Source {
+    /// The source chosen by `--secret-fd` or `--secret-env`, if either.
+    pub fn from_matches(matches: &ArgMatches) -> Source {
+        if let Some(&fd) = matches.get_one::<i32>("secret-fd") {
+            Source::Fd(fd)
+        } else if let Some(name) = matches.get_one::<String>("secret-env") {
+            Source::Env(name.clone())
+        } else {
+            Source::Prompt
+        }
+    }
+}
+
+/// Reads a secret from `source`, showing `prompt` if it is a terminal.
+pub fn read(source: &Source, prompt: &str) -> Result<String, String> {
+    match source {
+        Source::Prompt => {
+            let _guard = interrupt::Guard::install();
+            rpassword::prompt_password(prompt).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => "no secret given (end of input)".to_string(),
+                _ => format!(
+                    "cannot prompt for the secret ({}); without a terminal, pass --secret-fd or --secret-env",
+                    e
+                ),
+            })
+        }
+        Source::Fd(fd) => read_fd(*fd),
+        Source::Env(name) => {
+            let secret = env::var(name).map_err(|e| format!("${}: {}", name, e))?;
+            env::remove_var(name);
+            Ok(secret)
+        }
+    }
+}
+
+/// The first line of what `fd` holds, as with gpg's `--passphrase-fd`.
+#[cfg(unix)]
+fn read_fd(fd: i32) -> Result<String, String> {
+    use std::fs::File;
+    use std::io::{BufRead, BufReader};
+    use std::mem::ManuallyDrop;
+    use std::os::fd::FromRawFd;
+
+    // SAFETY: the caller handed over `fd` to read from; reading a descriptor
+    // that isn't open fails with EBADF, and ManuallyDrop leaves it open, so
+    // stdin, stdout or stderr given by mistake are not closed.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    let mut line = String::new();
+    BufReader::new(&*file)
+        .read_line(&mut line)
+        .map_err(|e| format!("cannot read the secret from file descriptor {}: {}", fd, e))?;
+    if line.is_empty() {
+        return Err(format!("file descriptor {} holds no secret", fd));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: i32) -> Result<String, String> {
+    Err("--secret-fd is only supported on Unix; use --secret-env".to_string())
+}
+
+#[cfg(unix)]
+mod interrupt {
+    use std::fs::File;
+    use std::os::fd::IntoRawFd;
+    use std::sync::OnceLock;
+
+    const SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// The terminal and its settings before any prompt changed them.
+    static TERMINAL: OnceLock<(libc::c_int, libc::termios)> = OnceLock::new();
+
+    extern "C" fn restore_and_exit(signal: libc::c_int) {
+        // Only async-signal-safe calls here.
+        if let Some((fd, settings)) = TERMINAL.get() {
+            unsafe {
+                libc::tcsetattr(*fd, libc::TCSANOW, settings);
+                libc::write(*fd, b"\n".as_ptr().cast(), 1);
+            }
+        }
+        unsafe { libc::_exit(128 + signal) };
+    }
+
+    /// While alive, a signal restores the terminal's settings and exits.
+    pub struct Guard {
+        previous: Vec<libc::sighandler_t>,
+    }
+
+    impl Guard {
+        pub fn install() -> Guard {
+            if TERMINAL.get().is_none() {
+                if let Ok(tty) = File::options().read(true).write(true).open("/dev/tty") {
+                    // The descriptor stays open for the handler to use.
+                    let fd = tty.into_raw_fd();
+                    let mut settings = unsafe { std::mem::zeroed::<libc::termios>() };
+                    if unsafe { libc::tcgetattr(fd, &mut settings) } == 0 {
+                        let _ = TERMINAL.set((fd, settings));
+                    }
+                }
+            }
+            let handler = restore_and_exit as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            let previous = SIGNALS
+                .iter()
+                .map(|&signal| unsafe { libc::signal(signal, handler) })
+                .collect();
+            Guard { previous }
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            for (&signal, &previous) in SIGNALS.iter().zip(&self.previous) {
+                unsafe { libc::signal(signal, previous) };
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod interrupt {
+    pub struct Guard;
+
+    impl Guard {
+        pub fn install() -> Guard {
+            Guard
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! `phraseforge selftest`: statistical sanity checks that phrases are drawn
+//! uniformly from the pools they claim to be, for security review. Nothing
+//! is sent anywhere and the sample phrases are thrown away.
+//!
+//! A large sample is generated through the ordinary generator with the
+//! template, filters and `--min-frequency` in effect, and checked three
+//! ways:
+//!
+//! - every word drawn is in its slot's filtered, eligible pool, so filtered
+//!   or infrequent words never leak in;
+//! - each slot's picks pass a chi-squared test against the uniform
+//!   distribution. Pools are in frequency order, so when the sample is too
+//!   small to test every word the test runs over runs of neighboring words,
+//!   which still catches a bias towards common (or rare) words;
+//! - the number of repeated phrases fits the birthday-problem expectation
+//!   for the estimated entropy, in either direction.
+//!
+//! Per-phrase constraints (`--alliterate`, `--acrostic`, `--rhyme`,
+//! `--distinct-sounds`) and policies are set aside, since they narrow slots on purpose. A check fails
+//! when its p-value is below `SIGNIFICANCE`, so a correct generator fails a
+//! run about once in ten thousand.
+
+use crate::entropy;
+use crate::generate::{eligible, generate_password, GenerationOptions};
+use crate::template::Slot;
+use crate::WordLists;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+const SIGNIFICANCE: f64 = 1e-4;
+/// Fewest expected picks per chi-squared cell for the test to be valid.
+const MIN_EXPECTED: f64 = 5.0;
+
+pub struct Check {
+    pub name: String,
+    pub detail: String,
+    pub passed: bool,
+}
+
+/// Runs every check over `samples` phrases, or explains why no phrase can be
+/// generated.
+pub fn run(
+    word_lists: &WordLists,
+    options: &GenerationOptions,
+    samples: usize,
+) -> Result<Vec<Check>, String> {
+    let options = GenerationOptions {
+        alliterate: false,
+        acrostic: None,
+        rhyme: false,
+        distinct_sounds: false,
+        policy: None,
+        min_entropy: None,
+        ..options.clone()
+    };
+    let phrases: Vec<_> = (0..samples)
+        .into_par_iter()
+        .map(|_| generate_password(word_lists, &options, &mut rand::rng()))
+        .collect::<Result<_, _>>()?;
+
+    let slots: Vec<Slot> = options
+        .template
+        .slots()
+        .filter(|&slot| slot != Slot::Number)
+        .collect();
+    let pools: Vec<Vec<&str>> = slots
+        .iter()
+        .map(|&slot| {
+            let entries = word_lists.pool(slot).expect("Word slots have a pool");
+            eligible(entries, &options.min_frequency.of_slot(slot), None)
+                .map(|entry| entry.word.as_str())
+                .collect()
+        })
+        .collect();
+
+    let members: Vec<HashSet<&str>> = pools
+        .iter()
+        .map(|pool| pool.iter().copied().collect())
+        .collect();
+
+    let mut checks = Vec::new();
+    let mut counts: Vec<HashMap<&str, usize>> = vec![HashMap::new(); slots.len()];
+    let mut strays = 0;
+    for phrase in &phrases {
+        if phrase.words.len() != slots.len() {
+            strays += 1;
+            continue;
+        }
+        for (i, (_, word)) in phrase.words.iter().enumerate() {
+            match members[i].get(word.as_str()) {
+                Some(&known) => *counts[i].entry(known).or_default() += 1,
+                None => strays += 1,
+            }
+        }
+    }
+    checks.push(Check {
+        name: "pool membership".to_string(),
+        detail: format!(
+            "{} of {} words drawn from outside their filtered pools",
+            strays,
+            phrases.len() * slots.len()
+        ),
+        passed: strays == 0,
+    });
+
+    for (i, slot) in slots.iter().enumerate() {
+        let name = format!("uniformity of word {} ({})", i + 1, slot.name());
+        checks.push(match uniformity(&pools[i], &counts[i], samples) {
+            Some((statistic, df, p)) => Check {
+                name,
+                detail: format!(
+                    "chi-squared {:.1} on {} degrees of freedom, p = {:.4}",
+                    statistic, df, p
+                ),
+                passed: p >= SIGNIFICANCE,
+            },
+            None => Check {
+                name,
+                detail: "too few samples or words to test; raise --samples".to_string(),
+                passed: true,
+            },
+        });
+    }
+
+    let distinct: HashSet<&str> = phrases.iter().map(|phrase| phrase.text.as_str()).collect();
+    let repeats = phrases.len() - distinct.len();
+    let n = phrases.len() as f64;
+    let bits_per_phrase = entropy::estimate(word_lists, &options).bits_per_phrase;
+    let expected = n * (n - 1.0) / 2.0 / bits_per_phrase.exp2();
+    let (below, above) = poisson_tails(repeats, expected);
+    checks.push(Check {
+        name: "repeated phrases".to_string(),
+        detail: format!(
+            "{} repeats, {:.2} expected at {:.1} bits per phrase",
+            repeats, expected, bits_per_phrase
+        ),
+        passed: below.min(above) >= SIGNIFICANCE / 2.0,
+    });
+    Ok(checks)
+}
+
+/// Chi-squared statistic, degrees of freedom and p-value of `counts` against
+/// a uniform draw from `pool`, over runs of neighboring words sized so each
+/// expects at least `MIN_EXPECTED` picks. `None` if that leaves under two
+/// cells.
+fn uniformity(
+    pool: &[&str],
+    counts: &HashMap<&str, usize>,
+    samples: usize,
+) -> Option<(f64, usize, f64)> {
+    let cells = pool.len().min((samples as f64 / MIN_EXPECTED) as usize);
+    if cells < 2 {
+        return None;
+    }
+    let mut statistic = 0.0;
+    for cell in 0..cells {
+        let words = &pool[cell * pool.len() / cells..(cell + 1) * pool.len() / cells];
+        let observed: usize = words
+            .iter()
+            .map(|word| counts.get(word).copied().unwrap_or(0))
+            .sum();
+        let expected = samples as f64 * words.len() as f64 / pool.len() as f64;
+        statistic += (observed as f64 - expected).powi(2) / expected;
+    }
+    let df = cells - 1;
+    Some((statistic, df, chi_squared_p(statistic, df)))
+}
+
+/// Two-sided p-value of a chi-squared statistic, through the Wilson-Hilferty
+/// normal approximation; a fit too good to be random fails too.
+fn chi_squared_p(statistic: f64, df: usize) -> f64 {
+    let k = df as f64;
+    let z = ((statistic / k).cbrt() - (1.0 - 2.0 / (9.0 * k))) / (2.0 / (9.0 * k)).sqrt();
+    erfc(z.abs() / std::f64::consts::SQRT_2)
+}
+
+/// The complementary error function, to within 1.2e-7 (Numerical Recipes'
+/// `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let poly = -x * x - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let value = t * poly.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// P(X <= observed) and P(X >= observed) for X ~ Poisson(`mean`).
+fn poisson_tails(observed: usize, mean: f64) -> (f64, f64) {
+    if mean <= 0.0 {
+        return (1.0, if observed == 0 { 1.0 } else { 0.0 });
+    }
+    // Terms in log space, so a large mean doesn't underflow e^-mean.
+    let mut log_term = -mean;
+    let mut below = 0.0;
+    for i in 0..observed {
+        below += log_term.exp();
+        log_term += mean.ln() - ((i + 1) as f64).ln();
+    }
+    let at = log_term.exp();
+    ((below + at).min(1.0), (1.0 - below).clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_skewed_picks_but_not_fair_ones() {
+        let pool: Vec<String> = (0..100).map(|i| format!("word{}", i)).collect();
+        let pool: Vec<&str> = pool.iter().map(String::as_str).collect();
+        let fair: HashMap<&str, usize> = pool.iter().map(|&word| (word, 100)).collect();
+        let (_, df, p) = uniformity(&pool, &fair, 10_000).unwrap();
+        assert_eq!(df, 99);
+        // Perfectly even counts are too good to be random.
+        assert!(p < SIGNIFICANCE);
+
+        let mut noisy = fair.clone();
+        for (i, word) in pool.iter().enumerate() {
+            *noisy.get_mut(word).unwrap() = if i % 2 == 0 { 90 } else { 110 };
+        }
+        assert!(uniformity(&pool, &noisy, 10_000).unwrap().2 > SIGNIFICANCE);
+
+        let skewed: HashMap<&str, usize> = pool
+            .iter()
+            .enumerate()
+            .map(|(i, &word)| (word, if i < 50 { 150 } else { 50 }))
+            .collect();
+        assert!(uniformity(&pool, &skewed, 10_000).unwrap().2 < SIGNIFICANCE);
+        assert!(uniformity(&pool, &fair, 5).is_none());
+
+        assert!(poisson_tails(0, 0.01).1 >= 1.0 - 1e-12);
+        assert!(poisson_tails(3, 0.01).1 < SIGNIFICANCE);
+        assert!(poisson_tails(1000, 1000.0).0 > 0.4);
+        assert!(poisson_tails(800, 1000.0).0 < SIGNIFICANCE);
+    }
+}
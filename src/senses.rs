@@ -0,0 +1,42 @@
+//! Polysemy filtering.
+//!
+//! Words with many meanings ("set", "run", "light") are less distinctive to
+//! remember than words with one or two. A word's sense count is summed over
+//! every part of speech, since "light" the noun and "light" the verb compete
+//! for the same spot in memory.
+
+use crate::filter::WordFilter;
+use crate::wordnet;
+use crate::PartOfSpeech;
+use std::collections::HashMap;
+use std::path::Path;
+
+pub struct SenseFilter {
+    max_senses: u32,
+    senses: HashMap<String, u32>,
+}
+
+impl SenseFilter {
+    pub fn new(max_senses: u32, dict_dir: &Path) -> Self {
+        let mut senses: HashMap<String, u32> = HashMap::new();
+        for pos in PartOfSpeech::ALL {
+            let index = dict_dir.join(format!("index.{}", pos.wordnet_suffix()));
+            for (lemma, count) in wordnet::sense_counts(&index) {
+                *senses.entry(lemma).or_default() += count;
+            }
+        }
+        SenseFilter { max_senses, senses }
+    }
+}
+
+impl WordFilter for SenseFilter {
+    fn name(&self) -> String {
+        format!("max-senses {}", self.max_senses)
+    }
+
+    fn keep(&self, _pos: PartOfSpeech, word: &str) -> bool {
+        self.senses
+            .get(word)
+            .is_none_or(|&count| count <= self.max_senses)
+    }
+}
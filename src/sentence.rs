@@ -0,0 +1,121 @@
+//! `--sentence`: phrases that read as a complete sentence, e.g.
+//! "The 42 hungry otters quietly devour pancakes."
+//!
+//! The words are drawn through the ordinary template below, so pools,
+//! filters and the entropy estimate all apply unchanged; only the rendering
+//! differs. The number decides agreement: above one, the subject is plural
+//! and the verb takes its base form; at one, the number becomes the article
+//! ("An eager otter quietly devours pancakes."). The object is always a bare
+//! plural, which needs no article.
+
+use crate::inflect::{conjugate, is_mass_noun, pluralize, VerbForm};
+use crate::normalize;
+use crate::template::Slot;
+
+/// The slots a sentence draws, in order: number, adjective, subject,
+/// adverb, verb and object.
+pub const TEMPLATE: &str = "{number} {adjective} {noun} {adverb} {verb} {noun}";
+
+/// Words starting with a vowel letter but a consonant sound, and the reverse.
+const A_EXCEPTIONS: [&str; 5] = ["uni", "use", "usu", "eu", "one"];
+const AN_EXCEPTIONS: [&str; 4] = ["hour", "honest", "honor", "heir"];
+
+/// "A" or "An", whichever reads correctly before `word`.
+fn article(word: &str) -> &'static str {
+    let starts_with = |prefixes: &[&str]| prefixes.iter().any(|p| word.starts_with(p));
+    let vowel = normalize::initial(word).is_some_and(|c| "aeiou".contains(c));
+    if (vowel && !starts_with(&A_EXCEPTIONS)) || starts_with(&AN_EXCEPTIONS) {
+        "An"
+    } else {
+        "A"
+    }
+}
+
+/// Renders the lemmas drawn for `TEMPLATE` as a sentence, as template pieces
+/// so coloring and `--abbrev` still see the slots.
+pub fn compose(number: u32, words: &[(Slot, String)]) -> Vec<(Option<Slot>, String)> {
+    let [(_, adjective), (_, subject), (_, adverb), (_, verb), (_, object)] = words else {
+        panic!("A sentence needs every word slot filled; try a lower --min-frequency");
+    };
+    let singular = number == 1 || is_mass_noun(subject);
+
+    let mut pieces = Vec::new();
+    if number == 1 {
+        pieces.push((Some(Slot::Number), article(adjective).to_string()));
+    } else {
+        pieces.push((None, "The ".to_string()));
+        pieces.push((Some(Slot::Number), number.to_string()));
+    }
+    let subject = if number == 1 {
+        subject.clone()
+    } else {
+        pluralize(subject)
+    };
+    let verb_form = if singular {
+        VerbForm::ThirdPerson
+    } else {
+        VerbForm::Base
+    };
+    for (slot, text) in [
+        (Slot::Adjective, adjective.clone()),
+        (Slot::Noun, subject),
+        (Slot::Adverb, adverb.clone()),
+        (Slot::Verb(None), conjugate(verb, verb_form)),
+        (Slot::Noun, pluralize(object)),
+    ] {
+        pieces.push((None, " ".to_string()));
+        pieces.push((Some(slot), text));
+    }
+    pieces.push((None, ".".to_string()));
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(adjective: &str, subject: &str, verb: &str, object: &str) -> Vec<(Slot, String)> {
+        [
+            (Slot::Adjective, adjective),
+            (Slot::Noun, subject),
+            (Slot::Adverb, "quietly"),
+            (Slot::Verb(None), verb),
+            (Slot::Noun, object),
+        ]
+        .into_iter()
+        .map(|(slot, word)| (slot, word.to_string()))
+        .collect()
+    }
+
+    fn sentence(number: u32, words: &[(Slot, String)]) -> String {
+        compose(number, words)
+            .into_iter()
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    #[test]
+    fn agrees_with_the_number() {
+        let words = words("hungry", "otter", "devour", "pancake");
+        assert_eq!(
+            sentence(42, &words),
+            "The 42 hungry otters quietly devour pancakes."
+        );
+        assert_eq!(
+            sentence(1, &words),
+            "A hungry otter quietly devours pancakes."
+        );
+        assert_eq!(
+            sentence(7, &self::words("brave", "mouse", "catch", "knife")),
+            "The 7 brave mice quietly catch knives."
+        );
+    }
+
+    #[test]
+    fn picks_the_article_by_sound() {
+        assert_eq!(article("eager"), "An");
+        assert_eq!(article("hungry"), "A");
+        assert_eq!(article("unique"), "A");
+        assert_eq!(article("honest"), "An");
+    }
+}
@@ -0,0 +1,108 @@
+//! `--session`, `--pick` and `--reroll`: follow-ups to the last run that
+//! reuse its flags, so one phrase of a batch, or one word of it, can be
+//! redrawn without retyping them.
+//!
+//! A `--session` run saves its arguments and phrases in `session.json` in
+//! the data directory. `--reroll N` parses those arguments again, followed
+//! by its own, replaces phrase N with a fresh one (or with `--slot`, just one
+//! of its words) and saves the result; `--pick N` prints phrase N and deletes
+//! the file. The phrases are kept in the clear, so sessions are opt-in, the
+//! file is readable only by its owner on Unix, and picking ends them.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    /// The `--session` run's arguments, without the program name.
+    pub args: Vec<String>,
+    pub phrases: Vec<String>,
+}
+
+fn path(root: &Path) -> PathBuf {
+    root.join(SESSION_FILE)
+}
+
+impl Session {
+    /// A session for a run given `args`, the program name first.
+    pub fn new(args: impl IntoIterator<Item = String>, phrases: Vec<String>) -> Session {
+        Session {
+            args: args
+                .into_iter()
+                .skip(1)
+                .filter(|arg| arg != "--session")
+                .collect(),
+            phrases,
+        }
+    }
+
+    pub fn load(root: &Path) -> Result<Session, String> {
+        let path = path(root);
+        let text = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => {
+                "no session to follow up; generate with --session first".to_string()
+            }
+            _ => format!("cannot read {}: {}", path.display(), e),
+        })?;
+        serde_json::from_str(&text).map_err(|e| {
+            format!(
+                "{} is damaged ({}); start a new --session",
+                path.display(),
+                e
+            )
+        })
+    }
+
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        fs::create_dir_all(root)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path(root))?;
+        file.write_all(
+            serde_json::to_string_pretty(self)
+                .expect("Session serializes")
+                .as_bytes(),
+        )
+    }
+
+    pub fn forget(root: &Path) {
+        let _ = fs::remove_file(path(root));
+    }
+
+    /// Index of the 1-based result `number`.
+    pub fn index(&self, number: usize) -> Result<usize, String> {
+        if (1..=self.phrases.len()).contains(&number) {
+            Ok(number - 1)
+        } else {
+            Err(format!(
+                "the session has results 1 to {}, not {}",
+                self.phrases.len(),
+                number
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remembers_the_run_without_its_session_flag() {
+        let args = ["phraseforge", "--session", "--count", "3", "--rhyme"].map(str::to_string);
+        let phrases = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let session = Session::new(args, phrases);
+        assert_eq!(session.args, ["--count", "3", "--rhyme"]);
+        assert_eq!(session.index(3), Ok(2));
+        assert!(session.index(0).is_err() && session.index(4).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! The first-run setup.
+//!
+//! A first run at a terminal, with no config file and no word lists
+//! anywhere, asks a few questions before anything is downloaded: which
+//! spelling, theme and policy to default to, and whether to download the
+//! sources now, import them from a directory (as `bootstrap` does) or stop
+//! there. The answers go to the config file's `[defaults]`, so the setup
+//! runs once; `--yes` skips it, and scripts never see it since it needs a
+//! terminal.
+
+use crate::cache::CACHE_VERSION;
+use crate::config::default_config_path;
+use crate::datadir;
+use crate::policy::Policy;
+use crate::sandbox;
+use crate::sources::Sources;
+use crate::theme::Theme;
+use crate::variants::Spelling;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// What to do about the word lists.
+pub enum Choice {
+    Download,
+    Import(PathBuf),
+    /// Stop after saving the answers.
+    Later,
+}
+
+/// Whether this is the first run and the setup should ask its questions.
+pub fn is_first_run(matches: &clap::ArgMatches) -> bool {
+    if matches.get_flag("yes")
+        || matches.subcommand().is_some()
+        || matches.contains_id("config")
+        || matches.contains_id("cache-dir")
+        || sandbox::root().is_some()
+        || !io::stdin().is_terminal()
+        || !io::stderr().is_terminal()
+    {
+        return false;
+    }
+    match default_config_path() {
+        Some(path) if !path.exists() => {}
+        _ => return false,
+    }
+    let caches = [datadir::platform_root(), datadir::fallback_root()]
+        .map(|root| root.join(format!("v{}", CACHE_VERSION)));
+    !caches
+        .iter()
+        .map(PathBuf::as_path)
+        .chain(datadir::SYSTEM_DATADIR.map(std::path::Path::new))
+        .any(crate::word_lists_exist)
+}
+
+/// Reads one answer: one of `choices`, or `default` for an empty line.
+fn ask(
+    input: &mut impl BufRead,
+    question: &str,
+    choices: &[&str],
+    default: &str,
+) -> Result<String, String> {
+    loop {
+        eprint!("{} [{}] ({}): ", question, choices.join(", "), default);
+        let _ = io::stderr().flush();
+        let mut line = String::new();
+        if input
+            .read_line(&mut line)
+            .map_err(|e| format!("cannot read the answer: {}", e))?
+            == 0
+        {
+            return Err("setup interrupted; run again to finish it, or pass --yes".to_string());
+        }
+        let answer = line.trim().to_lowercase();
+        if answer.is_empty() {
+            return Ok(default.to_string());
+        }
+        if choices.contains(&answer.as_str()) {
+            return Ok(answer);
+        }
+        eprintln!("  '{}' is not one of the choices", answer);
+    }
+}
+
+/// The `[defaults]` section for the answers; `none` and `either` are the
+/// built-in defaults and need no line.
+fn defaults_section(spelling: &str, theme: &str, policy: &str) -> String {
+    let mut text = String::from("[defaults]\n");
+    for (key, value) in [("spelling", spelling), ("theme", theme), ("policy", policy)] {
+        if value != "none" && value != "either" {
+            text.push_str(&format!("{} = \"{}\"\n", key, value));
+        }
+    }
+    text
+}
+
+/// Asks the setup's questions, saves the answers to the config file and
+/// returns what to do about the word lists.
+pub fn run(sources: &Sources) -> Result<Choice, String> {
+    let path = default_config_path().ok_or("no default config file location")?;
+    let mut input = io::stdin().lock();
+    eprintln!("Welcome to PhraseForge. A few questions first; press Enter to keep a default.");
+    eprintln!("The word lists are English; they can keep American or British spellings.");
+    let spelling = ask(&mut input, "Spelling", &Spelling::NAMES, "either")?;
+    let themes: Vec<&str> = std::iter::once("none").chain(Theme::NAMES).collect();
+    let theme = ask(&mut input, "Theme for nouns", &themes, "none")?;
+    let policies: Vec<&str> = std::iter::once("none")
+        .chain(Policy::PRESET_NAMES)
+        .collect();
+    let policy = ask(&mut input, "Password policy", &policies, "none")?;
+
+    eprintln!("The word lists are built from these sources, several megabytes in all:");
+    for (name, url) in sources.word_list_sources() {
+        eprintln!("  {}: {}", name, url);
+    }
+    let choice = match ask(
+        &mut input,
+        "Download them now, import them from a directory, or stop here",
+        &["download", "import", "later"],
+        "download",
+    )?
+    .as_str()
+    {
+        "import" => {
+            eprint!("Directory holding the files a mirror serves, e.g. WNdb-3.0.tar.gz: ");
+            let _ = io::stderr().flush();
+            let mut line = String::new();
+            input
+                .read_line(&mut line)
+                .map_err(|e| format!("cannot read the answer: {}", e))?;
+            Choice::Import(PathBuf::from(line.trim()))
+        }
+        "later" => Choice::Later,
+        _ => Choice::Download,
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("cannot create {}: {}", parent.display(), e))?;
+    }
+    let text = format!(
+        "# Written by the first-run setup; flags on the command line override these.\n{}",
+        defaults_section(&spelling, &theme, &policy)
+    );
+    fs::write(&path, text).map_err(|e| format!("cannot write {}: {}", path.display(), e))?;
+    eprintln!("Saved your answers to {}", path.display());
+    Ok(choice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asks_until_an_answer_is_a_choice() {
+        let mut input = io::Cursor::new("purple\nFOOD\n\n");
+        let choices = ["none", "food"];
+        assert_eq!(ask(&mut input, "Theme", &choices, "none").unwrap(), "food");
+        assert_eq!(ask(&mut input, "Theme", &choices, "none").unwrap(), "none");
+        assert!(ask(&mut input, "Theme", &choices, "none").is_err());
+        assert_eq!(
+            defaults_section("either", "food", "nist"),
+            "[defaults]\ntheme = \"food\"\npolicy = \"nist\"\n"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! `--split k-of-n` and `phraseforge combine`: Shamir secret sharing of a
+//! phrase among trustees, any k of whom can rebuild it while fewer learn
+//! nothing about it.
+//!
+//! Each byte of the phrase is the constant term of its own random polynomial
+//! of degree k - 1 over GF(256), and share x holds every polynomial's value
+//! at x. Shares are written one word per byte from a fixed list of 256, so
+//! they can be read aloud or written on paper, and decode without the
+//! downloaded word lists. A share's words are its x, the threshold, the
+//! values and a checksum word that catches a mistyped or misheard word.
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// One word per byte value.
+const WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adobe", "agent", "alarm", "album", "alert", "alley", "amber",
+    "angle", "ankle", "apple", "apron", "arena", "arrow", "aspen", "atlas", "attic", "award",
+    "bacon", "badge", "bagel", "baker", "banjo", "barn", "basil", "basin", "beach", "beard",
+    "berry", "bison", "blade", "blaze", "bloom", "board", "boat", "bonus", "boot", "brain",
+    "brick", "bride", "brook", "brush", "bucket", "bugle", "cabin", "cable", "camel", "candy",
+    "canoe", "canyon", "cargo", "carpet", "castle", "cedar", "chalk", "charm", "cheek", "chess",
+    "chief", "chili", "cider", "circle", "cliff", "clock", "cloud", "clover", "coast", "cobra",
+    "cocoa", "comet", "coral", "cotton", "couch", "cradle", "crane", "crater", "crown", "crumb",
+    "cube", "cycle", "daisy", "dance", "delta", "denim", "desk", "diary", "dingo", "dock",
+    "dolphin", "donut", "dragon", "drum", "dune", "eagle", "easel", "echo", "elbow", "elk",
+    "ember", "emerald", "engine", "epic", "fable", "falcon", "fawn", "feast", "fence", "ferry",
+    "fiber", "fig", "flame", "flask", "flute", "foam", "forest", "fossil", "fox", "frost", "fudge",
+    "galaxy", "garden", "garlic", "gecko", "geyser", "ghost", "giant", "ginger", "glacier",
+    "globe", "glove", "goat", "gravy", "guitar", "gull", "hammer", "harbor", "hazel", "helmet",
+    "heron", "hinge", "hippo", "honey", "hotel", "husky", "igloo", "index", "iris", "island",
+    "ivory", "jacket", "jaguar", "jelly", "jester", "jewel", "jigsaw", "jungle", "kayak", "kettle",
+    "kiosk", "kitten", "koala", "ladder", "lagoon", "lamp", "lantern", "laser", "lava", "lemon",
+    "lentil", "lilac", "linen", "lizard", "locket", "lotus", "lunar", "magnet", "mango", "maple",
+    "marble", "meadow", "melon", "meteor", "mitten", "mocha", "moose", "mosaic", "muffin", "mural",
+    "napkin", "nectar", "needle", "nickel", "noodle", "nutmeg", "oasis", "ocean", "olive", "onion",
+    "opal", "orbit", "orchid", "otter", "owl", "oyster", "paddle", "palace", "panda", "parade",
+    "parrot", "pasta", "peach", "pebble", "pelican", "pepper", "piano", "pickle", "pigeon",
+    "pillow", "pilot", "pine", "planet", "plum", "pocket", "polka", "pony", "poppy", "puzzle",
+    "quail", "quartz", "quilt", "rabbit", "radar", "radish", "raft", "raven", "reef", "ribbon",
+    "river", "robin", "rocket", "rodeo", "ruby", "saddle", "salmon", "sandal", "satin", "scarf",
+    "shadow", "shell", "sierra", "silk", "siren", "sled", "sloth",
+];
+
+/// How many shares to make and how many rebuild the phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Split {
+    pub threshold: u8,
+    pub shares: u8,
+}
+
+impl Split {
+    /// Parses `k-of-n`; suitable for use as a clap value parser.
+    pub fn parse(text: &str) -> Result<Split, String> {
+        let (threshold, shares) = text
+            .split_once("-of-")
+            .ok_or_else(|| format!("'{}' is not of the form k-of-n, e.g. 3-of-5", text))?;
+        let number = |part: &str| {
+            part.parse::<u8>()
+                .map_err(|_| format!("'{}' is not a number of shares from 1 to 255", part))
+        };
+        let (threshold, shares) = (number(threshold)?, number(shares)?);
+        if threshold < 2 || threshold > shares {
+            return Err(format!(
+                "{}-of-{} needs 2 <= k <= n; with k = 1 every share would be the phrase",
+                threshold, shares
+            ));
+        }
+        Ok(Split { threshold, shares })
+    }
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-of-{}", self.threshold, self.shares)
+    }
+}
+
+/// A decoded share.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Share {
+    x: u8,
+    /// Shares needed to rebuild the phrase.
+    pub threshold: u8,
+    values: Vec<u8>,
+}
+
+/// Multiplication in GF(256) with the AES polynomial.
+fn multiply(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// `a` to the 254th power, which is its inverse since `a^255 = 1`.
+fn inverse(a: u8) -> u8 {
+    let mut result = 1;
+    let mut power = a;
+    let mut exponent = 254u8;
+    while exponent != 0 {
+        if exponent & 1 != 0 {
+            result = multiply(result, power);
+        }
+        power = multiply(power, power);
+        exponent >>= 1;
+    }
+    result
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    Sha256::digest(bytes)[0]
+}
+
+/// Splits `secret` into shares, each as a line of words.
+pub fn split(secret: &[u8], split: Split, rng: &mut impl Rng) -> Vec<String> {
+    let polynomials: Vec<Vec<u8>> = secret
+        .iter()
+        .map(|&byte| {
+            let mut coefficients = vec![byte];
+            coefficients.extend((1..split.threshold).map(|_| rng.random::<u8>()));
+            coefficients
+        })
+        .collect();
+    (1..=split.shares)
+        .map(|x| {
+            let mut bytes = vec![x, split.threshold];
+            // Horner's rule, from the highest coefficient down.
+            bytes.extend(polynomials.iter().map(|coefficients| {
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |value, &coefficient| multiply(value, x) ^ coefficient)
+            }));
+            bytes.push(checksum(&bytes));
+            bytes
+                .iter()
+                .map(|&byte| WORDS[byte as usize])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Decodes a share's words, ignoring a `share 2/5:` style label before them.
+pub fn decode(text: &str) -> Result<Share, String> {
+    let words = text.rsplit(':').next().unwrap_or(text);
+    let bytes: Vec<u8> = words
+        .split_whitespace()
+        .map(|word| {
+            WORDS
+                .iter()
+                .position(|known| known.eq_ignore_ascii_case(word))
+                .map(|byte| byte as u8)
+                .ok_or_else(|| format!("'{}' is not a share word", word))
+        })
+        .collect::<Result<_, _>>()?;
+    let Some((&check, body)) = bytes.split_last().filter(|(_, body)| body.len() > 2) else {
+        return Err("a share has at least four words".to_string());
+    };
+    if checksum(body) != check {
+        return Err("the share's checksum does not match; check each word".to_string());
+    }
+    Ok(Share {
+        x: body[0],
+        threshold: body[1],
+        values: body[2..].to_vec(),
+    })
+}
+
+/// Rebuilds the secret from at least a threshold's worth of `shares`.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, String> {
+    let first = shares.first().ok_or("no shares given")?;
+    if shares
+        .iter()
+        .any(|share| share.threshold != first.threshold || share.values.len() != first.values.len())
+    {
+        return Err("the shares come from different splits".to_string());
+    }
+    let mut used: Vec<&Share> = Vec::new();
+    for share in shares {
+        if share.x == 0 {
+            return Err("share 0 is not a valid share".to_string());
+        }
+        if used.iter().any(|other| other.x == share.x) {
+            return Err(format!("share {} was given twice", share.x));
+        }
+        used.push(share);
+    }
+    if used.len() < first.threshold as usize {
+        return Err(format!(
+            "{} shares given but {} are needed",
+            used.len(),
+            first.threshold
+        ));
+    }
+    used.truncate(first.threshold as usize);
+
+    // Lagrange interpolation at x = 0, where subtraction is XOR.
+    let weights: Vec<u8> = used
+        .iter()
+        .map(|share| {
+            used.iter()
+                .filter(|other| other.x != share.x)
+                .fold(1, |weight, other| {
+                    multiply(weight, multiply(other.x, inverse(other.x ^ share.x)))
+                })
+        })
+        .collect();
+    Ok((0..first.values.len())
+        .map(|i| {
+            used.iter()
+                .zip(&weights)
+                .fold(0, |secret, (share, &weight)| {
+                    secret ^ multiply(share.values[i], weight)
+                })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn any_threshold_of_shares_rebuilds_the_phrase() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let phrase = "42-brave-otters-bounce-briskly";
+        let split = Split::parse("3-of-5").unwrap();
+        let shares: Vec<Share> = super::split(phrase.as_bytes(), split, &mut rng)
+            .iter()
+            .map(|share| decode(share).unwrap())
+            .collect();
+        for picked in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset: Vec<Share> = picked.iter().map(|&i| shares[i].clone()).collect();
+            assert_eq!(combine(&subset).unwrap(), phrase.as_bytes());
+        }
+        assert!(combine(&shares[..2]).is_err());
+        assert!(Split::parse("1-of-3").is_err());
+        assert!(Split::parse("4-of-3").is_err());
+
+        let mut words: Vec<String> = super::split(b"x", split, &mut rng)[0]
+            .split(' ')
+            .map(str::to_string)
+            .collect();
+        words[2] = if words[2] == "acid" { "acorn" } else { "acid" }.to_string();
+        assert!(decode(&words.join(" ")).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+//! `phraseforge similar`: variations on a phrase the user almost liked.
+//!
+//! The phrase is split back into the template's slots by the literal text
+//! between them, so it must be given with the same `--template` or
+//! `--separator` it was generated with. Each variation keeps the number and
+//! all but one or two of the words, which are redrawn from the same pools
+//! under the current filters and `--min-frequency`.
+
+use crate::generate::{eligible, render_slot, GenerationOptions};
+use crate::template::{Slot, Template, Token};
+use crate::{leet, WordLists};
+use rand::seq::IndexedRandom;
+use rand::Rng;
+
+/// The rendered text of each template token, as in `GeneratedPhrase::pieces`.
+pub struct Pieces(Vec<(Option<Slot>, String)>);
+
+impl Pieces {
+    /// Splits `phrase` into the tokens of `template`.
+    pub fn parse(template: &Template, phrase: &str) -> Result<Pieces, String> {
+        let mismatch = || {
+            "the phrase does not match the template; pass the --template or --separator it was generated with"
+                .to_string()
+        };
+        let tokens = template.tokens();
+        let mut pieces = Vec::new();
+        let mut rest = phrase;
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Literal(text) => {
+                    rest = rest.strip_prefix(text.as_str()).ok_or_else(mismatch)?;
+                    pieces.push((None, text.clone()));
+                }
+                Token::Slot(slot) => {
+                    let end = match tokens.get(i + 1) {
+                        None => rest.len(),
+                        Some(Token::Literal(next)) => rest.find(next.as_str()).ok_or_else(mismatch)?,
+                        Some(Token::Slot(_)) => {
+                            return Err("cannot tell where one word ends and the next begins; the template needs text between its slots".to_string())
+                        }
+                    };
+                    if end == 0 {
+                        return Err(mismatch());
+                    }
+                    pieces.push((Some(*slot), rest[..end].to_string()));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        if !rest.is_empty() {
+            return Err(mismatch());
+        }
+
+        if template.has_number() && Self::number(&pieces).is_none() {
+            return Err(
+                "the phrase has no number where the template's {number} slot is".to_string(),
+            );
+        }
+        Ok(Pieces(pieces))
+    }
+
+    fn number(pieces: &[(Option<Slot>, String)]) -> Option<u32> {
+        pieces
+            .iter()
+            .find(|(slot, _)| *slot == Some(Slot::Number))
+            .and_then(|(_, text)| text.parse().ok())
+    }
+
+    /// Positions of the word slots, the ones a variation may swap.
+    fn word_positions(&self) -> Vec<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, (slot, _))| slot.is_some_and(|slot| slot != Slot::Number))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// A copy with `swaps` randomly chosen words redrawn, each different from
+    /// every word already in the phrase. A slot whose pool has nothing else
+    /// to offer keeps its word.
+    pub fn vary(
+        &self,
+        word_lists: &WordLists,
+        options: &GenerationOptions,
+        swaps: usize,
+        rng: &mut impl Rng,
+    ) -> String {
+        let mut pieces = self.0.clone();
+        for &position in self.word_positions().choose_multiple(rng, swaps) {
+            self.redraw(&mut pieces, position, word_lists, options, rng);
+        }
+        pieces.into_iter().map(|(_, text)| text).collect()
+    }
+
+    /// A copy with the `word`th word, counting from 1, redrawn as `vary`
+    /// would; for `--reroll --slot`.
+    pub fn reroll(
+        &self,
+        word: usize,
+        word_lists: &WordLists,
+        options: &GenerationOptions,
+        rng: &mut impl Rng,
+    ) -> Result<String, String> {
+        let positions = self.word_positions();
+        let &position = word
+            .checked_sub(1)
+            .and_then(|i| positions.get(i))
+            .ok_or_else(|| {
+                format!(
+                    "the phrase has words 1 to {}, not {}",
+                    positions.len(),
+                    word
+                )
+            })?;
+        let mut pieces = self.0.clone();
+        self.redraw(&mut pieces, position, word_lists, options, rng);
+        Ok(pieces.into_iter().map(|(_, text)| text).collect())
+    }
+
+    fn redraw(
+        &self,
+        pieces: &mut [(Option<Slot>, String)],
+        position: usize,
+        word_lists: &WordLists,
+        options: &GenerationOptions,
+        rng: &mut impl Rng,
+    ) {
+        let plural = Self::number(&self.0).is_some_and(|n| n > 1);
+        let slot = pieces[position].0.expect("Word positions hold slots");
+        let pool = word_lists.pool(slot).expect("Word slots have a pool");
+        let taken: Vec<&str> = self
+            .word_positions()
+            .iter()
+            .map(|&i| pieces[i].1.as_str())
+            .collect();
+        let candidates: Vec<String> = eligible(pool, &options.min_frequency.of_slot(slot), None)
+            .map(|entry| render_slot(&entry.word, slot, plural, options))
+            .filter(|rendered| !taken.contains(&rendered.as_str()))
+            .collect();
+        if let Some(rendered) = candidates.choose(rng) {
+            pieces[position].1 = match options.leet {
+                Some(level) => leet::apply(rendered, level, rng),
+                None => rendered.clone(),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_a_phrase_into_its_template_slots() {
+        let template = Template::default();
+        let pieces = Pieces::parse(&template, "7-brave-badgers-bounce-briskly").unwrap();
+        assert_eq!(pieces.0.len(), 9);
+        assert_eq!(pieces.0[2], (Some(Slot::Adjective), "brave".to_string()));
+        assert_eq!(pieces.0[8], (Some(Slot::Adverb), "briskly".to_string()));
+        assert_eq!(Pieces::number(&pieces.0), Some(7));
+        assert_eq!(pieces.word_positions(), [2, 4, 6, 8]);
+
+        assert!(Pieces::parse(&template, "7-brave-badgers-bounce").is_err());
+        assert!(Pieces::parse(&template, "seven-brave-badgers-bounce-briskly").is_err());
+        let adjacent = Template::parse("{adjective}{noun}").unwrap();
+        assert!(Pieces::parse(&adjacent, "bravebadgers").is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Where upstream data is downloaded from.
+//!
+//! Each source can be pointed elsewhere individually, or all at once with a
+//! mirror that serves the same file names (e.g. a corporate artifact store).
+//! Proxies come from `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` as usual, or from
+//! `--proxy`/the config file. Every HTTP client is built here, which is where
+//! `--sandbox` cuts the network off.
+
+use crate::config::SourcesConfig;
+use crate::corpus::{COMMON_PASSWORDS_FILE, COMMON_PASSWORDS_URL};
+use crate::diceware::{EFF_LARGE_WORDLIST_FILE, EFF_LARGE_WORDLIST_URL};
+use crate::dictionary::{Dictionary, DEFAULT_DICTIONARY};
+use crate::exit;
+use crate::hibp::HIBP_RANGE_URL;
+use crate::moby::{self, MOBY_POS_URL};
+use crate::provenance::{self, TrustKey, TrustedChecksums, BUNDLED_KEY};
+use crate::sandbox;
+use crate::scowl::{SCOWL_ARCHIVE, SCOWL_URL};
+use crate::{
+    frequency_list_file, DEFAULT_FREQUENCY_LIST, FREQUENCY_LISTS,
+    HERMIT_DAVES_FREQUENTLY_USED_WORD_LISTS_URL, WORDNET_ARCHIVE, WORDNET_URL,
+};
+use clap::ArgMatches;
+use reqwest::blocking::{Client, Response};
+use reqwest::Proxy;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct Sources {
+    /// Which dictionary the word lists' parts of speech come from.
+    pub dictionary: Dictionary,
+    pub wordnet: String,
+    pub scowl: String,
+    pub moby_pos: String,
+    pub frequency_list: String,
+    /// Which frequency list, e.g. `en_50k`; each is cached separately.
+    pub frequency_list_variant: String,
+    pub eff_word_list: String,
+    /// The breached-password list `--reject-common` screens against.
+    pub common_passwords: String,
+    /// The range API `--hibp` appends hash prefixes to.
+    pub hibp_range: String,
+    /// What downloads are verified with; see [`crate::provenance`].
+    pub trust_key: Option<TrustKey>,
+    pub signed_manifest: Option<String>,
+    proxy: Option<String>,
+}
+
+/// Picks a source URL: an explicit override, then the mirror, then upstream.
+fn resolve(
+    explicit: Option<&String>,
+    mirror: Option<&String>,
+    file_name: &str,
+    upstream: &str,
+) -> String {
+    match (explicit, mirror) {
+        (Some(url), _) => url.clone(),
+        (None, Some(mirror)) => format!("{}/{}", mirror.trim_end_matches('/'), file_name),
+        (None, None) => upstream.to_string(),
+    }
+}
+
+impl Sources {
+    /// Command-line flags take precedence over the config file.
+    pub fn new(matches: &ArgMatches, config: &SourcesConfig) -> Result<Self, String> {
+        let flag_or_config = |flag: &str, configured: &Option<String>| {
+            matches
+                .get_one::<String>(flag)
+                .or(configured.as_ref())
+                .cloned()
+        };
+        let mirror = flag_or_config("mirror", &config.mirror);
+        let wordnet = flag_or_config("wordnet-url", &config.wordnet_url);
+        let scowl = flag_or_config("scowl-url", &config.scowl_url);
+        let moby_pos = flag_or_config("moby-pos-url", &config.moby_pos_url);
+        let dictionary = flag_or_config("dictionary", &config.dictionary)
+            .unwrap_or_else(|| DEFAULT_DICTIONARY.to_string());
+        let dictionary = Dictionary::from_name(&dictionary)
+            .map_err(|e| format!("[sources] dictionary: {}", e))?;
+        let frequency_list = flag_or_config("frequency-list-url", &config.frequency_list_url);
+        let variant = flag_or_config("frequency-list", &config.frequency_list)
+            .unwrap_or_else(|| DEFAULT_FREQUENCY_LIST.to_string());
+        if !FREQUENCY_LISTS.contains(&variant.as_str()) {
+            return Err(format!(
+                "unknown frequency list '{}' in config (expected one of: {})",
+                variant,
+                FREQUENCY_LISTS.join(", ")
+            ));
+        }
+        let variant_file = frequency_list_file(&variant);
+        let explicit_key = matches
+            .get_one::<PathBuf>("trust-key")
+            .or(config.trust_key.as_ref());
+        let trust_key = match explicit_key {
+            Some(path) => Some(provenance::key_file(path)?),
+            None => BUNDLED_KEY.map(TrustKey::Bundled),
+        };
+        let signed_manifest = config.signed_manifest_url.clone().or_else(|| {
+            mirror.as_ref().map(|mirror| {
+                format!(
+                    "{}/{}",
+                    mirror.trim_end_matches('/'),
+                    provenance::MANIFEST_FILE
+                )
+            })
+        });
+        if explicit_key.is_some() && signed_manifest.is_none() {
+            return Err(
+                "--trust-key needs a signed manifest: set --mirror, or signed_manifest_url in [sources]"
+                    .to_string(),
+            );
+        }
+
+        Ok(Sources {
+            dictionary,
+            wordnet: resolve(
+                wordnet.as_ref(),
+                mirror.as_ref(),
+                WORDNET_ARCHIVE,
+                WORDNET_URL,
+            ),
+            scowl: resolve(scowl.as_ref(), mirror.as_ref(), SCOWL_ARCHIVE, SCOWL_URL),
+            moby_pos: resolve(
+                moby_pos.as_ref(),
+                mirror.as_ref(),
+                moby::POS_FILE,
+                MOBY_POS_URL,
+            ),
+            frequency_list: resolve(
+                frequency_list.as_ref(),
+                mirror.as_ref(),
+                &variant_file,
+                &format!(
+                    "{}/{}",
+                    HERMIT_DAVES_FREQUENTLY_USED_WORD_LISTS_URL, variant_file
+                ),
+            ),
+            frequency_list_variant: variant,
+            eff_word_list: resolve(
+                config.eff_word_list_url.as_ref(),
+                mirror.as_ref(),
+                EFF_LARGE_WORDLIST_FILE,
+                EFF_LARGE_WORDLIST_URL,
+            ),
+            common_passwords: resolve(
+                config.common_passwords_url.as_ref(),
+                mirror.as_ref(),
+                COMMON_PASSWORDS_FILE,
+                COMMON_PASSWORDS_URL,
+            ),
+            hibp_range: config
+                .hibp_range_url
+                .clone()
+                .unwrap_or_else(|| HIBP_RANGE_URL.to_string()),
+            trust_key,
+            signed_manifest,
+            proxy: flag_or_config("proxy", &config.proxy),
+        })
+    }
+
+    pub fn frequency_list_file(&self) -> String {
+        frequency_list_file(&self.frequency_list_variant)
+    }
+
+    /// The sources the dictionary is read from.
+    pub fn dictionary_sources(&self) -> Vec<(&'static str, &str)> {
+        match self.dictionary {
+            Dictionary::WordNet => vec![("WordNet", &self.wordnet)],
+            Dictionary::Scowl(_) => vec![("SCOWL", &self.scowl), ("Moby POS list", &self.moby_pos)],
+            Dictionary::Moby => vec![("Moby POS list", &self.moby_pos)],
+        }
+    }
+
+    /// The sources the word lists are built from, for the cache manifest.
+    pub fn word_list_sources(&self) -> Vec<(&'static str, &str)> {
+        let mut sources = self.dictionary_sources();
+        sources.push(("frequency list", &self.frequency_list));
+        sources
+    }
+
+    /// The verified checksums downloads must match, when both a trust key
+    /// and a signed manifest are configured.
+    pub fn trusted_checksums(&self) -> Result<Option<TrustedChecksums>, String> {
+        match (&self.trust_key, &self.signed_manifest) {
+            (Some(key), Some(manifest)) => {
+                TrustedChecksums::fetch(&self.client(), manifest, key).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Checks `bytes` downloaded from `url` against the signed manifest, if
+    /// there is one.
+    pub fn verify(&self, url: &str, bytes: &[u8]) -> Result<(), String> {
+        match self.trusted_checksums()? {
+            Some(checksums) => checksums.check(url, bytes),
+            None => Ok(()),
+        }
+    }
+
+    pub fn proxy_url(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    fn proxy(&self) -> Option<Proxy> {
+        self.proxy
+            .as_ref()
+            .map(|proxy| Proxy::all(proxy).expect("Invalid proxy URL"))
+    }
+
+    pub fn client(&self) -> Client {
+        sandbox::check_network().unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+        let mut builder = Client::builder();
+        if let Some(proxy) = self.proxy() {
+            builder = builder.proxy(proxy);
+        }
+        builder.build().expect("Failed to build HTTP client")
+    }
+
+    /// A client for [`crate::download`]'s concurrent downloads.
+    pub fn async_client(&self) -> reqwest::Client {
+        sandbox::check_network().unwrap_or_else(|e| exit::fail(exit::NETWORK, e));
+        let mut builder = reqwest::Client::builder();
+        if let Some(proxy) = self.proxy() {
+            builder = builder.proxy(proxy);
+        }
+        builder.build().expect("Failed to build HTTP client")
+    }
+
+    pub fn get(&self, url: &str) -> reqwest::Result<Response> {
+        self.client().get(url).send()?.error_for_status()
+    }
+}
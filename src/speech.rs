@@ -0,0 +1,110 @@
+//! `--screen-reader`: output laid out to be listened to.
+//!
+//! Each phrase is followed by a spoken form a screen reader reads as it must
+//! be typed: words as words, numbers digit by digit, every separator by name
+//! and capitals called out, with commas where the reader should pause, so
+//! "42-Brave-otters" is read "four two, dash, capital brave, dash, otters".
+//! Words mixing letters and digits, as `--leet` makes them, are spelled out
+//! one character at a time. Nothing is conveyed by color or alignment alone:
+//! colors are off, and notes follow the phrase in words rather than in a
+//! padded column. Interactive modes say a thing once rather than redrawing
+//! it in place.
+
+use crate::spell;
+
+const DIGITS: [&str; 10] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+/// A separator's name in lowercase words, e.g. "open paren"; characters
+/// without one, such as emoji, are left for the screen reader to name.
+fn symbol(c: char) -> String {
+    let Some(name) = spell::symbol_name(c) else {
+        return c.to_string();
+    };
+    let mut words = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
+
+fn character(c: char) -> String {
+    if let Some(digit) = c.to_digit(10) {
+        DIGITS[digit as usize].to_string()
+    } else if c.is_uppercase() {
+        format!("capital {}", c.to_lowercase())
+    } else {
+        c.to_string()
+    }
+}
+
+/// How to say one run of letters and digits.
+fn word(word: &str) -> String {
+    if !word.chars().all(char::is_alphabetic) {
+        return word.chars().map(character).collect::<Vec<_>>().join(" ");
+    }
+    let letters = word.chars().count();
+    let capitals = word.chars().filter(|c| c.is_uppercase()).count();
+    if capitals == 0 {
+        word.to_string()
+    } else if capitals == 1 && word.chars().next().is_some_and(char::is_uppercase) {
+        format!("capital {}", word.to_lowercase())
+    } else if capitals == letters && letters > 1 {
+        format!("all caps {}", word.to_lowercase())
+    } else {
+        word.chars().map(character).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// `text` as a screen reader should say it, pieces separated by commas.
+pub fn spoken(text: &str) -> String {
+    let mut pieces = Vec::new();
+    let mut run = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            run.push(c);
+            continue;
+        }
+        if !run.is_empty() {
+            pieces.push(word(&run));
+            run.clear();
+        }
+        pieces.push(symbol(c));
+    }
+    if !run.is_empty() {
+        pieces.push(word(&run));
+    }
+    pieces.join(", ")
+}
+
+/// The note after a phrase in an interactive batch, in words rather than a
+/// column: its estimated bits and any weakness zxcvbn found.
+pub fn note(bits: f64, weakness: Option<&str>) -> String {
+    match weakness {
+        Some(weakness) => format!(", {:.1} bits, weaker than estimated: {}.", bits, weakness),
+        None => format!(", {:.1} bits.", bits),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn says_what_must_be_typed() {
+        assert_eq!(
+            spoken("42-Brave-otters"),
+            "four two, dash, capital brave, dash, otters"
+        );
+        assert_eq!(
+            spoken("br4ve OTTERS(sing)"),
+            "b r four v e, space, all caps otters, open paren, sing, close paren"
+        );
+        assert_eq!(spoken("café🦊"), "café, 🦊");
+        assert_eq!(note(52.04, None), ", 52.0 bits.");
+    }
+}
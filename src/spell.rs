@@ -0,0 +1,104 @@
+//! NATO phonetic spelling for reading passphrases aloud.
+
+const LETTERS: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett",
+    "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango",
+    "Uniform", "Victor", "Whiskey", "Xray", "Yankee", "Zulu",
+];
+
+/// Digits use the ICAO radio pronunciations ("Tree", "Fife", "Niner") that
+/// are hard to mishear.
+const DIGITS: [&str; 10] = [
+    "Zero", "One", "Two", "Tree", "Four", "Fife", "Six", "Seven", "Eight", "Niner",
+];
+
+pub fn symbol_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '-' => "Dash",
+        '_' => "Underscore",
+        ' ' => "Space",
+        '.' => "Period",
+        ',' => "Comma",
+        ':' => "Colon",
+        ';' => "Semicolon",
+        '!' => "Exclamation",
+        '?' => "Question",
+        '@' => "At",
+        '#' => "Hash",
+        '$' => "Dollar",
+        '%' => "Percent",
+        '&' => "Ampersand",
+        '*' => "Asterisk",
+        '+' => "Plus",
+        '=' => "Equals",
+        '/' => "Slash",
+        '\\' => "Backslash",
+        '|' => "Pipe",
+        '~' => "Tilde",
+        '^' => "Caret",
+        '\'' => "Apostrophe",
+        '"' => "Quote",
+        '(' => "OpenParen",
+        ')' => "CloseParen",
+        '[' => "OpenBracket",
+        ']' => "CloseBracket",
+        '{' => "OpenBrace",
+        '}' => "CloseBrace",
+        '<' => "LessThan",
+        '>' => "GreaterThan",
+        _ => return None,
+    })
+}
+
+fn spell_char(c: char) -> String {
+    if c.is_ascii_lowercase() {
+        LETTERS[(c as u8 - b'a') as usize].to_string()
+    } else if c.is_ascii_uppercase() {
+        format!("Capital-{}", LETTERS[(c as u8 - b'A') as usize])
+    } else if let Some(digit) = c.to_digit(10) {
+        DIGITS[digit as usize].to_string()
+    } else if let Some(name) = symbol_name(c) {
+        name.to_string()
+    } else {
+        format!("U+{:04X}", c as u32)
+    }
+}
+
+/// Spells `text` one character at a time, e.g. `ab-1` becomes
+/// `Alfa-Bravo-Dash-One`. Every code word is a single token so the hyphens
+/// only ever separate characters.
+pub fn spell(text: &str) -> String {
+    text.chars().map(spell_char).collect::<Vec<_>>().join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spells_each_kind_of_character() {
+        let cases = [
+            ('a', "Alfa"),
+            ('z', "Zulu"),
+            ('A', "Capital-Alfa"),
+            ('Q', "Capital-Quebec"),
+            ('3', "Tree"),
+            ('5', "Fife"),
+            ('9', "Niner"),
+            ('-', "Dash"),
+            (' ', "Space"),
+            ('(', "OpenParen"),
+            ('!', "Exclamation"),
+            ('é', "U+00E9"),
+        ];
+        for (c, expected) in cases {
+            assert_eq!(spell_char(c), expected, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn joins_code_words_with_hyphens() {
+        assert_eq!(spell("ab-1"), "Alfa-Bravo-Dash-One");
+        assert_eq!(spell("Ok 9"), "Capital-Oscar-Kilo-Space-Niner");
+    }
+}
@@ -0,0 +1,131 @@
+//! Handing generated phrases straight to a password manager
+//! (`phraseforge store`), so they never pass through the terminal or a
+//! clipboard.
+
+use crate::secret::{self, Source};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Pass,
+    KeePassXc,
+}
+
+impl Backend {
+    pub const NAMES: [&'static str; 2] = ["pass", "keepassxc"];
+
+    pub fn from_name(name: &str) -> Option<Backend> {
+        match name {
+            "pass" => Some(Backend::Pass),
+            "keepassxc" => Some(Backend::KeePassXc),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Pass => "pass",
+            Backend::KeePassXc => "keepassxc",
+        }
+    }
+}
+
+/// Where a phrase should be stored.
+pub struct StoreTarget {
+    pub backend: Backend,
+    pub entry: String,
+    /// KeePassXC database file; unused by `pass`.
+    pub database: Option<PathBuf>,
+    /// Replace an existing entry instead of failing.
+    pub force: bool,
+    /// Where the KeePassXC database password comes from.
+    pub unlock: Source,
+}
+
+/// Runs `command`, writing `input` to its stdin.
+pub(crate) fn run_with_input(mut command: Command, input: &str) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", program, e))?;
+    child
+        .stdin
+        .take()
+        .expect("Child stdin is piped")
+        .write_all(input.as_bytes())
+        .map_err(|e| format!("failed to send phrase to {}: {}", program, e))?;
+    let status = child
+        .wait()
+        .map_err(|e| format!("failed to wait for {}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", program, status))
+    }
+}
+
+/// Like `run_with_input`, but returns what `command` wrote to stdout. The
+/// input is written from another thread so a child that streams its output
+/// can't deadlock against a full stdin pipe.
+pub(crate) fn run_capturing(mut command: Command, input: &str) -> Result<Vec<u8>, String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", program, e))?;
+    let mut stdin = child.stdin.take().expect("Child stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed to wait for {}: {}", program, e))?;
+    let written = writer.join().expect("Writer thread panicked");
+    // A child that failed early closes stdin; its exit status is the better
+    // explanation.
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", program, output.status));
+    }
+    written.map_err(|e| format!("failed to send input to {}: {}", program, e))?;
+    Ok(output.stdout)
+}
+
+impl StoreTarget {
+    pub fn store(&self, secret: &str) -> Result<(), String> {
+        match self.backend {
+            Backend::Pass => {
+                let mut command = Command::new("pass");
+                command.args(["insert", "--multiline"]);
+                if self.force {
+                    command.arg("--force");
+                }
+                command.arg(&self.entry);
+                run_with_input(command, &format!("{}\n", secret))
+            }
+            Backend::KeePassXc => {
+                let database = self
+                    .database
+                    .as_ref()
+                    .expect("--database is required for keepassxc");
+                let unlock = secret::read(
+                    &self.unlock,
+                    &format!("Password to unlock {}: ", database.display()),
+                )
+                .map_err(|e| format!("failed to read database password: {}", e))?;
+
+                let mut command = Command::new("keepassxc-cli");
+                command
+                    .arg(if self.force { "edit" } else { "add" })
+                    .arg("--password-prompt")
+                    .arg(database)
+                    .arg(&self.entry);
+                // keepassxc-cli reads the unlock password, then the entry
+                // password; some versions ask for it a second time to confirm.
+                run_with_input(command, &format!("{}\n{}\n{}\n", unlock, secret, secret))
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Secondary strength scoring with zxcvbn.
+//!
+//! The combinatorial estimate in `entropy` assumes an attacker who knows
+//! exactly how phrases are built. zxcvbn looks at the finished text instead,
+//! for what a cracker would try first: well-known phrases, keyboard walks,
+//! dates and repeats. A phrase is only as strong as the weaker of the two, so
+//! that is the figure reported and enforced.
+
+use std::f64::consts::LOG2_10;
+
+/// zxcvbn's verdict on one secret.
+pub struct Score {
+    /// log2 of the guesses zxcvbn expects an attacker to need.
+    pub bits: f64,
+    /// 0 (too guessable) to 4 (very unguessable).
+    pub score: u8,
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+pub fn score(secret: &str) -> Score {
+    let estimate = zxcvbn::zxcvbn(secret, &[]);
+    let feedback = estimate.feedback();
+    Score {
+        bits: estimate.guesses_log10() * LOG2_10,
+        score: estimate.score() as u8,
+        warning: feedback
+            .and_then(|f| f.warning())
+            .map(|warning| warning.to_string()),
+        suggestions: feedback
+            .map(|f| f.suggestions().iter().map(ToString::to_string).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// The weaker of `combinatorial_bits` and zxcvbn's estimate for `secret`,
+/// with zxcvbn's warning when it is the weaker one.
+pub fn weaker(combinatorial_bits: f64, secret: &str) -> (f64, Option<String>) {
+    let zxcvbn = score(secret);
+    if zxcvbn.bits < combinatorial_bits {
+        let reason = zxcvbn
+            .warning
+            .unwrap_or_else(|| "matches a common pattern".to_string());
+        (zxcvbn.bits, Some(reason))
+    } else {
+        (combinatorial_bits, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catches_patterns_the_combinatorial_estimate_misses() {
+        let (bits, reason) = weaker(40.0, "qwertyuiop");
+        assert!(bits < 40.0);
+        assert!(reason.is_some());
+        assert_eq!(weaker(20.0, "7-quiet-otters-swim-boldly").0, 20.0);
+    }
+}
@@ -0,0 +1,217 @@
+//! Phrase templates.
+//!
+//! A template is literal text with `{slot}` placeholders, e.g.
+//! `{number}-{adjective}-{noun}-{verb:past}-{adverb}`. Slots may carry a
+//! modifier after a colon to select an inflected form.
+
+use crate::inflect::VerbForm;
+use crate::PartOfSpeech;
+use std::fmt;
+
+pub const DEFAULT_TEMPLATE: &str = "{number}-{adjective}-{noun}-{verb}-{adverb}";
+
+/// Built-in templates for `--preset`, by name.
+pub const PRESETS: [(&str, &str); 5] = [
+    ("classic", DEFAULT_TEMPLATE),
+    ("short", "{number}-{adjective}-{noun}"),
+    // Reads as a sentence: "7 brave badgers bounced briskly".
+    (
+        "sentence",
+        "{number} {adjective} {noun} {verb:past} {adverb}",
+    ),
+    (
+        "noun-pair",
+        "{adjective}-{noun}-{number}-{adjective}-{noun}",
+    ),
+    // xkcd 936: "correct horse battery staple".
+    ("xkcd4", "{adjective} {noun} {noun} {noun}"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Number,
+    Adjective,
+    Noun,
+    /// A verb, optionally pinned to a form; `None` uses `--verb-form`.
+    Verb(Option<VerbForm>),
+    Adverb,
+}
+
+impl Slot {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Slot::Number => "number",
+            Slot::Adjective => "adjective",
+            Slot::Noun => "noun",
+            Slot::Verb(_) => "verb",
+            Slot::Adverb => "adverb",
+        }
+    }
+
+    /// The part of speech a slot draws from, or `None` for the number slot.
+    pub fn part_of_speech(&self) -> Option<PartOfSpeech> {
+        match self {
+            Slot::Number => None,
+            Slot::Adjective => Some(PartOfSpeech::Adjective),
+            Slot::Noun => Some(PartOfSpeech::Noun),
+            Slot::Verb(_) => Some(PartOfSpeech::Verb),
+            Slot::Adverb => Some(PartOfSpeech::Adverb),
+        }
+    }
+
+    fn parse(spec: &str) -> Result<Slot, String> {
+        let (name, modifier) = match spec.split_once(':') {
+            Some((name, modifier)) => (name, Some(modifier)),
+            None => (spec, None),
+        };
+
+        match (name, modifier) {
+            ("number", None) => Ok(Slot::Number),
+            ("adjective", None) => Ok(Slot::Adjective),
+            ("noun", None) => Ok(Slot::Noun),
+            ("adverb", None) => Ok(Slot::Adverb),
+            ("verb", None) => Ok(Slot::Verb(None)),
+            ("verb", Some(form)) => VerbForm::from_name(form)
+                .map(|form| Slot::Verb(Some(form)))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown verb form '{}' (expected one of: {})",
+                        form,
+                        VerbForm::NAMES.join(", ")
+                    )
+                }),
+            (name, Some(modifier)) => Err(format!(
+                "slot '{}' does not accept a modifier (got '{}')",
+                name, modifier
+            )),
+            (name, None) => Err(format!("unknown slot '{{{}}}'", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Literal(String),
+    Slot(Slot),
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    tokens: Vec<Token>,
+}
+
+impl Template {
+    /// Parses a template string; suitable for use as a clap value parser.
+    pub fn parse(text: &str) -> Result<Template, String> {
+        let mut tokens = Vec::new();
+        let mut rest = text;
+
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                tokens.push(Token::Literal(rest[..open].to_string()));
+            }
+            let close = rest[open..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed '{{' in template '{}'", text))?;
+            tokens.push(Token::Slot(Slot::parse(&rest[open + 1..open + close])?));
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            tokens.push(Token::Literal(rest.to_string()));
+        }
+
+        if !tokens.iter().any(|t| matches!(t, Token::Slot(_))) {
+            return Err(format!("template '{}' contains no slots", text));
+        }
+        Ok(Template { tokens })
+    }
+
+    /// The built-in template called `name`, if any.
+    pub fn preset(name: &str) -> Option<Template> {
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, text)| Template::parse(text).expect("Preset templates are valid"))
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        self.tokens.iter().filter_map(|t| match t {
+            Token::Slot(slot) => Some(*slot),
+            Token::Literal(_) => None,
+        })
+    }
+
+    pub fn has_number(&self) -> bool {
+        self.slots().any(|slot| slot == Slot::Number)
+    }
+
+    /// The same slots with every literal between them replaced by
+    /// `separator`, e.g. `{noun}-{verb}` becomes `{noun}🦊{verb}`.
+    pub fn with_separator(&self, separator: &str) -> Template {
+        let tokens = self
+            .tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(_) => Token::Literal(separator.to_string()),
+                Token::Slot(slot) => Token::Slot(*slot),
+            })
+            .collect();
+        Template { tokens }
+    }
+}
+
+/// The template as it would be written on the command line.
+impl fmt::Display for Template {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Literal(text) => write!(f, "{}", text)?,
+                Token::Slot(Slot::Verb(Some(form))) => write!(f, "{{verb:{}}}", form.name())?,
+                Token::Slot(slot) => write!(f, "{{{}}}", slot.name())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template::parse(DEFAULT_TEMPLATE).expect("Default template is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_parse() {
+        for (name, _) in PRESETS {
+            assert!(Template::preset(name).is_some(), "{}", name);
+        }
+        assert!(Template::preset("nonesuch").is_none());
+    }
+
+    #[test]
+    fn parse_never_panics() {
+        use rand::rngs::StdRng;
+        use rand::seq::IndexedRandom;
+        use rand::{Rng, SeedableRng};
+
+        let alphabet: Vec<char> = "{}:-nounverbpast🦊 ".chars().collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10_000 {
+            let len = rng.random_range(0..24);
+            let text: String = (0..len)
+                .map(|_| *alphabet.choose(&mut rng).unwrap())
+                .collect();
+            if let Ok(template) = Template::parse(&text) {
+                assert!(template.slots().count() > 0);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Semantic themes that restrict nouns to one branch of WordNet.
+//!
+//! Each theme names a few root synsets in WordNet 3.0's `data.noun`; a noun
+//! belongs to the theme if any of its senses sits below one of those roots in
+//! the hypernym hierarchy.
+
+use crate::filter::WordFilter;
+use crate::wordnet;
+use crate::PartOfSpeech;
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Animals,
+    Food,
+    Nature,
+    Tools,
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 4] = ["animals", "food", "nature", "tools"];
+
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "animals" => Some(Theme::Animals),
+            "food" => Some(Theme::Food),
+            "nature" => Some(Theme::Nature),
+            "tools" => Some(Theme::Tools),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::Animals => "animals",
+            Theme::Food => "food",
+            Theme::Nature => "nature",
+            Theme::Tools => "tools",
+        }
+    }
+
+    /// WordNet 3.0 synset offsets at the top of the theme.
+    fn roots(&self) -> &'static [u64] {
+        match self {
+            // animal, animate_being, beast, brute, creature, fauna
+            Theme::Animals => &[15388],
+            // food, nutrient; food, solid_food
+            Theme::Food => &[21265, 7555863],
+            // plant, flora; natural_object; body_of_water; geological_formation
+            Theme::Nature => &[17222, 19308, 9225146, 9287968],
+            // tool
+            Theme::Tools => &[4451818],
+        }
+    }
+}
+
+/// Drops nouns that fall outside the theme; other parts of speech pass through.
+pub struct ThemeFilter {
+    theme: Theme,
+    lemmas: HashSet<String>,
+}
+
+impl ThemeFilter {
+    pub fn new(theme: Theme, dict_dir: &Path) -> Self {
+        let lemmas = wordnet::hyponym_lemmas(&dict_dir.join("data.noun"), theme.roots());
+        if lemmas.is_empty() {
+            log::warn!("No WordNet nouns found for theme '{}'", theme.name());
+        }
+        ThemeFilter { theme, lemmas }
+    }
+}
+
+impl WordFilter for ThemeFilter {
+    fn name(&self) -> String {
+        format!("theme:{}", self.theme.name())
+    }
+
+    fn keep(&self, pos: PartOfSpeech, word: &str) -> bool {
+        pos != PartOfSpeech::Noun || self.lemmas.contains(word)
+    }
+}
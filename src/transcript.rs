@@ -0,0 +1,159 @@
+//! Session transcripts for security review.
+//!
+//! A transcript is a single JSON document describing how a run produced its
+//! passphrases: the effective configuration, hashes of the word sources, the
+//! entropy of each phrase slot and how long each stage took. Generated
+//! passphrases are never written to it.
+
+use crate::entropy::Entropy;
+use crate::sources::Sources;
+use clap::ArgMatches;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Arguments whose values must never appear in a transcript.
+const REDACTED_ARGS: [&str; 0] = [];
+
+#[derive(Debug, Serialize)]
+pub struct ConfigValue {
+    pub values: Vec<String>,
+    pub source: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceRecord {
+    pub name: String,
+    pub location: String,
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Determinism {
+    pub deterministic: bool,
+    pub seed_commitment: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Timing {
+    pub load_ms: u128,
+    pub generate_ms: u128,
+    pub total_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Transcript {
+    pub phraseforge_version: &'static str,
+    pub created_at_unix: u64,
+    pub config: BTreeMap<String, ConfigValue>,
+    pub sources: Vec<SourceRecord>,
+    pub determinism: Determinism,
+    pub entropy: Entropy,
+    pub phrase_count: usize,
+    pub timing: Timing,
+}
+
+/// Captures every argument clap resolved for this run, along with whether it
+/// came from the command line or a default.
+pub fn effective_config(matches: &ArgMatches) -> BTreeMap<String, ConfigValue> {
+    let mut config = BTreeMap::new();
+    for id in matches.ids() {
+        let name = id.as_str();
+        let values = if REDACTED_ARGS.contains(&name) {
+            vec!["<redacted>".to_string()]
+        } else {
+            matches
+                .get_raw(name)
+                .map(|raw| raw.map(|v| v.to_string_lossy().into_owned()).collect())
+                .unwrap_or_default()
+        };
+        let source = matches
+            .value_source(name)
+            .map(|s| format!("{:?}", s))
+            .unwrap_or_else(|| "Unknown".to_string());
+        config.insert(name.to_string(), ConfigValue { values, source });
+    }
+
+    if let Some((name, subcommand_matches)) = matches.subcommand() {
+        for (arg, value) in effective_config(subcommand_matches) {
+            config.insert(format!("{}.{}", name, arg), value);
+        }
+    }
+    config
+}
+
+pub fn sha256_file(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+/// A record for a local file, identified by its SHA-256.
+pub fn file_record(name: &str, path: &Path) -> SourceRecord {
+    SourceRecord {
+        name: name.to_string(),
+        location: path.display().to_string(),
+        sha256: sha256_file(path),
+    }
+}
+
+pub fn source_records(data_dir: &Path, sources: &Sources) -> Vec<SourceRecord> {
+    let mut sources: Vec<SourceRecord> = sources
+        .word_list_sources()
+        .into_iter()
+        .map(|(name, url)| SourceRecord {
+            name: name.to_lowercase().replace(' ', "-"),
+            location: url.to_string(),
+            sha256: None,
+        })
+        .collect();
+
+    for file in ["adjectives.txt", "nouns.txt", "verbs.txt", "adverbs.txt"] {
+        sources.push(file_record(file, &data_dir.join(file)));
+    }
+    sources
+}
+
+impl Transcript {
+    pub fn new(
+        matches: &ArgMatches,
+        sources: Vec<SourceRecord>,
+        entropy: Entropy,
+        phrase_count: usize,
+    ) -> Self {
+        let created_at_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Transcript {
+            phraseforge_version: env!("CARGO_PKG_VERSION"),
+            created_at_unix,
+            config: effective_config(matches),
+            sources,
+            determinism: Determinism {
+                deterministic: false,
+                seed_commitment: None,
+            },
+            entropy,
+            phrase_count,
+            timing: Timing::default(),
+        }
+    }
+
+    /// Fills in timing given when the run started and when setup (loading
+    /// word lists) finished; generation is assumed to have just ended.
+    pub fn record_timing(&mut self, started: Instant, loaded: Instant) {
+        self.timing.load_ms = loaded.duration_since(started).as_millis();
+        self.timing.generate_ms = loaded.elapsed().as_millis();
+        self.timing.total_ms = started.elapsed().as_millis();
+    }
+
+    pub fn save(&self, file_path: &Path) {
+        let file = File::create(file_path).expect("Failed to create transcript file");
+        serde_json::to_writer_pretty(file, self).expect("Failed to write transcript");
+    }
+}
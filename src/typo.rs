@@ -0,0 +1,92 @@
+//! `--typo-report`: which words of a phrase are one typo away from another.
+//!
+//! A word one edit (an insertion, deletion, substitution or swap of adjacent
+//! letters) from another word in the same pool turns a single slip into a
+//! different phrase the generator could just as well have produced. That
+//! matters when the phrase is dictated, or checked by a system that accepts
+//! near matches, since neither will catch the slip.
+
+use crate::generate::eligible;
+use crate::template::Slot;
+use crate::WordEntry;
+
+/// Whether `a` and `b` differ by exactly one edit, counting a swap of two
+/// adjacent letters as one.
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (shorter, longer) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    match longer.len() - shorter.len() {
+        0 => {
+            let differing: Vec<usize> = (0..a.len()).filter(|&i| a[i] != b[i]).collect();
+            match differing[..] {
+                [_] => true,
+                [i, j] => j == i + 1 && a[i] == b[j] && a[j] == b[i],
+                _ => false,
+            }
+        }
+        1 => {
+            let prefix = shorter
+                .iter()
+                .zip(longer.iter())
+                .take_while(|(x, y)| x == y)
+                .count();
+            shorter[prefix..] == longer[prefix + 1..]
+        }
+        _ => false,
+    }
+}
+
+/// The eligible words in `pool` one edit from `word`.
+pub fn neighbors(word: &str, pool: &[WordEntry], min_frequency: &u32) -> Vec<String> {
+    eligible(pool, min_frequency, None)
+        .filter(|entry| one_edit_apart(word, &entry.word))
+        .map(|entry| entry.word.clone())
+        .collect()
+}
+
+/// Report lines for a phrase's words, given as (slot, word, neighbors).
+pub fn report(words: &[(Slot, &str, Vec<String>)]) -> Vec<String> {
+    let mut lines: Vec<String> = words
+        .iter()
+        .filter(|(_, _, neighbors)| !neighbors.is_empty())
+        .map(|(slot, word, neighbors)| {
+            format!(
+                "{} '{}' is one typo from: {}",
+                slot.name(),
+                word,
+                neighbors.join(", ")
+            )
+        })
+        .collect();
+    lines.push(if lines.is_empty() {
+        "typo report: no word is one typo from another in its pool".to_string()
+    } else {
+        format!(
+            "warning: a single typo in {} of {} words gives another valid-looking phrase",
+            lines.len(),
+            words.len()
+        )
+    });
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_single_edits_and_adjacent_swaps() {
+        assert!(one_edit_apart("brave", "grave"));
+        assert!(one_edit_apart("brave", "braver"));
+        assert!(one_edit_apart("brave", "rave"));
+        assert!(one_edit_apart("brave", "bravr") && one_edit_apart("form", "from"));
+        assert!(!one_edit_apart("brave", "brave"));
+        assert!(!one_edit_apart("brave", "grace"));
+        assert!(!one_edit_apart("abcd", "dbca"));
+    }
+}
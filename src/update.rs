@@ -0,0 +1,161 @@
+//! `phraseforge update`: refreshes upstream sources only when they changed.
+//!
+//! Each source's `ETag` and `Last-Modified` headers are remembered in
+//! `sources.json` and sent back as conditional request headers, so an
+//! unchanged source costs one `304 Not Modified` round trip.
+
+use reqwest::blocking::Client;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const VALIDATORS_FILE: &str = "sources.json";
+
+/// HTTP cache validators for one source URL.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub enum Fetched {
+    Unchanged,
+    Changed {
+        body: Vec<u8>,
+        validators: Validators,
+    },
+}
+
+/// Validators keyed by source URL; empty if none have been saved yet.
+pub fn load_validators(data_dir: &Path) -> BTreeMap<String, Validators> {
+    fs::read_to_string(data_dir.join(VALIDATORS_FILE))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_validators(data_dir: &Path, validators: &BTreeMap<String, Validators>) {
+    let json = serde_json::to_string_pretty(validators).expect("Validators serialize");
+    write_atomically(&data_dir.join(VALIDATORS_FILE), json.as_bytes());
+}
+
+/// When the frequency list was downloaded or `update` last confirmed the
+/// sources current, whichever is later; `None` before either.
+pub fn last_refreshed(data_dir: &Path, frequency_list_file: &str) -> Option<SystemTime> {
+    [frequency_list_file, VALIDATORS_FILE]
+        .iter()
+        .filter_map(|file| {
+            fs::metadata(data_dir.join(file))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .max()
+}
+
+/// Downloads `url` unless the server confirms it still matches `previous`.
+pub fn fetch_if_changed(
+    client: &Client,
+    url: &str,
+    previous: Option<&Validators>,
+) -> Result<Fetched, String> {
+    let mut request = client.get(url);
+    if let Some(previous) = previous {
+        if let Some(etag) = &previous.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &previous.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request
+        .send()
+        .map_err(|e| format!("cannot reach {}: {}", url, e))?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::Unchanged);
+    }
+    if !response.status().is_success() {
+        return Err(format!("{} returned {}", url, response.status()));
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+    let body = response
+        .bytes()
+        .map_err(|e| format!("failed to read {}: {}", url, e))?
+        .to_vec();
+    Ok(Fetched::Changed { body, validators })
+}
+
+/// How often a rename denied by Windows is retried, and how long to wait
+/// between tries.
+const RENAME_ATTEMPTS: u32 = 10;
+const RENAME_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// `fs::rename`, retried for a second or so when Windows denies it. Virus
+/// scanners and the search indexer briefly open files that were just
+/// written, and Windows refuses to move an open file, so the first attempt
+/// right after a download or rebuild often fails for no lasting reason.
+pub fn rename(from: &Path, to: &Path) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match fs::rename(from, to) {
+            Err(e)
+                if cfg!(windows)
+                    && e.kind() == io::ErrorKind::PermissionDenied
+                    && attempt < RENAME_ATTEMPTS =>
+            {
+                attempt += 1;
+                thread::sleep(RENAME_RETRY_DELAY);
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Writes to a sibling temporary file and renames it over `path`, so readers
+/// see either the old contents or the new, never a partial file.
+pub fn write_atomically(path: &Path, bytes: &[u8]) {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, bytes).expect("Failed to write temporary file");
+    rename(Path::new(&temporary), path).expect("Failed to move file into place");
+}
+
+fn words_in(path: &Path) -> HashSet<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// A one-line summary of how a word list changed, e.g.
+/// `nouns.txt: 10234 words (+12, -3)`.
+pub fn describe_change(name: &str, old: &Path, new: &Path) -> String {
+    let before = words_in(old);
+    let after = words_in(new);
+    format!(
+        "{}: {} words (+{}, -{})",
+        name,
+        after.len(),
+        after.difference(&before).count(),
+        before.difference(&after).count()
+    )
+}
@@ -0,0 +1,173 @@
+//! British and American spelling variants.
+//!
+//! WordNet lists both spellings of many words ("color" and "colour"), so
+//! either can turn up in a phrase. An organization that wants its issued
+//! phrases to read consistently can keep one side of each pair.
+
+use crate::filter::WordFilter;
+use crate::PartOfSpeech;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spelling {
+    Us,
+    Uk,
+    Either,
+}
+
+impl Spelling {
+    pub const NAMES: [&'static str; 3] = ["us", "uk", "either"];
+
+    pub fn from_name(name: &str) -> Option<Spelling> {
+        match name {
+            "us" => Some(Spelling::Us),
+            "uk" => Some(Spelling::Uk),
+            "either" => Some(Spelling::Either),
+            _ => None,
+        }
+    }
+}
+
+/// American and British spellings of the same word. Only base forms are
+/// listed; plurals and verb forms follow from them. Pairs where the other
+/// spelling is also a word in its own right ("tire"/"tyre", "check"/"cheque")
+/// are left out.
+const VARIANTS: &[(&str, &str)] = &[
+    ("acknowledgment", "acknowledgement"),
+    ("aging", "ageing"),
+    ("airplane", "aeroplane"),
+    ("aluminum", "aluminium"),
+    ("analog", "analogue"),
+    ("analyze", "analyse"),
+    ("anemia", "anaemia"),
+    ("apologize", "apologise"),
+    ("ardor", "ardour"),
+    ("armor", "armour"),
+    ("armory", "armoury"),
+    ("artifact", "artefact"),
+    ("behavior", "behaviour"),
+    ("caliber", "calibre"),
+    ("candor", "candour"),
+    ("catalog", "catalogue"),
+    ("center", "centre"),
+    ("civilize", "civilise"),
+    ("clamor", "clamour"),
+    ("color", "colour"),
+    ("colorful", "colourful"),
+    ("counselor", "counsellor"),
+    ("cozy", "cosy"),
+    ("criticize", "criticise"),
+    ("defense", "defence"),
+    ("dialog", "dialogue"),
+    ("donut", "doughnut"),
+    ("emphasize", "emphasise"),
+    ("encyclopedia", "encyclopaedia"),
+    ("endeavor", "endeavour"),
+    ("enroll", "enrol"),
+    ("favor", "favour"),
+    ("favorite", "favourite"),
+    ("fervor", "fervour"),
+    ("fiber", "fibre"),
+    ("flavor", "flavour"),
+    ("fulfill", "fulfil"),
+    ("gray", "grey"),
+    ("harbor", "harbour"),
+    ("honor", "honour"),
+    ("humor", "humour"),
+    ("installment", "instalment"),
+    ("jeweler", "jeweller"),
+    ("jewelry", "jewellery"),
+    ("labor", "labour"),
+    ("likable", "likeable"),
+    ("liter", "litre"),
+    ("luster", "lustre"),
+    ("maneuver", "manoeuvre"),
+    ("marvelous", "marvellous"),
+    ("meager", "meagre"),
+    ("memorize", "memorise"),
+    ("mold", "mould"),
+    ("moldy", "mouldy"),
+    ("mustache", "moustache"),
+    ("neighbor", "neighbour"),
+    ("odor", "odour"),
+    ("offense", "offence"),
+    ("organize", "organise"),
+    ("pajamas", "pyjamas"),
+    ("paralyze", "paralyse"),
+    ("parlor", "parlour"),
+    ("plow", "plough"),
+    ("pretense", "pretence"),
+    ("realize", "realise"),
+    ("recognize", "recognise"),
+    ("rigor", "rigour"),
+    ("rumor", "rumour"),
+    ("saber", "sabre"),
+    ("savor", "savour"),
+    ("savory", "savoury"),
+    ("sizable", "sizeable"),
+    ("skeptic", "sceptic"),
+    ("skeptical", "sceptical"),
+    ("skillful", "skilful"),
+    ("smolder", "smoulder"),
+    ("somber", "sombre"),
+    ("specter", "spectre"),
+    ("splendor", "splendour"),
+    ("sulfur", "sulphur"),
+    ("summarize", "summarise"),
+    ("symbolize", "symbolise"),
+    ("theater", "theatre"),
+    ("tidbit", "titbit"),
+    ("traveler", "traveller"),
+    ("tumor", "tumour"),
+    ("valor", "valour"),
+    ("vapor", "vapour"),
+    ("vigor", "vigour"),
+    ("willful", "wilful"),
+    ("woolen", "woollen"),
+    ("yogurt", "yoghurt"),
+];
+
+/// Drops the spellings of the dialect not chosen.
+pub struct SpellingFilter {
+    spelling: Spelling,
+    excluded: HashSet<&'static str>,
+}
+
+impl SpellingFilter {
+    pub fn new(spelling: Spelling) -> Self {
+        let excluded = VARIANTS
+            .iter()
+            .filter_map(|&(us, uk)| match spelling {
+                Spelling::Us => Some(uk),
+                Spelling::Uk => Some(us),
+                Spelling::Either => None,
+            })
+            .collect();
+        SpellingFilter { spelling, excluded }
+    }
+}
+
+impl WordFilter for SpellingFilter {
+    fn name(&self) -> String {
+        format!("spelling {:?}", self.spelling).to_lowercase()
+    }
+
+    fn keep(&self, _pos: PartOfSpeech, word: &str) -> bool {
+        !self.excluded.contains(word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_spelling_belongs_to_one_pair() {
+        let mut seen = HashSet::new();
+        for &(us, uk) in VARIANTS {
+            assert_ne!(us, uk);
+            assert!(seen.insert(us), "{} listed twice", us);
+            assert!(seen.insert(uk), "{} listed twice", uk);
+        }
+    }
+}
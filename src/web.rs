@@ -0,0 +1,312 @@
+//! `phraseforge web`: a page in the browser for choosing a phrase, for
+//! people who would rather click than type commands.
+//!
+//! The page shows a few phrases, each with buttons to copy it, reroll it or
+//! pick it; the picked phrase is printed as the command line would have
+//! printed it. The server stops at the first pick under `--once`, and at
+//! `--timeout` whatever happens, so it never lingers. It listens on
+//! 127.0.0.1 only and every URL carries a random token, so neither other
+//! users of the machine nor other pages open in the browser can ask it for
+//! phrases, and the page loads nothing from anywhere else.
+
+use crate::codec::to_hex;
+use rand::Rng;
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TOKEN_BYTES: usize = 16;
+/// How often to look for a connection, and so how late a timeout can be.
+const POLL: Duration = Duration::from_millis(50);
+/// How long a browser has to send its request once connected.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// Larger requests aren't from the page.
+const MAX_REQUEST: usize = 16 * 1024;
+
+pub struct Server {
+    listener: TcpListener,
+    token: String,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+impl Server {
+    /// Listens on localhost, on `port` or, for 0, any free port.
+    pub fn bind(port: u16) -> io::Result<Server> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let mut token = [0; TOKEN_BYTES];
+        rand::rng().fill(&mut token[..]);
+        Ok(Server {
+            listener,
+            token: to_hex(&token),
+        })
+    }
+
+    /// The page's address, token included.
+    pub fn url(&self) -> io::Result<String> {
+        let port = self.listener.local_addr()?.port();
+        Ok(format!("http://127.0.0.1:{}/{}/", port, self.token))
+    }
+
+    /// Serves the page with `count` phrases from `generate` until the
+    /// timeout, or under `once` the first pick, calling `pick` with each
+    /// phrase picked. Returns how many were.
+    pub fn run(
+        &self,
+        count: usize,
+        once: bool,
+        timeout: Duration,
+        mut generate: impl FnMut() -> String,
+        mut pick: impl FnMut(&str),
+    ) -> io::Result<usize> {
+        let deadline = Instant::now() + timeout;
+        let prefix = format!("/{}/", self.token);
+        // Only phrases the page was shown can be picked.
+        let mut issued = HashSet::new();
+        let mut issue = |issued: &mut HashSet<String>| {
+            let phrase = generate();
+            issued.insert(phrase.clone());
+            phrase
+        };
+        let mut picks = 0;
+        self.listener.set_nonblocking(true)?;
+        while Instant::now() < deadline {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let Some(request) = read_request(&mut stream) else {
+                continue;
+            };
+            let mut done = false;
+            let response = match (request.method.as_str(), request.path.strip_prefix(&prefix)) {
+                ("GET", Some("")) => {
+                    let phrases: Vec<String> = (0..count).map(|_| issue(&mut issued)).collect();
+                    respond("200 OK", "text/html", &page(&phrases))
+                }
+                ("GET", Some("phrase")) => respond("200 OK", "text/plain", &issue(&mut issued)),
+                ("POST", Some("pick")) if issued.contains(&request.body) => {
+                    pick(&request.body);
+                    picks += 1;
+                    done = once;
+                    let note = if once {
+                        "Picked. You can close this page now."
+                    } else {
+                        "Picked."
+                    };
+                    respond("200 OK", "text/plain", note)
+                }
+                ("POST", Some("pick")) => respond(
+                    "400 Bad Request",
+                    "text/plain",
+                    "That phrase wasn't offered here.",
+                ),
+                _ => respond("404 Not Found", "text/plain", "Not found."),
+            };
+            let _ = stream.write_all(response.as_bytes());
+            if done {
+                break;
+            }
+        }
+        Ok(picks)
+    }
+}
+
+/// Reads one request; `None` if it is malformed, oversized or too slow.
+fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(READ_TIMEOUT)).ok()?;
+    let mut data = Vec::new();
+    let mut buffer = [0; 4096];
+    let header_end = loop {
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let n = stream.read(&mut buffer).ok()?;
+        if n == 0 || data.len() > MAX_REQUEST {
+            return None;
+        }
+        data.extend_from_slice(&buffer[..n]);
+    };
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let length: usize = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map_or(Some(0), |(_, value)| value.trim().parse().ok())?;
+    if length > MAX_REQUEST {
+        return None;
+    }
+    let mut body = data.split_off(header_end + 4);
+    while body.len() < length {
+        let n = stream.read(&mut buffer).ok()?;
+        if n == 0 {
+            return None;
+        }
+        body.extend_from_slice(&buffer[..n]);
+    }
+    body.truncate(length);
+    Some(Request {
+        method,
+        path,
+        body: String::from_utf8(body).ok()?,
+    })
+}
+
+fn respond(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\n\
+         Content-Type: {}; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Cache-Control: no-store\r\n\
+         Content-Security-Policy: default-src 'none'; script-src 'unsafe-inline'; style-src 'unsafe-inline'; connect-src 'self'\r\n\
+         Referrer-Policy: no-referrer\r\n\
+         X-Content-Type-Options: nosniff\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const PAGE_START: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>PhraseForge</title>
+<style>
+body { font-family: sans-serif; max-width: 40em; margin: 2em auto; padding: 0 1em; }
+li { list-style: none; margin: 1em 0; }
+code { display: block; font-size: 1.4em; margin-bottom: 0.3em; }
+button { font-size: 1em; margin-right: 0.5em; }
+</style>
+</head>
+<body>
+<h1>Pick a passphrase</h1>
+<p>Reroll any you don't like, then choose the one you'll remember best.</p>
+<ul>
+"#;
+
+const PAGE_END: &str = r#"</ul>
+<p id="status" role="status"></p>
+<script>
+const status = document.getElementById("status");
+for (const item of document.querySelectorAll("li")) {
+  const phrase = item.querySelector("code");
+  item.querySelector(".copy").onclick = async () => {
+    await navigator.clipboard.writeText(phrase.textContent);
+    status.textContent = "Copied.";
+  };
+  item.querySelector(".reroll").onclick = async () => {
+    phrase.textContent = await (await fetch("phrase")).text();
+  };
+  item.querySelector(".pick").onclick = async () => {
+    const response = await fetch("pick", { method: "POST", body: phrase.textContent });
+    status.textContent = await response.text();
+  };
+}
+</script>
+</body>
+</html>
+"#;
+
+fn page(phrases: &[String]) -> String {
+    let mut html = PAGE_START.to_string();
+    for phrase in phrases {
+        html.push_str(&format!(
+            "<li><code>{}</code><button class=\"copy\">Copy</button><button class=\"reroll\">Reroll</button><button class=\"pick\">Use this one</button></li>\n",
+            escape_html(phrase)
+        ));
+    }
+    html.push_str(PAGE_END);
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(url: &str, method: &str, path: &str, body: &str) -> String {
+        let address = url.trim_start_matches("http://").split('/').next().unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            address,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serves_phrases_until_one_is_picked() {
+        let server = Server::bind(0).unwrap();
+        let url = server.url().unwrap();
+        let prefix = format!("/{}/", server.token);
+        let client = thread::spawn(move || {
+            let page = send(&url, "GET", &prefix, "");
+            assert!(page.starts_with("HTTP/1.1 200"));
+            assert!(page.contains("<code>brave&lt;otters-1</code>"));
+            assert!(send(&url, "GET", "/", "").starts_with("HTTP/1.1 404"));
+            let reroll = send(&url, "GET", &format!("{}phrase", prefix), "");
+            assert!(reroll.ends_with("brave<otters-3"));
+            let pick = format!("{}pick", prefix);
+            assert!(send(&url, "POST", &pick, "never-offered").starts_with("HTTP/1.1 400"));
+            assert!(send(&url, "POST", &pick, "brave<otters-2").starts_with("HTTP/1.1 200"));
+        });
+
+        let mut n = 0;
+        let mut picked = Vec::new();
+        let picks = server
+            .run(
+                2,
+                true,
+                Duration::from_secs(30),
+                || {
+                    n += 1;
+                    format!("brave<otters-{}", n)
+                },
+                |phrase| picked.push(phrase.to_string()),
+            )
+            .unwrap();
+        client.join().unwrap();
+        assert_eq!(picks, 1);
+        assert_eq!(picked, ["brave<otters-2"]);
+    }
+}
@@ -0,0 +1,263 @@
+//! Parsing for WordNet's `index.*` and `data.*` files.
+//!
+//! The `index.*` files only carry lowercased lemmas, which is enough to build
+//! the word lists but loses everything else WordNet knows. The data files hold
+//! the synsets themselves, with lemmas in their original case.
+
+use crate::dictionary::Pools;
+use crate::PartOfSpeech;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// A relation from one synset to another, e.g. `@` for a hypernym.
+#[derive(Debug)]
+pub struct Pointer {
+    pub symbol: String,
+    pub offset: u64,
+}
+
+/// A single synset line from a `data.*` file.
+#[derive(Debug)]
+pub struct Synset {
+    pub offset: u64,
+    /// Lexicographer file number, e.g. 5 for `noun.animal`.
+    pub lex_filenum: u8,
+    pub words: Vec<String>,
+    pub pointers: Vec<Pointer>,
+    /// Definition followed by any example sentences.
+    pub gloss: String,
+}
+
+impl Synset {
+    /// Offsets of this synset's hypernyms, including instance hypernyms.
+    pub fn hypernyms(&self) -> impl Iterator<Item = u64> + '_ {
+        self.pointers
+            .iter()
+            .filter(|p| p.symbol == "@" || p.symbol == "@i")
+            .map(|p| p.offset)
+    }
+}
+
+/// Parses one synset line, returning `None` for the license header and any
+/// malformed lines.
+pub fn parse_synset(line: &str) -> Option<Synset> {
+    // The license header at the top of every data file is indented.
+    if line.starts_with(' ') {
+        return None;
+    }
+
+    let mut fields = line.split_whitespace();
+    let offset = fields.next()?.parse::<u64>().ok()?;
+    let lex_filenum = fields.next()?.parse::<u8>().ok()?;
+    let _ss_type = fields.next()?;
+    let word_count = usize::from_str_radix(fields.next()?, 16).ok()?;
+
+    let mut words = Vec::with_capacity(word_count);
+    for _ in 0..word_count {
+        words.push(fields.next()?.to_string());
+        let _lex_id = fields.next()?;
+    }
+
+    let pointer_count = fields.next()?.parse::<usize>().ok()?;
+    let mut pointers = Vec::with_capacity(pointer_count);
+    for _ in 0..pointer_count {
+        let symbol = fields.next()?.to_string();
+        let offset = fields.next()?.parse::<u64>().ok()?;
+        let _pos = fields.next()?;
+        let _source_target = fields.next()?;
+        pointers.push(Pointer { symbol, offset });
+    }
+
+    let gloss = line
+        .split_once(" | ")
+        .map(|(_, gloss)| gloss.trim().to_string())
+        .unwrap_or_default();
+
+    Some(Synset {
+        offset,
+        lex_filenum,
+        words,
+        pointers,
+        gloss,
+    })
+}
+
+pub fn read_synsets(data_file: &Path) -> Vec<Synset> {
+    let file = File::open(data_file).expect("Failed to open WordNet data file");
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| parse_synset(&line))
+        .collect()
+}
+
+/// Reads a data file into a map keyed by synset offset.
+pub fn synsets_by_offset(data_file: &Path) -> HashMap<u64, Synset> {
+    read_synsets(data_file)
+        .into_iter()
+        .map(|synset| (synset.offset, synset))
+        .collect()
+}
+
+/// Lemmas in an `index.*` file that `keep` accepts, each with its
+/// `tagsense_cnt`: how many of its senses were seen in WordNet's tagged
+/// corpus, a rough measure of how often the word is used as this part of
+/// speech.
+fn tagged_lemmas(index_file: &Path, keep: &(impl Fn(&str) -> bool + Sync)) -> HashMap<String, u32> {
+    let file = File::open(index_file).expect("Failed to open WordNet index file");
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.expect("Failed to read line"))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let lemma = *fields.first()?;
+            if !keep(lemma) {
+                return None;
+            }
+            // lemma pos synset_cnt p_cnt [ptr_symbol...] sense_cnt tagsense_cnt ...
+            let tagged_senses = fields
+                .get(3)
+                .and_then(|p_cnt| p_cnt.parse::<usize>().ok())
+                .and_then(|pointer_count| fields.get(5 + pointer_count))
+                .and_then(|tagsense_cnt| tagsense_cnt.parse().ok())
+                .unwrap_or(0);
+            Some((lemma.to_string(), tagged_senses))
+        })
+        .collect()
+}
+
+/// The lemmas of each `index.*` file in `dict_dir` that `keep` accepts, and
+/// the proper nouns in `data.noun`. The files parse independently, so they
+/// are read in parallel.
+pub fn pools(dict_dir: &Path, keep: impl Fn(&str) -> bool + Sync) -> Pools {
+    let (words, proper_nouns) = rayon::join(
+        || {
+            PartOfSpeech::ALL
+                .par_iter()
+                .map(|pos| {
+                    tagged_lemmas(
+                        &dict_dir.join(format!("index.{}", pos.wordnet_suffix())),
+                        &keep,
+                    )
+                })
+                .collect()
+        },
+        || proper_noun_lemmas(&dict_dir.join("data.noun")),
+    );
+    Pools {
+        words,
+        proper_nouns,
+    }
+}
+
+/// Maps each lemma in an `index.*` file to the offset of its most frequent
+/// sense, which WordNet lists first.
+pub fn primary_senses(index_file: &Path) -> HashMap<String, u64> {
+    let file = File::open(index_file).expect("Failed to open WordNet index file");
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.starts_with(' '))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // lemma pos synset_cnt p_cnt [ptr_symbol...] sense_cnt tagsense_cnt offset...
+            let pointer_count = fields.get(3)?.parse::<usize>().ok()?;
+            let offset = fields.get(6 + pointer_count)?.parse::<u64>().ok()?;
+            Some((fields[0].to_string(), offset))
+        })
+        .collect()
+}
+
+/// Maps each lemma in an `index.*` file to its `synset_cnt`, the number of
+/// senses it has in that part of speech.
+pub fn sense_counts(index_file: &Path) -> HashMap<String, u32> {
+    let file = File::open(index_file).expect("Failed to open WordNet index file");
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.starts_with(' '))
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let lemma = fields.next()?.to_string();
+            let synset_count = fields.nth(1)?.parse::<u32>().ok()?;
+            Some((lemma, synset_count))
+        })
+        .collect()
+}
+
+/// Lowercased lemmas that only ever appear capitalized in `data.noun`: names
+/// ("John"), places and acronyms ("NATO").
+pub fn proper_noun_lemmas(data_noun: &Path) -> HashSet<String> {
+    // lemma -> whether it was ever seen starting with a lowercase letter
+    let mut seen_lowercase: HashMap<String, bool> = HashMap::new();
+
+    for synset in read_synsets(data_noun) {
+        for word in synset.words {
+            let lowercase = word.chars().next().is_some_and(|c| c.is_lowercase());
+            *seen_lowercase.entry(word.to_lowercase()).or_insert(false) |= lowercase;
+        }
+    }
+
+    seen_lowercase
+        .into_iter()
+        .filter(|(_, lowercase)| !lowercase)
+        .map(|(lemma, _)| lemma)
+        .collect()
+}
+
+/// Lowercased lemmas of every synset at or below `roots` in the hypernym
+/// hierarchy of `data_noun`.
+pub fn hyponym_lemmas(data_noun: &Path, roots: &[u64]) -> HashSet<String> {
+    let synsets = read_synsets(data_noun);
+
+    let mut hyponyms: HashMap<u64, Vec<u64>> = HashMap::new();
+    for synset in &synsets {
+        for hypernym in synset.hypernyms() {
+            hyponyms.entry(hypernym).or_default().push(synset.offset);
+        }
+    }
+
+    let mut reachable: HashSet<u64> = HashSet::new();
+    let mut pending: Vec<u64> = roots.to_vec();
+    while let Some(offset) = pending.pop() {
+        if reachable.insert(offset) {
+            if let Some(children) = hyponyms.get(&offset) {
+                pending.extend(children);
+            }
+        }
+    }
+
+    synsets
+        .into_iter()
+        .filter(|synset| reachable.contains(&synset.offset))
+        .flat_map(|synset| synset.words)
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_synset_words() {
+        let line = "08294696 14 n 02 North_Atlantic_Treaty_Organization 0 NATO 0 001 @ 08294366 n 0000 | an international organization";
+        let synset = parse_synset(line).unwrap();
+        assert_eq!(synset.offset, 8294696);
+        assert_eq!(synset.lex_filenum, 14);
+        assert_eq!(
+            synset.words,
+            vec!["North_Atlantic_Treaty_Organization", "NATO"]
+        );
+        assert_eq!(synset.hypernyms().collect::<Vec<_>>(), vec![8294366]);
+        assert_eq!(synset.gloss, "an international organization");
+    }
+
+    #[test]
+    fn skips_license_header() {
+        assert!(parse_synset("  1 This software and database is being provided").is_none());
+    }
+}